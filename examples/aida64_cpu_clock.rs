@@ -16,9 +16,9 @@ async fn main() -> e_utils::AnyResult<()> {
     let mut results = TestResults::new();
     results.hw_type = HardwareType::CPU;
     results.sensor_type = SensorType::Clock;
-    let mut tester = Tester {
-      inner: Inner::AIDA64(hw::aida64::AIDA64::new()?),
-      core: TestCore {
+    let mut tester = Tester::new(
+      Inner::Backend(Box::new(hw::aida64::AIDA64::new()?)),
+      TestCore {
         results,
         params,
         core_count: 0,
@@ -27,7 +27,7 @@ async fn main() -> e_utils::AnyResult<()> {
         is_print: false,
         is_data: true,
       },
-    };
+    );
     let pids = hw::common::process::run("aida64.exe", std::env::current_dir()?)?;
     hw::aida64::AIDA64::test(100)?;
     tester.core.core_count = tester.inner.get_cpu_core_count().await?;
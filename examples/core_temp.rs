@@ -16,9 +16,9 @@ async fn main() -> e_utils::AnyResult<()> {
     let mut results = TestResults::new();
     results.hw_type = HardwareType::CPU;
     results.sensor_type = SensorType::Load;
-    let mut tester = Tester {
-      inner: Inner::CoreTemp(hw::core_temp::CoreTemp::new()?),
-      core: TestCore {
+    let mut tester = Tester::new(
+      Inner::Backend(Box::new(hw::core_temp::CoreTemp::new()?)),
+      TestCore {
         results,
         params,
         core_count: 0,
@@ -27,7 +27,7 @@ async fn main() -> e_utils::AnyResult<()> {
         is_print: false,
         is_data: true,
       },
-    };
+    );
     hw::core_temp::CoreTemp::clean()?;
     let pids = hw::common::process::run("CoreTemp.exe", std::env::current_dir()?)?;
     hw::core_temp::CoreTemp::test(100)?;
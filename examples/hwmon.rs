@@ -0,0 +1,36 @@
+#[tokio::main]
+async fn main() -> e_utils::AnyResult<()> {
+  #[cfg(all(feature = "hwmon", target_os = "linux"))]
+  {
+    use hw::api_test::Tester;
+    use hw::{
+      api_test::{HardwareType, Inner, SensorType, TestCore, TestParams, TestResults},
+      wmic::HardwareMonitor as _,
+    };
+    let params = TestParams {
+      test_secs: 3,
+      v1: 60.0,
+      v2: 20.0,
+      v3: 0.0,
+    };
+    let mut results = TestResults::new();
+    results.hw_type = HardwareType::CPU;
+    results.sensor_type = SensorType::Temperature;
+    let tester = Tester::new(
+      Inner::Backend(Box::new(hw::hwmon::HWMON::new()?)),
+      TestCore {
+        results,
+        params,
+        core_count: 0,
+        is_full: true,
+        is_check: false,
+        is_print: false,
+        is_data: true,
+      },
+    );
+    let mut tester = tester.run().await?;
+    tester.core.results.res = "PASS".to_string();
+    hw::p(tester.get_test_summary());
+  }
+  Ok(())
+}
@@ -16,9 +16,9 @@ async fn main() -> e_utils::AnyResult<()> {
     let mut results = TestResults::new();
     results.hw_type = HardwareType::CPU;
     results.sensor_type = SensorType::Clock;
-    let mut tester = Tester {
-      inner: Inner::OHM(hw::ohm::OHM::new()?),
-      core: TestCore {
+    let mut tester = Tester::new(
+      Inner::Backend(Box::new(hw::ohm::OHM::new()?)),
+      TestCore {
         results,
         params,
         core_count: 0,
@@ -27,7 +27,7 @@ async fn main() -> e_utils::AnyResult<()> {
         is_print: false,
         is_data: true,
       },
-    };
+    );
     let pids = hw::common::process::run("OpenHardwareMonitor.exe", std::env::current_dir()?)?;
     hw::ohm::OHM::test(100)?;
     tester.core.core_count = tester.inner.get_cpu_core_count().await?;
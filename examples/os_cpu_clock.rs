@@ -13,9 +13,9 @@ async fn main() -> e_utils::AnyResult<()> {
     let mut results = TestResults::new();
     results.hw_type = HardwareType::CPU;
     results.sensor_type = SensorType::Clock;
-    let mut tester = Tester {
-      inner: Inner::OS(hw::os::OS::new()),
-      core: TestCore {
+    let mut tester = Tester::new(
+      Inner::Backend(Box::new(hw::os::OS::new())),
+      TestCore {
         results,
         params,
         core_count: 0,
@@ -24,7 +24,7 @@ async fn main() -> e_utils::AnyResult<()> {
         is_print: false,
         is_data: true,
       },
-    };
+    );
     tester.core.core_count = tester.inner.get_cpu_core_count().await?;
     let load_handles = tester.spawn_load()?;
     let res = tester.run().await;
@@ -1,7 +1,7 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 use crate::{
-  api_test::{HardwareType, Sensor, SensorType},
+  api_test::{BackendFuture, ExeBackend, HardwareType, Sensor, SensorBackend, SensorType},
   wmic::HardwareMonitor,
 };
 use e_utils::{
@@ -19,8 +19,6 @@ pub type RawQuery = HashMap<String, Variant>;
 /// AIDA64
 pub struct AIDA64(WMIConnection);
 impl AIDA64 {
-  pub const EXE: &'static str = "AIDA64.exe";
-  pub const DIR: &'static str = "plugins/AIDA64";
   /// 获取WMI连接
   pub fn get(&self) -> &WMIConnection {
     &self.0
@@ -100,6 +98,7 @@ impl AIDA64 {
       SensorType::Control => (Some("S".into()), vec!["Label LIKE '%Control%'"].into_iter().map(String::from).collect()),
       SensorType::Level => (Some("S".into()), vec!["Label LIKE '%Level%'"].into_iter().map(String::from).collect()),
       SensorType::Throughput => (Some("S".into()), vec!["Label LIKE '%Throughput%'"].into_iter().map(String::from).collect()),
+      SensorType::Current => (None, vec!["Label LIKE '%Current%'"].into_iter().map(String::from).collect()),
       SensorType::SmallData | SensorType::GBSmallData => (Some("S".into()), vec!["Label LIKE '%Small%'"].into_iter().map(String::from).collect()),
       SensorType::ALL => (None, vec![]),
       SensorType::Unknown => (None, vec!["Type NOT IN ('S', 'T', 'F', 'V', 'P')"].into_iter().map(String::from).collect()),
@@ -131,6 +130,29 @@ impl AIDA64 {
     }
   }
 }
+impl SensorBackend for AIDA64 {
+  fn name(&self) -> &str {
+    "AIDA64"
+  }
+  fn prepare(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::start() })
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    crate::api_test::wait_ready::<Self>(100)
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move { self.a_query(hw_type, sensor_type).await })
+  }
+  fn teardown(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::shutdown() })
+  }
+}
+
+impl ExeBackend for AIDA64 {
+  const EXE: &'static str = "AIDA64.exe";
+  const DIR: &'static str = "plugins/AIDA64";
+}
+
 impl HardwareMonitor for AIDA64 {
   type HWType = HardwareType;
   type SensorType = Sensor;
@@ -0,0 +1,258 @@
+//! 传感器后端
+//!
+//! 每个监控实现 [`SensorBackend`], 按 准备 -> 等待就绪 -> 查询 -> 清理 的生命周期接入 [`Tester`](super::Tester);
+//! 后端按名称登记在全局注册表, `--api <名称>` 通过 [`create_backend`] 创建, 第三方 crate 可用 [`register_backend`] 注册自己的后端
+use super::{HardwareType, Sensor, SensorType};
+use e_utils::{once_cell::sync::Lazy, AnyResult};
+use std::{future::Future, pin::Pin, sync::RwLock};
+
+/// 后端返回的异步结果
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = AnyResult<T>> + 'a>>;
+/// 后端构造函数, 参数为 `--args` 中硬件与传感器之后的部分
+pub type BackendFactory = fn(&[String]) -> AnyResult<Box<dyn SensorBackend>>;
+
+/// 统一的传感器后端
+pub trait SensorBackend: std::fmt::Debug {
+  /// 注册名称
+  fn name(&self) -> &str;
+  /// 准备, 如启动外部监控程序
+  fn prepare(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Ok(()) })
+  }
+  /// 等待后端可读到数据
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Ok(()) })
+  }
+  /// 查询传感器
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>>;
+  /// 清理, 测试失败时同样会调用
+  fn teardown(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Ok(()) })
+  }
+}
+
+static BACKENDS: Lazy<RwLock<Vec<(&'static str, BackendFactory)>>> = Lazy::new(|| RwLock::new(builtin_backends()));
+
+/// 内置后端
+#[allow(unused_mut, clippy::vec_init_then_push)]
+fn builtin_backends() -> Vec<(&'static str, BackendFactory)> {
+  let mut list: Vec<(&'static str, BackendFactory)> = vec![];
+  #[cfg(all(feature = "ohm", target_os = "windows"))]
+  list.push(("OHM", boxed::<crate::ohm::OHM>));
+  #[cfg(all(feature = "lhm", target_os = "windows"))]
+  list.push(("LHM", boxed::<crate::lhm::LHM>));
+  #[cfg(all(feature = "aida64", target_os = "windows"))]
+  list.push(("AIDA64", boxed::<crate::aida64::AIDA64>));
+  #[cfg(feature = "os")]
+  list.push(("OS", |_| Ok(Box::new(crate::os::OS::new()))));
+  #[cfg(all(feature = "core-temp", target_os = "windows"))]
+  list.push(("CoreTemp", boxed::<crate::core_temp::CoreTemp>));
+  #[cfg(all(feature = "argus-monitor", target_os = "windows"))]
+  list.push(("ArgusMonitor", boxed::<crate::argus_monitor::ArgusMonitor>));
+  #[cfg(all(feature = "hwmon", target_os = "linux"))]
+  list.push(("HWMON", boxed::<crate::hwmon::HWMON>));
+  #[cfg(all(feature = "rapl", target_os = "linux"))]
+  list.push(("RAPL", boxed::<crate::rapl::RAPL>));
+  list.push((super::Mock::NAME, super::Mock::from_args));
+  list.push((super::Replay::NAME, super::Replay::from_args));
+  list
+}
+
+#[allow(dead_code)]
+fn boxed<T: crate::wmic::HardwareMonitor + SensorBackend + 'static>(_: &[String]) -> AnyResult<Box<dyn SensorBackend>> {
+  Ok(Box::new(T::new()?))
+}
+
+/// 注册后端, 名称不区分大小写, 同名时替换
+pub fn register_backend(name: &'static str, factory: BackendFactory) {
+  let mut list = BACKENDS.write().unwrap_or_else(|e| e.into_inner());
+  match list.iter_mut().find(|(v, _)| v.eq_ignore_ascii_case(name)) {
+    Some(entry) => *entry = (name, factory),
+    None => list.push((name, factory)),
+  }
+}
+
+/// 已注册的后端名称
+pub fn backend_names() -> Vec<&'static str> {
+  BACKENDS.read().unwrap_or_else(|e| e.into_inner()).iter().map(|(name, _)| *name).collect()
+}
+
+/// 按名称创建后端
+pub fn create_backend(name: &str, args: &[String]) -> AnyResult<Box<dyn SensorBackend>> {
+  let factory = BACKENDS
+    .read()
+    .unwrap_or_else(|e| e.into_inner())
+    .iter()
+    .find(|(v, _)| v.eq_ignore_ascii_case(name))
+    .map(|(_, factory)| *factory);
+  match factory {
+    Some(factory) => factory(args),
+    None => Err(format!("{} not supported, 可用: {}", name, backend_names().join(", ")).into()),
+  }
+}
+
+/// 在阻塞线程中轮询 [`HardwareMonitor::test`](crate::wmic::HardwareMonitor::test) 等待就绪, 不占用异步运行时, 截止时间可及时生效
+pub fn wait_ready<T: crate::wmic::HardwareMonitor + 'static>(count: u64) -> BackendFuture<'static, ()> {
+  Box::pin(async move {
+    tokio::task::spawn_blocking(move || T::test(count).map_err(|e| e.to_string())).await??;
+    Ok(())
+  })
+}
+
+/// 启动外部监控程序, 程序位于当前目录下的 dir
+#[cfg(feature = "system")]
+pub fn start_exe(exe: &str, dir: &str) -> AnyResult<()> {
+  let pids = crate::common::process::run(exe, std::env::current_dir()?.join(dir))?;
+  if pids.is_empty() {
+    return Err(format!("Task {} is empty", exe).into());
+  }
+  Ok(())
+}
+
+/// 由外部监控程序提供数据的后端, 共用启动与清理流程
+#[cfg(feature = "system")]
+pub trait ExeBackend: crate::wmic::HardwareMonitor {
+  /// 程序名
+  const EXE: &'static str;
+  /// 程序所在目录, 相对当前目录
+  const DIR: &'static str;
+  /// 启动程序
+  fn start() -> AnyResult<()> {
+    start_exe(Self::EXE, Self::DIR)
+  }
+  /// 结束程序并清理
+  fn shutdown() -> AnyResult<()> {
+    crate::common::process::kill_name(Self::EXE)?;
+    Self::stop()?;
+    Self::clean()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api_test::{Inner, TestCore, Tester};
+
+  #[derive(Debug, Default)]
+  struct Fixed(Vec<f64>, usize);
+  impl SensorBackend for Fixed {
+    fn name(&self) -> &str {
+      "Fixed"
+    }
+    fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+      Box::pin(async move {
+        let value = *self.0.get(self.1).ok_or("no value")?;
+        self.1 += 1;
+        Ok(vec![Sensor {
+          Name: "Fixed".into(),
+          Parent: hw_type.to_string(),
+          SensorType: sensor_type,
+          Value: value,
+          ..Default::default()
+        }])
+      })
+    }
+  }
+
+  #[derive(Debug)]
+  struct Broken;
+  impl SensorBackend for Broken {
+    fn name(&self) -> &str {
+      "Broken"
+    }
+    fn query(&mut self, _: HardwareType, _: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+      Box::pin(async { Err("query failed".into()) })
+    }
+    fn teardown(&mut self) -> BackendFuture<'_, ()> {
+      Box::pin(async { Err("teardown failed".into()) })
+    }
+  }
+
+  /// 记录是否已清理
+  #[derive(Debug, Default)]
+  struct Tracked(std::sync::Arc<std::sync::atomic::AtomicBool>);
+  impl SensorBackend for Tracked {
+    fn name(&self) -> &str {
+      "Tracked"
+    }
+    fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+      Box::pin(async move {
+        Ok(vec![Sensor {
+          Name: "Tracked".into(),
+          Parent: hw_type.to_string(),
+          SensorType: sensor_type,
+          Value: 41.0,
+          ..Default::default()
+        }])
+      })
+    }
+    fn teardown(&mut self) -> BackendFuture<'_, ()> {
+      self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+      Box::pin(async { Ok(()) })
+    }
+  }
+
+  fn fixed_tester(backend: Box<dyn SensorBackend>, test_secs: usize) -> Tester {
+    Tester::new(Inner::Backend(backend), TestCore::fixture(HardwareType::Mainboard, test_secs))
+  }
+
+  #[test]
+  fn test_registry() {
+    register_backend("fixed", |_| Ok(Box::new(Fixed(vec![41.0], 0))));
+    assert!(backend_names().contains(&"fixed"));
+    assert_eq!(create_backend("FIXED", &[]).unwrap().name(), "Fixed");
+    assert!(create_backend("missing", &[]).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_execute() {
+    let tester = fixed_tester(Box::new(Fixed(vec![38.0, 42.0], 0)), 2).execute().await.unwrap();
+    assert_eq!((tester.core.results.samples, tester.core.results.avg), (2, 40.0));
+    let err = fixed_tester(Box::new(Fixed(vec![50.0; 3], 0)), 3).execute().await.err().unwrap();
+    assert!(err.to_string().contains("测试失败"));
+    let err = fixed_tester(Box::new(Broken), 1).execute().await.err().unwrap();
+    assert_eq!(err.to_string(), "query failed");
+  }
+
+  /// 阻塞等待就绪的监控
+  struct SlowMonitor;
+  impl crate::wmic::HardwareMonitor for SlowMonitor {
+    const CON_QUERY: &'static str = "";
+    const HW_QUERY: &'static str = "";
+    const SENSOR_QUERY: &'static str = "";
+    type HWType = HardwareType;
+    type SensorType = Sensor;
+    fn new() -> AnyResult<Self> {
+      Ok(Self)
+    }
+    fn test(_: u64) -> AnyResult<()> {
+      std::thread::sleep(std::time::Duration::from_secs(2));
+      Ok(())
+    }
+    fn stop() -> AnyResult<()> {
+      Ok(())
+    }
+    fn clean() -> AnyResult<()> {
+      Ok(())
+    }
+  }
+
+  #[tokio::test]
+  async fn test_wait_ready() {
+    let start = std::time::Instant::now();
+    let res = tokio::time::timeout(std::time::Duration::from_millis(200), wait_ready::<SlowMonitor>(1)).await;
+    assert!(res.is_err() && start.elapsed() < std::time::Duration::from_secs(1));
+    assert!(wait_ready::<SlowMonitor>(1).await.is_ok());
+  }
+
+  #[tokio::test]
+  async fn test_execute_deadline() {
+    let backend = Tracked::default();
+    let torn_down = backend.0.clone();
+    let mut tester = fixed_tester(Box::new(backend), 10);
+    tester.set_deadline(Some(std::time::Instant::now() + std::time::Duration::from_millis(1500)));
+    let err = tester.execute().await.err().unwrap();
+    assert_eq!(err.to_string(), "测试超时");
+    assert!(torn_down.load(std::sync::atomic::Ordering::SeqCst));
+  }
+}
@@ -0,0 +1,304 @@
+//! 判定条件, 替代固定的 `目标 ± 误差` 与超出 2 次即失败
+//!
+//! 命令行 `--criteria` 为 `key=value` 列表, 未设置的条件保持原有行为:
+//! - `min=30` `max=85` 绝对上下限, 只设置一个即单边限制, 设置后忽略目标与误差
+//! - `pct=5` 百分比误差, 目标 ± 目标 × 5%, 不能与 `min`/`max` 同时使用
+//! - `avg` 只在结束时判定平均值
+//! - `p95=80` 结束时判定第 95 百分位不高于 80
+//! - `violations=2` 最多违规次数, 默认 2, `-` 不限; `consecutive=3` 最多连续违规次数; `ratio=10` 最大违规比例 %
+//! - `warmup=5` 忽略开头 5 次采样
+//! - `hysteresis=2` 回差, 违规后需回到范围内 2 以上才算恢复
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// 百分位上限
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PercentileLimit {
+  /// 百分位 0 ~ 100
+  pub p: f64,
+  pub max: f64,
+}
+
+/// 判定条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Criteria {
+  /// 绝对下限
+  pub min: Option<f64>,
+  /// 绝对上限
+  pub max: Option<f64>,
+  /// 百分比误差, 设置时替代绝对误差
+  pub tolerance_pct: Option<f64>,
+  /// 只判定平均值, 单个采样不计违规
+  pub avg_only: bool,
+  pub percentile: Option<PercentileLimit>,
+  /// 违规次数超过该值即失败
+  pub max_violations: Option<usize>,
+  /// 连续违规次数超过该值即失败
+  pub max_consecutive: Option<usize>,
+  /// 结束时违规比例 % 超过该值失败
+  pub max_ratio: Option<f64>,
+  /// 开头忽略的采样次数
+  pub warmup: usize,
+  /// 回差
+  pub hysteresis: f64,
+}
+impl Default for Criteria {
+  fn default() -> Self {
+    Self {
+      min: None,
+      max: None,
+      tolerance_pct: None,
+      avg_only: false,
+      percentile: None,
+      max_violations: Some(Self::MAX_VIOLATIONS),
+      max_consecutive: None,
+      max_ratio: None,
+      warmup: 0,
+      hysteresis: 0.0,
+    }
+  }
+}
+impl Criteria {
+  /// 默认最多违规次数
+  pub const MAX_VIOLATIONS: usize = 2;
+
+  /// 解析 `key=value` 列表, 设置了 consecutive 或 ratio 而未设置 violations 时不限违规次数
+  pub fn parse<T: AsRef<str>>(items: &[T]) -> AnyResult<Self> {
+    let mut res = Self::default();
+    let mut has_violations = false;
+    for item in items.iter().map(AsRef::as_ref).filter(|v| !v.trim().is_empty()) {
+      let (key, value) = item.split_once('=').unwrap_or((item, ""));
+      let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+      let num = || value.parse::<f64>().map_err(|_| format!("判定条件 {} 取值错误: {}", key, value));
+      let count = || value.parse::<usize>().map_err(|_| format!("判定条件 {} 取值错误: {}", key, value));
+      match key.as_str() {
+        "min" => res.min = Some(num()?),
+        "max" => res.max = Some(num()?),
+        "pct" => res.tolerance_pct = Some(num()?),
+        "avg" => res.avg_only = value.is_empty() || value.parse::<bool>().map_err(|_| format!("判定条件 avg 取值错误: {}", value))?,
+        "violations" => {
+          has_violations = true;
+          res.max_violations = if value == "-" { None } else { Some(count()?) };
+        }
+        "consecutive" => res.max_consecutive = Some(count()?),
+        "ratio" => res.max_ratio = Some(num()?),
+        "warmup" => res.warmup = count()?,
+        "hysteresis" => res.hysteresis = num()?,
+        k => match k.strip_prefix('p').and_then(|p| p.parse::<f64>().ok()).filter(|p| (0.0..=100.0).contains(p)) {
+          Some(p) => res.percentile = Some(PercentileLimit { p, max: num()? }),
+          None => return Err(format!("未知判定条件: {}", item).into()),
+        },
+      }
+    }
+    if res.tolerance_pct.is_some() && (res.min.is_some() || res.max.is_some()) {
+      return Err("判定条件 pct 不能与 min/max 同时使用".into());
+    }
+    if !has_violations && (res.max_consecutive.is_some() || res.max_ratio.is_some()) {
+      res.max_violations = None;
+    }
+    Ok(res)
+  }
+  /// 允许范围, 优先绝对上下限, 其次百分比误差, 最后为 目标 ± 误差
+  pub fn bounds(&self, target: f64, tolerance: f64) -> (f64, f64) {
+    if self.min.is_some() || self.max.is_some() {
+      (self.min.unwrap_or(f64::NEG_INFINITY), self.max.unwrap_or(f64::INFINITY))
+    } else {
+      let tolerance = self.tolerance_pct.map(|pct| (target * pct / 100.0).abs()).unwrap_or(tolerance);
+      (target - tolerance, target + tolerance)
+    }
+  }
+  /// 允许范围说明, 如 `35.0 ~ 45.0` 或 `≤ 85.0`
+  pub fn range_str(&self, target: f64, tolerance: f64) -> String {
+    match self.bounds(target, tolerance) {
+      (lo, hi) if lo.is_infinite() && hi.is_infinite() => "不限".to_string(),
+      (lo, hi) if lo.is_infinite() => format!("≤ {:.1}", hi),
+      (lo, hi) if hi.is_infinite() => format!("≥ {:.1}", lo),
+      (lo, hi) => format!("{:.1} ~ {:.1}", lo, hi),
+    }
+  }
+}
+impl fmt::Display for Criteria {
+  /// 与 [`Criteria::parse`] 相同的格式, 只列出非默认条件
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut items = vec![];
+    let default = Self::default();
+    if let Some(v) = self.min {
+      items.push(format!("min={}", v));
+    }
+    if let Some(v) = self.max {
+      items.push(format!("max={}", v));
+    }
+    if let Some(v) = self.tolerance_pct {
+      items.push(format!("pct={}", v));
+    }
+    if self.avg_only {
+      items.push("avg".to_string());
+    }
+    if let Some(v) = self.percentile {
+      items.push(format!("p{}={}", v.p, v.max));
+    }
+    let implied = self.max_violations.is_none() && (self.max_consecutive.is_some() || self.max_ratio.is_some());
+    if self.max_violations != default.max_violations && !implied {
+      items.push(format!("violations={}", self.max_violations.map(|v| v.to_string()).unwrap_or("-".into())));
+    }
+    if let Some(v) = self.max_consecutive {
+      items.push(format!("consecutive={}", v));
+    }
+    if let Some(v) = self.max_ratio {
+      items.push(format!("ratio={}", v));
+    }
+    if self.warmup > 0 {
+      items.push(format!("warmup={}", self.warmup));
+    }
+    if self.hysteresis > 0.0 {
+      items.push(format!("hysteresis={}", self.hysteresis));
+    }
+    write!(f, "{}", items.join(" "))
+  }
+}
+
+/// 第 p 百分位 (最近秩)
+pub fn percentile(values: &[f64], p: f64) -> Option<f64> {
+  if values.is_empty() {
+    return None;
+  }
+  let mut sorted = values.to_vec();
+  sorted.sort_by(f64::total_cmp);
+  let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+  Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[derive(Debug, Clone, Default)]
+struct Track {
+  violated: bool,
+  consecutive: usize,
+}
+
+/// 按传感器跟踪违规状态
+#[derive(Debug, Clone, Default)]
+pub struct ViolationTracker {
+  tracks: HashMap<String, Track>,
+}
+impl ViolationTracker {
+  /// 判定一个采样, 返回是否违规与该传感器当前连续违规次数
+  ///
+  /// 处于违规状态时, 需回到 `[lo + hysteresis, hi - hysteresis]` 内才算恢复
+  pub fn check(&mut self, key: &str, value: f64, (lo, hi): (f64, f64), hysteresis: f64) -> (bool, usize) {
+    let track = self.tracks.entry(key.to_string()).or_default();
+    let margin = if track.violated { hysteresis } else { 0.0 };
+    track.violated = value < lo + margin || value > hi - margin;
+    track.consecutive = if track.violated { track.consecutive + 1 } else { 0 };
+    (track.violated, track.consecutive)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api_test::{HardwareType, Sensor, SensorType, TestCore};
+
+  /// 目标 40 ± 5, 按秒送入取值后结束判定
+  fn check(values: &[f64], criteria: &[&str]) -> Result<TestCore, String> {
+    let (core, res) = run(values, criteria);
+    res.map(|_| core)
+  }
+
+  /// 同 [`check`], 失败时同样返回结果
+  fn run(values: &[f64], criteria: &[&str]) -> (TestCore, Result<(), String>) {
+    let mut core = TestCore::fixture(HardwareType::CPU, values.len());
+    core.results.criteria = Criteria::parse(criteria).unwrap();
+    for (i, value) in values.iter().enumerate() {
+      let sensor = Sensor {
+        Name: "Mock".into(),
+        SensorType: SensorType::Temperature,
+        Value: *value,
+        ..Default::default()
+      };
+      if let Err(e) = core.update_test_status(i, &[sensor]) {
+        // 同 Tester::sample, 失败时统计一次
+        core.update_stats();
+        return (core, Err(e.to_string()));
+      }
+    }
+    let res = core.finish().map_err(|e| e.to_string());
+    (core, res)
+  }
+
+  #[test]
+  fn test_parse() {
+    let criteria = Criteria::parse(&["max=85", "p95=80", "consecutive=3", "warmup=2", "avg"]).unwrap();
+    assert_eq!(criteria.percentile, Some(PercentileLimit { p: 95.0, max: 80.0 }));
+    assert_eq!((criteria.max_violations, criteria.max_consecutive, criteria.avg_only), (None, Some(3), true));
+    assert_eq!(criteria.to_string(), "max=85 avg p95=80 consecutive=3 warmup=2");
+    let items: Vec<String> = criteria.to_string().split(' ').map(String::from).collect();
+    assert_eq!(Criteria::parse(&items).unwrap(), criteria);
+    assert_eq!(Criteria::parse(&["violations=-"]).unwrap().max_violations, None);
+    assert_eq!(Criteria::parse::<&str>(&[]).unwrap(), Criteria::default());
+    assert!(Criteria::parse(&["p101=3"]).is_err());
+    assert!(Criteria::parse(&["max=x"]).is_err());
+    assert!(Criteria::parse(&["pct=10", "max=85"]).is_err());
+  }
+
+  #[test]
+  fn test_bounds() {
+    assert_eq!(Criteria::default().range_str(40.0, 5.0), "35.0 ~ 45.0");
+    let pct = Criteria::parse(&["pct=10"]).unwrap();
+    assert_eq!(pct.bounds(3000.0, 1.0), (2700.0, 3300.0));
+    assert_eq!(Criteria::parse(&["max=85"]).unwrap().range_str(40.0, 5.0), "≤ 85.0");
+    assert_eq!(percentile(&[5.0, 1.0, 4.0, 2.0, 3.0], 95.0), Some(5.0));
+    assert_eq!(percentile(&[5.0, 1.0, 4.0, 2.0, 3.0], 40.0), Some(2.0));
+  }
+
+  #[test]
+  fn test_hysteresis() {
+    let mut tracker = ViolationTracker::default();
+    let bounds = (f64::NEG_INFINITY, 80.0);
+    let res: Vec<(bool, usize)> = [81.0, 79.0, 77.0, 79.0, 81.0].iter().map(|v| tracker.check("cpu", *v, bounds, 2.0)).collect();
+    assert_eq!(res, vec![(true, 1), (true, 2), (false, 0), (false, 0), (true, 1)]);
+    // 传感器之间互不影响
+    assert_eq!(tracker.check("gpu", 79.0, bounds, 2.0), (false, 0));
+  }
+
+  #[test]
+  fn test_check() {
+    // 默认: 超出 2 次以上失败
+    assert!(check(&[46.0, 40.0, 34.0], &[]).is_ok());
+    assert!(check(&[46.0, 34.0, 50.0], &[]).err().unwrap().contains("错误次数超过 2 次"));
+    // 单边上限, 目标与误差不再生效
+    assert!(check(&[20.0, 30.0, 44.0], &["max=45"]).is_ok());
+    assert!(check(&[40.0, 45.0, 46.0, 47.0], &["pct=10"]).is_err());
+    // 预热采样不计入统计与判定
+    let core = check(&[90.0, 90.0, 90.0, 40.0], &["warmup=3"]).unwrap();
+    assert_eq!((core.results.samples, core.results.error_count), (1, 0));
+    // 只限制连续次数: 间隔违规可通过, 连续 3 次失败
+    let core = check(&[50.0, 40.0, 50.0, 40.0, 50.0, 40.0], &["consecutive=2"]).unwrap();
+    assert_eq!((core.results.max_consecutive, core.results.violation_ratio), (1, 50.0));
+    assert!(check(&[40.0, 50.0, 50.0, 50.0], &["consecutive=2"])
+      .err()
+      .unwrap()
+      .contains("连续错误超过 2 次"));
+    // 中途失败时同样计算违规比例与百分位
+    let (mut core, res) = run(&[46.0, 34.0, 50.0, 40.0], &["p50=45"]);
+    assert!(res.err().unwrap().contains("错误次数超过 2 次"));
+    assert_eq!((core.results.violation_ratio, core.results.percentile), (100.0, Some(46.0)));
+    core.is_full = true;
+    assert_eq!((core.results.samples, core.results.error_count), (3, 3));
+    assert!(core.get_test_summary().contains("样本数量: 3"));
+    // 回差: 回到 44 仍视为违规
+    assert!(check(&[46.0, 44.0, 46.0, 44.0], &["consecutive=3"]).is_ok());
+    assert!(check(&[46.0, 44.0, 46.0, 44.0], &["consecutive=3", "hysteresis=2"]).is_err());
+    // 百分位, 平均值与违规比例在结束时判定
+    let err = check(&[40.0, 41.0, 42.0, 43.0, 60.0], &["p80=45", "ratio=10"]).err().unwrap();
+    assert!(!err.contains("P80") && err.contains("违规比例 20.0% 超过 10.0%"));
+    let err = check(&[40.0, 41.0, 42.0, 43.0, 60.0], &["p100=45"]).err().unwrap();
+    assert!(err.contains("P100 60.0 高于 45.0"));
+    assert_eq!(check(&[40.0, 41.0, 42.0, 43.0, 60.0], &["p80=45"]).unwrap().results.percentile, Some(43.0));
+    assert!(check(&[30.0, 50.0, 30.0, 50.0], &["avg"]).is_ok());
+    assert!(check(&[46.0, 47.0, 46.0, 47.0], &["avg"])
+      .err()
+      .unwrap()
+      .contains("平均值 46.5 超出允许范围 35.0 ~ 45.0"));
+  }
+}
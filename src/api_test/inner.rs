@@ -19,55 +19,37 @@ pub const EXTEND1: i32 = 0x1;
 #[doc(hidden)]
 #[derive(Debug)]
 pub enum Inner {
-  #[cfg(all(feature = "lhm", target_os = "windows"))]
-  LHM(crate::lhm::LHM),
-  #[cfg(all(feature = "ohm", target_os = "windows"))]
-  OHM(crate::ohm::OHM),
-  #[cfg(all(feature = "aida64", target_os = "windows"))]
-  AIDA64(crate::aida64::AIDA64),
-  #[cfg(feature = "os")]
-  OS(crate::os::OS),
+  /// 传感器后端, 见 [`SensorBackend`](super::SensorBackend)
+  Backend(Box<dyn super::SensorBackend>),
   OSMore,
   Drive,
   FileInfo,
   OSSystem,
   OSOffice,
   Disk,
-  #[cfg(all(feature = "core-temp", target_os = "windows"))]
-  CoreTemp(crate::core_temp::CoreTemp),
+  Plan,
 }
 impl Inner {
-  /// 从API创建Inner
+  /// 从API创建Inner, args 为传给传感器后端的参数
   #[cfg(feature = "cli")]
-  pub fn from_api(api: crate::OptsApi) -> e_utils::AnyResult<Self> {
-    use crate::{wmic::HardwareMonitor as _, OptsApi};
+  pub fn from_api(api: &crate::OptsApi, args: &[String]) -> e_utils::AnyResult<Self> {
+    use crate::OptsApi;
     match api {
-      #[cfg(all(feature = "lhm", target_os = "windows"))]
-      OptsApi::LHM => Ok(Self::LHM(crate::lhm::LHM::new()?)),
-      #[cfg(not(all(feature = "lhm", target_os = "windows")))]
-      OptsApi::LHM => Err("LHM not supported".into()),
-      #[cfg(all(feature = "ohm", target_os = "windows"))]
-      OptsApi::OHM => Ok(Self::OHM(crate::ohm::OHM::new()?)),
-      #[cfg(not(all(feature = "ohm", target_os = "windows")))]
-      OptsApi::OHM => Err("OHM not supported".into()),
-      #[cfg(all(feature = "aida64", target_os = "windows"))]
-      OptsApi::AIDA64 => Ok(Self::AIDA64(crate::aida64::AIDA64::new()?)),
-      #[cfg(not(all(feature = "aida64", target_os = "windows")))]
-      OptsApi::AIDA64 => Err("AIDA64 not supported".into()),
-      #[cfg(feature = "os")]
-      OptsApi::OS => Ok(Self::OS(crate::os::OS::new())),
-      #[cfg(not(feature = "os"))]
-      OptsApi::OS => Err("OS not supported".into()),
       OptsApi::OSMore => Ok(Self::OSMore),
       OptsApi::Drive => Ok(Self::Drive),
       OptsApi::FileInfo => Ok(Self::FileInfo),
       OptsApi::OSSystem => Ok(Self::OSSystem),
       OptsApi::OSOffice => Ok(Self::OSOffice),
       OptsApi::Disk => Ok(Self::Disk),
-      #[cfg(all(feature = "core-temp", target_os = "windows"))]
-      OptsApi::CoreTemp => Ok(Self::CoreTemp(crate::core_temp::CoreTemp::new()?)),
-      #[cfg(not(all(feature = "core-temp", target_os = "windows")))]
-      OptsApi::CoreTemp => Err("CoreTemp not supported".into()),
+      OptsApi::Plan => Ok(Self::Plan),
+      api => Ok(Self::Backend(super::create_backend(&api.to_string(), args)?)),
+    }
+  }
+  /// 传感器后端
+  pub fn backend_mut(&mut self) -> e_utils::AnyResult<&mut dyn super::SensorBackend> {
+    match self {
+      Self::Backend(backend) => Ok(backend.as_mut()),
+      _ => Err("不支持".into()),
     }
   }
 }
@@ -168,7 +150,10 @@ impl LoadController {
     LOAD_CONTROLLER.set_loaded(loaded);
     if matches!(
       (hw_type, s_type),
-      (HardwareType::CPU, SensorType::Load | SensorType::Clock | SensorType::ClockAverage)
+      (
+        HardwareType::CPU,
+        SensorType::Load | SensorType::Clock | SensorType::ClockAverage | SensorType::Power
+      )
     ) {
       // 设置全局负载
       if loaded == 0.0 {
@@ -303,6 +288,20 @@ pub struct TestResults {
   pub error_count: usize,
   pub load: TestLoadResult,
   pub status: Vec<(String, f64)>,
+  /// 判定条件
+  #[serde(default)]
+  pub criteria: super::Criteria,
+  /// 最长连续违规次数
+  #[serde(default)]
+  pub max_consecutive: usize,
+  /// 违规比例 %
+  #[serde(default)]
+  pub violation_ratio: f64,
+  /// 判定条件中百分位的实际值
+  #[serde(default)]
+  pub percentile: Option<f64>,
+  #[serde(skip)]
+  pub tracker: super::ViolationTracker,
 }
 impl TestResults {
   pub fn new() -> Self {
@@ -321,6 +320,11 @@ impl TestResults {
       error_count: 0,
       load: TestLoadResult::default(),
       status: Vec::new(),
+      criteria: super::Criteria::default(),
+      max_consecutive: 0,
+      violation_ratio: 0.0,
+      percentile: None,
+      tracker: super::ViolationTracker::default(),
     }
   }
 }
@@ -379,6 +383,8 @@ pub enum HardwareType {
   HDD,
   #[strum(message = "内存")]
   RAM,
+  #[strum(message = "网络适配器")]
+  Network,
   #[strum(message = "未知")]
   Unknown,
 }
@@ -452,6 +458,9 @@ pub enum SensorType {
   /// 平均时钟速度传感器 (单位: 兆赫兹, 显示: "{value} MHz")
   #[strum(message = "平均频率")]
   ClockAverage,
+  /// 电流传感器 (单位: 安培, 显示: "{value} A")
+  #[strum(message = "电流")]
+  Current,
   /// 未知
   #[strum(message = "未知")]
   Unknown,
@@ -478,6 +487,7 @@ impl SensorType {
       SensorType::Control => "%",
       SensorType::Level => "%",
       SensorType::Power => "W",
+      SensorType::Current => "A",
       SensorType::Data => "B",
       SensorType::GBData | SensorType::GBSmallData => "GB",
       SensorType::Throughput => "B/s",
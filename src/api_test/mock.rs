@@ -0,0 +1,223 @@
+//! 脚本化模拟后端, 无硬件时验证判定与统计
+//!
+//! `--api Mock --args <硬件> <传感器> <脚本>`, 脚本为 JSON 文件或逗号分隔的取值 (`err` 表示注入错误):
+//! ```json
+//! {
+//!   "sensors": [
+//!     { "name": "CPU Package", "hw_type": "CPU", "sensor_type": "Temperature", "values": [40, 41, 55], "repeat": true }
+//!   ],
+//!   "errors": [3],
+//!   "delay_ms": 100,
+//!   "ready_error": null
+//! }
+//! ```
+use super::{BackendFuture, HardwareType, Sensor, SensorBackend, SensorType};
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 模拟传感器
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockSensor {
+  pub name: String,
+  /// 默认 `/mock/<序号>`
+  #[serde(default)]
+  pub identifier: String,
+  /// ALL 匹配任意查询
+  #[serde(default)]
+  pub hw_type: HardwareType,
+  /// ALL 匹配任意查询, 返回时使用查询的类型
+  #[serde(default)]
+  pub sensor_type: SensorType,
+  /// 每次查询依次取值
+  pub values: Vec<f64>,
+  /// 取完后循环, 否则保持最后一个值
+  #[serde(default)]
+  pub repeat: bool,
+}
+impl MockSensor {
+  pub fn value(&self, tick: usize) -> Option<f64> {
+    match self.values.len() {
+      0 => None,
+      len if self.repeat => Some(self.values[tick % len]),
+      len => Some(self.values[tick.min(len - 1)]),
+    }
+  }
+  fn matches(&self, hw_type: &HardwareType, sensor_type: &SensorType) -> bool {
+    (self.hw_type == HardwareType::ALL || *hw_type == HardwareType::ALL || self.hw_type == *hw_type)
+      && (self.sensor_type == SensorType::ALL || *sensor_type == SensorType::ALL || self.sensor_type == *sensor_type)
+  }
+}
+
+/// 模拟脚本
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockScript {
+  pub sensors: Vec<MockSensor>,
+  /// 返回错误的查询序号 (从 0 开始)
+  pub errors: Vec<usize>,
+  /// 每次查询前的延时
+  pub delay_ms: u64,
+  /// 等待就绪时返回的错误
+  pub ready_error: Option<String>,
+}
+impl MockScript {
+  /// 解析逗号分隔的取值, 如 `38,42,err,50`
+  pub fn parse_inline(v: &str) -> AnyResult<Self> {
+    let mut script = Self::default();
+    let mut values = vec![];
+    for (i, item) in v.split(',').map(str::trim).enumerate() {
+      if item.eq_ignore_ascii_case("err") {
+        script.errors.push(i);
+        values.push(values.last().copied().unwrap_or_default());
+      } else {
+        values.push(item.parse::<f64>().map_err(|_| format!("Mock 取值错误: {}", item))?);
+      }
+    }
+    script.sensors.push(MockSensor {
+      name: "Mock".into(),
+      identifier: String::new(),
+      hw_type: HardwareType::ALL,
+      sensor_type: SensorType::ALL,
+      values,
+      repeat: false,
+    });
+    Ok(script)
+  }
+  /// 参数为文件时读取 JSON, 否则按逗号分隔的取值解析
+  pub fn from_arg(v: &str) -> AnyResult<Self> {
+    if Path::new(v).is_file() {
+      Ok(serde_json::from_str(&std::fs::read_to_string(v)?)?)
+    } else {
+      Self::parse_inline(v)
+    }
+  }
+}
+
+/// 模拟后端
+#[derive(Debug, Clone, Default)]
+pub struct Mock {
+  pub script: MockScript,
+  /// 已查询次数
+  pub tick: usize,
+}
+impl Mock {
+  pub const NAME: &'static str = "Mock";
+  pub fn new(script: MockScript) -> Self {
+    Self { script, tick: 0 }
+  }
+  /// 注册表构造函数, 首个参数为脚本
+  pub fn from_args(args: &[String]) -> AnyResult<Box<dyn SensorBackend>> {
+    let arg = args.first().ok_or("Mock 缺少脚本: JSON 文件或逗号分隔的取值")?;
+    Ok(Box::new(Self::new(MockScript::from_arg(arg)?)))
+  }
+  /// 同步查询, 每次调用推进一次
+  pub fn next(&mut self, hw_type: &HardwareType, sensor_type: &SensorType) -> AnyResult<Vec<Sensor>> {
+    let tick = self.tick;
+    self.tick += 1;
+    if self.script.errors.contains(&tick) {
+      return Err(format!("Mock 注入错误 (第 {} 次查询)", tick + 1).into());
+    }
+    let sensors: Vec<Sensor> = self
+      .script
+      .sensors
+      .iter()
+      .enumerate()
+      .filter(|(_, s)| s.matches(hw_type, sensor_type))
+      .filter_map(|(i, s)| {
+        let value = s.value(tick)?;
+        let seen = || (0..=tick).filter_map(|t| s.value(t));
+        let st = Some(&s.sensor_type).filter(|v| **v != SensorType::ALL).unwrap_or(sensor_type);
+        let hw = Some(&s.hw_type).filter(|v| **v != HardwareType::ALL).unwrap_or(hw_type);
+        Some(Sensor {
+          Name: s.name.clone(),
+          Identifier: Some(s.identifier.clone()).filter(|v| !v.is_empty()).unwrap_or_else(|| format!("/mock/{}", i)),
+          _SensorType: st.to_string(),
+          SensorType: st.clone(),
+          Parent: hw.to_string(),
+          Value: value,
+          Min: seen().fold(f64::MAX, f64::min),
+          Max: seen().fold(f64::MIN, f64::max),
+          Index: i as i32,
+          data: String::new(),
+        })
+      })
+      .collect();
+    if sensors.is_empty() {
+      return Err(format!("Mock No sensors found for {} {}", hw_type, sensor_type).into());
+    }
+    Ok(sensors)
+  }
+}
+impl SensorBackend for Mock {
+  fn name(&self) -> &str {
+    Self::NAME
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async {
+      match &self.script.ready_error {
+        Some(e) => Err(e.clone().into()),
+        None => Ok(()),
+      }
+    })
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move {
+      if self.script.delay_ms > 0 {
+        tokio::time::sleep(std::time::Duration::from_millis(self.script.delay_ms)).await;
+      }
+      self.next(&hw_type, &sensor_type)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api_test::{Inner, TestCore, Tester};
+
+  fn mock_tester(script: &str, test_secs: usize, is_check: bool) -> Tester {
+    let mut core = TestCore::fixture(HardwareType::CPU, test_secs);
+    core.is_full = true;
+    core.set_check(is_check);
+    Tester::new(Inner::Backend(Box::new(Mock::new(MockScript::parse_inline(script).unwrap()))), core)
+  }
+
+  #[test]
+  fn test_script() {
+    let script = MockScript::parse_inline("38, 42, err").unwrap();
+    assert_eq!((script.sensors[0].values.clone(), script.errors.clone()), (vec![38.0, 42.0, 42.0], vec![2]));
+    assert!(MockScript::parse_inline("38,x").is_err());
+    let script: MockScript = serde_json::from_str(
+      r#"{"sensors":[{"name":"Fan","hw_type":"Mainboard","sensor_type":"Fan","values":[1200,1300],"repeat":true},
+        {"name":"Core","hw_type":"CPU","sensor_type":"Temperature","values":[50]}]}"#,
+    )
+    .unwrap();
+    let mut mock = Mock::new(script);
+    let fans: Vec<f64> = (0..3).map(|_| mock.next(&HardwareType::ALL, &SensorType::Fan).unwrap()[0].Value).collect();
+    assert_eq!(fans, vec![1200.0, 1300.0, 1200.0]);
+    let cpu = mock.next(&HardwareType::CPU, &SensorType::ALL).unwrap();
+    assert_eq!((cpu.len(), cpu[0].Name.as_str(), cpu[0].Identifier.as_str()), (1, "Core", "/mock/1"));
+    assert!(mock.next(&HardwareType::RAM, &SensorType::ALL).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_statistics() {
+    let tester = mock_tester("38,42,40,err", 4, false).run().await.ok().unwrap();
+    let results = &tester.core.results;
+    assert_eq!((results.samples, results.min, results.max, results.avg), (3, 38.0, 42.0, 40.0));
+    assert_eq!(results.std_deviation(), 2.0);
+    assert!(tester.get_test_summary().contains("最高: 42.0"));
+  }
+
+  #[tokio::test]
+  async fn test_three_strikes() {
+    // 两次超出范围仍可通过, 第三次失败
+    let tester = mock_tester("46,40,34,40", 4, true).run().await.ok().unwrap();
+    assert_eq!(tester.core.results.error_count, 2);
+    let err = mock_tester("46,34,50", 3, true).run().await.err().unwrap().to_string();
+    assert!(err.contains("当前Mock: 50.0") && err.contains("连续错误: 3 次") && err.contains("允许范围: 35.0 ~ 45.0"));
+    // 检查模式下查询错误直接失败
+    assert!(mock_tester("40,err", 2, true).run().await.is_err());
+  }
+}
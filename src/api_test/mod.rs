@@ -1,5 +1,15 @@
+mod backend;
+mod criteria;
 mod inner;
+mod mock;
+mod record;
+mod replay;
+pub use backend::*;
+pub use criteria::*;
 pub use inner::*;
+pub use mock::*;
+pub use record::*;
+pub use replay::*;
 use std::sync::atomic::Ordering;
 
 use serde::Serialize;
@@ -10,6 +20,12 @@ pub struct Tester {
   #[serde(skip)]
   pub inner: Inner,
   pub core: TestCore,
+  /// 采样记录, 见 `--record`
+  #[serde(skip)]
+  recorder: Option<Recorder>,
+  /// 截止时间, 到期后停止采样并清理
+  #[serde(skip)]
+  deadline: Option<std::time::Instant>,
 }
 
 #[derive(Serialize)]
@@ -23,6 +39,24 @@ pub struct TestCore {
   pub is_data: bool,
 }
 impl Tester {
+  pub fn new(inner: Inner, core: TestCore) -> Self {
+    Self {
+      inner,
+      core,
+      recorder: None,
+      deadline: None,
+    }
+  }
+  /// 设置采样记录
+  pub fn set_recorder(&mut self, recorder: Option<Recorder>) -> &mut Self {
+    self.recorder = recorder;
+    self
+  }
+  /// 设置截止时间
+  pub fn set_deadline(&mut self, deadline: Option<std::time::Instant>) -> &mut Self {
+    self.deadline = deadline;
+    self
+  }
   #[cfg(feature = "cli")]
   pub fn from_opts(op: &crate::cli::Opts) -> e_utils::AnyResult<Self> {
     use std::str::FromStr as _;
@@ -40,8 +74,14 @@ impl Tester {
     results.api = op.api.to_string();
     results.hw_type = hw_type;
     results.sensor_type = sensor_type;
+    results.criteria = Criteria::parse(&op.criteria)?;
+    let inner = Inner::from_api(&op.api, op.args.get(2..).unwrap_or_default())?;
+    let recorder = match (&op.record, &inner) {
+      (Some(path), Inner::Backend(_)) => Some(Recorder::create(path, op.record_rotate * 1024 * 1024)?),
+      _ => None,
+    };
     Ok(Self {
-      inner: Inner::from_api(op.api)?,
+      inner,
       core: TestCore {
         results,
         params,
@@ -51,9 +91,43 @@ impl Tester {
         is_print,
         is_data,
       },
+      recorder,
+      deadline: op.deadline,
     })
   }
 }
+
+/// 在截止时间前完成, 否则返回超时错误
+async fn until<T>(deadline: Option<std::time::Instant>, fut: impl std::future::Future<Output = e_utils::AnyResult<T>>) -> e_utils::AnyResult<T> {
+  match deadline {
+    Some(deadline) => tokio::time::timeout_at(deadline.into(), fut).await.unwrap_or_else(|_| Err("测试超时".into())),
+    None => fut.await,
+  }
+}
+
+#[cfg(test)]
+impl TestCore {
+  /// 测试用: 温度传感器, 目标 40 ± 5, 检查模式
+  pub(crate) fn fixture(hw_type: HardwareType, test_secs: usize) -> Self {
+    let mut results = TestResults::new();
+    results.hw_type = hw_type;
+    results.sensor_type = SensorType::Temperature;
+    Self {
+      results,
+      params: TestParams {
+        test_secs,
+        v1: 40.0,
+        v2: 5.0,
+        v3: 0.0,
+      },
+      core_count: 0,
+      is_full: false,
+      is_check: true,
+      is_print: false,
+      is_data: false,
+    }
+  }
+}
 impl TestCore {
   pub fn set_check(&mut self, is_check: bool) -> &mut Self {
     self.is_check = is_check;
@@ -79,7 +153,8 @@ impl TestCore {
       "\n=== 开始 {} {} {} ===\n\
        --- 传感器 -> {} {} {} ---\n\
        目标: {:.1}\n\
-       允许误差: ±{:.1}\n\
+       允许{}\n\
+       {}\
        测试时长: {} 秒\n\
        ====================================",
       self.hw_str(),
@@ -89,10 +164,39 @@ impl TestCore {
       self.sensor_name(),
       self.sensor_unit(),
       self.params.v1,
-      self.params.v2,
+      self.tolerance_str(),
+      self.criteria_str(),
       self.params.test_secs
     )
   }
+  /// `误差: ±v2`, 判定条件设置了绝对上下限或百分比误差时为 `范围: ...`
+  pub fn tolerance_str(&self) -> String {
+    if self.is_custom_range() {
+      format!("范围: {}", self.results.criteria.range_str(self.params.v1, self.params.v2))
+    } else {
+      format!("误差: ±{:.1}", self.params.v2)
+    }
+  }
+  fn is_custom_range(&self) -> bool {
+    let criteria = &self.results.criteria;
+    criteria.min.is_some() || criteria.max.is_some() || criteria.tolerance_pct.is_some()
+  }
+  /// 误差行, 已另行列出允许范围, 自定义范围时省略
+  fn tolerance_line(&self, prefix: &str) -> String {
+    if self.is_custom_range() {
+      String::new()
+    } else {
+      format!("{}{}\n", prefix, self.tolerance_str())
+    }
+  }
+  /// 非默认判定条件的说明行
+  fn criteria_str(&self) -> String {
+    if self.results.criteria == Criteria::default() {
+      String::new()
+    } else {
+      format!("判定条件: {}\n", self.results.criteria)
+    }
+  }
 
   pub fn get_test_summary(&self) -> String {
     let stats_info = if self.is_full {
@@ -106,6 +210,19 @@ impl TestCore {
     } else {
       String::new()
     };
+    let criteria_info = match (self.criteria_str(), self.results.percentile) {
+      (s, _) if s.is_empty() => s,
+      (s, percentile) => format!(
+        "{}最长连续违规: {}\n违规比例: {:.1}%\n{}",
+        s,
+        self.results.max_consecutive,
+        self.results.violation_ratio,
+        percentile
+          .zip(self.results.criteria.percentile)
+          .map(|(v, limit)| format!("P{}: {:.1} {}\n", limit.p, v, self.sensor_unit()))
+          .unwrap_or_default()
+      ),
+    };
 
     format!(
       "\n=== 总结 -> {} {} ===\n\
@@ -119,10 +236,11 @@ impl TestCore {
          最高: {:.1} {}\n\
          次数: {}\n\
          错误次数: {}\n\
+         {}\
          负载: {:.1}%\n\
          平均负载: {:.1}%\n\
-         允许误差: ±{:.1}\n\
-         允许范围: {:.1} ~ {:.1} {}\n\
+         {}\
+         允许范围: {} {}\n\
          ====================\n",
       self.hw_str(),
       self.hw_name(),
@@ -142,17 +260,25 @@ impl TestCore {
       self.sensor_unit(),
       self.params.test_secs,
       self.results.error_count,
+      criteria_info,
       self.params.v3,
       self.results.load.avg,
-      self.params.v2,
-      self.params.v1 - self.params.v2,
-      self.params.v1 + self.params.v2,
+      self.tolerance_line("允许"),
+      self.results.criteria.range_str(self.params.v1, self.params.v2),
       self.sensor_unit(),
     )
   }
 
   pub fn update_test_status(&mut self, current_sec: usize, sensors: &[Sensor]) -> e_utils::AnyResult<()> {
     crate::dp(format!("\n--- 第 {} 秒{}状态 ---", current_sec + 1, self.hw_name()));
+    let criteria = self.results.criteria.clone();
+    if current_sec < criteria.warmup {
+      for sensor in sensors {
+        crate::p(format!("{} - 当前={:.1} {} 预热, 不计入", sensor.Name, sensor.Value, sensor.sensor_unit()));
+      }
+      return Ok(());
+    }
+    let bounds = criteria.bounds(self.params.v1, self.params.v2);
 
     for (_idx, sensor) in sensors.iter().enumerate() {
       self.results.update(sensor.Value);
@@ -167,42 +293,50 @@ impl TestCore {
       } else {
         String::new()
       };
-      let check = if self.is_check {
-        format!("误差: ±{:.1}", self.params.v2,)
-      } else {
-        String::new()
-      };
+      let check = if self.is_check { self.tolerance_str() } else { String::new() };
       let s = format!("{} - 当前={:.1} {} {}{}", sensor.Name, sensor.Value, sensor.sensor_unit(), full, check);
       crate::p(s);
-      if self.is_check && is_value_out_of_range(sensor.Value, self.params.v1, self.params.v2) {
+      if !self.is_check || criteria.avg_only {
+        continue;
+      }
+      let key = if sensor.Identifier.is_empty() { &sensor.Name } else { &sensor.Identifier };
+      let (violated, consecutive) = self.results.tracker.check(key, sensor.Value, bounds, criteria.hysteresis);
+      self.results.max_consecutive = self.results.max_consecutive.max(consecutive);
+      if violated {
         self.results.error_count += 1;
         crate::p(format!("警告：{}超出允许范围！", sensor.Name));
 
-        if self.results.error_count > 2 {
-          let err = format!(
-            "{} 测试失败：\n\
-            - 数据: {}\n\
-            - 当前{}: {:.1} {}\n\
-            - 目标{}: {:.1} {}\n\
-            - 连续错误: {} 次\n\
-            - 误差: ±{:.1}\n\
-            - 允许范围: {:.1} ~ {:.1} {}",
-            self.hw_name(),
-            sensor.Name,
-            self.results.data,
-            sensor.Value,
-            sensor.sensor_unit(),
-            sensor.Name,
-            self.params.v1,
-            sensor.sensor_unit(),
-            self.params.v1 - self.params.v2,
-            self.params.v1 + self.params.v2,
-            sensor.sensor_unit(),
-            self.params.v2,
-            self.results.error_count
-          );
-          return Err(err.into());
-        }
+        let reason = match (criteria.max_violations, criteria.max_consecutive) {
+          (Some(max), _) if self.results.error_count > max => format!("错误次数超过 {} 次", max),
+          (_, Some(max)) if consecutive > max => format!("连续错误超过 {} 次", max),
+          _ => continue,
+        };
+        let err = format!(
+          "{} 测试失败：\n\
+          - 判定: {}\n\
+          - 数据: {}\n\
+          - 当前{}: {:.1} {}\n\
+          - 目标{}: {:.1} {}\n\
+          - 连续错误: {} 次\n\
+          - 错误次数: {} 次\n\
+          {}\
+          - 允许范围: {} {}",
+          self.hw_name(),
+          reason,
+          self.results.data,
+          sensor.Name,
+          sensor.Value,
+          sensor.sensor_unit(),
+          sensor.Name,
+          self.params.v1,
+          sensor.sensor_unit(),
+          consecutive,
+          self.results.error_count,
+          self.tolerance_line("- "),
+          criteria.range_str(self.params.v1, self.params.v2),
+          sensor.sensor_unit(),
+        );
+        return Err(err.into());
       }
     }
     crate::p("--------------------------------");
@@ -215,6 +349,45 @@ impl TestCore {
     ));
     Ok(())
   }
+  /// 计算违规比例与百分位, 采样中途失败时同样调用
+  pub fn update_stats(&mut self) {
+    if self.results.samples > 0 {
+      self.results.violation_ratio = self.results.error_count as f64 * 100.0 / self.results.samples as f64;
+    }
+    let values: Vec<f64> = self.results.status.iter().map(|v| v.1).collect();
+    self.results.percentile = self.results.criteria.percentile.and_then(|limit| percentile(&values, limit.p));
+  }
+  /// 采样结束后计算违规比例与百分位, 检查模式下按平均值, 百分位与违规比例判定
+  pub fn finish(&mut self) -> e_utils::AnyResult<()> {
+    self.update_stats();
+    let criteria = self.results.criteria.clone();
+    let samples = self.results.samples;
+    if !self.is_check || samples == 0 {
+      return Ok(());
+    }
+    let mut reasons = vec![];
+    let (lo, hi) = criteria.bounds(self.params.v1, self.params.v2);
+    let avg = self.results.total / samples as f64;
+    if criteria.avg_only && (avg < lo || avg > hi) {
+      reasons.push(format!(
+        "平均值 {:.1} 超出允许范围 {} {}",
+        avg,
+        criteria.range_str(self.params.v1, self.params.v2),
+        self.sensor_unit()
+      ));
+    }
+    if let Some((limit, v)) = criteria.percentile.zip(self.results.percentile).filter(|(limit, v)| *v > limit.max) {
+      reasons.push(format!("P{} {:.1} 高于 {:.1} {}", limit.p, v, limit.max, self.sensor_unit()));
+    }
+    if let Some(max) = criteria.max_ratio.filter(|max| self.results.violation_ratio > *max) {
+      reasons.push(format!("违规比例 {:.1}% 超过 {:.1}%", self.results.violation_ratio, max));
+    }
+    if reasons.is_empty() {
+      Ok(())
+    } else {
+      Err(format!("{} {} 测试失败：\n- {}", self.hw_name(), self.sensor_name(), reasons.join("\n- ")).into())
+    }
+  }
 }
 /// 运行
 impl Tester {
@@ -234,35 +407,34 @@ impl Tester {
       self.core.params.v3,
     )
   }
-  #[cfg(any(
-    all(feature = "ohm", target_os = "windows"),
-    all(feature = "aida64", target_os = "windows"),
-    feature = "os",
-    all(feature = "core-temp", target_os = "windows"),
-    all(feature = "lhm", target_os = "windows")
-  ))]
+  /// 按秒采样 test_secs 次
   pub async fn run(mut self) -> e_utils::AnyResult<Self> {
+    self.sample().await?;
+    Ok(self)
+  }
+  /// 采样并判定, 失败时输出截至失败的总结
+  async fn sample(&mut self) -> e_utils::AnyResult<()> {
+    let res = self.sample_ticks().await;
+    if res.is_err() {
+      self.core.update_stats();
+      self.core.results.res = "FAIL".to_string();
+      crate::p(self.get_test_summary());
+    }
+    res
+  }
+  async fn sample_ticks(&mut self) -> e_utils::AnyResult<()> {
+    let backend = self.inner.backend_mut()?;
     for i in 0..self.core.params.test_secs {
-      tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-      let res: e_utils::AnyResult<Vec<Sensor>> = match &mut self.inner {
-        #[cfg(all(feature = "ohm", target_os = "windows"))]
-        Inner::OHM(ohm) => ohm
-          .a_query(self.core.results.hw_type.clone(), self.core.results.sensor_type.clone())
-          .await
-          .map(|v| v.into_iter().filter(|v| v.Name != "Bus Speed").collect()),
-        #[cfg(all(feature = "lhm", target_os = "windows"))]
-        Inner::LHM(lhm) => lhm
-          .a_query(self.core.results.hw_type.clone(), self.core.results.sensor_type.clone())
-          .await
-          .map(|v| v.into_iter().filter(|v| v.Name != "Bus Speed").collect()),
-        #[cfg(all(feature = "aida64", target_os = "windows"))]
-        Inner::AIDA64(aida64) => aida64.a_query(self.core.results.hw_type.clone(), self.core.results.sensor_type.clone()).await,
-        #[cfg(feature = "os")]
-        Inner::OS(os) => os.query(self.core.results.hw_type.clone(), self.core.results.sensor_type.clone()),
-        #[cfg(all(feature = "core-temp", target_os = "windows"))]
-        Inner::CoreTemp(core_temp) => core_temp.query(self.core.results.hw_type.clone(), self.core.results.sensor_type.clone()),
-        _ => return Err("不支持".into()),
+      let tick = async {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        Ok(backend.query(self.core.results.hw_type.clone(), self.core.results.sensor_type.clone()).await)
       };
+      let res = until(self.deadline, tick).await?;
+      if let Some(recorder) = &mut self.recorder {
+        if let Err(e) = recorder.record(backend.name(), &self.core.results.hw_type, &self.core.results.sensor_type, &res) {
+          crate::wp(format!("记录采样失败 {}: {}", recorder.path.display(), e));
+        }
+      }
       match res {
         Ok(sensors) => self.core.update_test_status(i, &sensors)?,
         Err(e) => {
@@ -274,8 +446,47 @@ impl Tester {
         }
       }
     }
-
-    Ok(self)
+    self.core.finish()
+  }
+  /// 完整执行: 准备后端, 等待就绪, 启动负载, 采样, 最后无论成败都关闭负载并清理后端
+  pub async fn execute(mut self) -> e_utils::AnyResult<Self> {
+    let deadline = self.deadline;
+    let backend = self.inner.backend_mut()?;
+    if let Err(e) = until(deadline, backend.prepare()).await {
+      if let Err(te) = backend.teardown().await {
+        crate::wp(format!("清理失败: {}", te));
+      }
+      return Err(e);
+    }
+    if let Err(e) = until(deadline, backend.ready()).await {
+      if let Err(te) = backend.teardown().await {
+        crate::wp(format!("清理失败: {}", te));
+      }
+      return Err(e);
+    }
+    #[cfg(feature = "system")]
+    let load_handles = {
+      self.core.core_count = self.inner.get_cpu_core_count().await.unwrap_or(1);
+      self.spawn_load().unwrap_or_default()
+    };
+    #[cfg(not(feature = "system"))]
+    let load_handles: Vec<std::thread::JoinHandle<()>> = vec![];
+    crate::p(self.get_test_start());
+    let res = self.sample().await;
+    crate::api_test::LOAD_CONTROLLER.stop_running();
+    let backend = self.inner.backend_mut()?;
+    let name = backend.name().to_string();
+    // 先等负载线程退出再清理, 采样错误优先于清理错误返回
+    let joined = load_handles
+      .into_iter()
+      .try_for_each(|handle| handle.join().map_err(|_| format!("{}线程错误", name)));
+    let teardown = backend.teardown().await;
+    if let (Err(_), Err(te)) = (&res, &teardown) {
+      crate::wp(format!("清理失败: {}", te));
+    }
+    res?;
+    joined?;
+    teardown.map(|_| self)
   }
   pub fn get_test_start(&self) -> String {
     self.core.get_test_start()
@@ -284,8 +495,3 @@ impl Tester {
     self.core.get_test_summary()
   }
 }
-
-#[inline]
-fn is_value_out_of_range(value: f64, target: f64, range: f64) -> bool {
-  value > target + range || value < target - range
-}
@@ -0,0 +1,242 @@
+//! 采样记录, 测试过程中将每个采样写入 CSV 或 JSON Lines, 文件可直接用于 [`Replay`](super::Replay)
+//!
+//! 文件超过大小上限时轮转: `session.csv` -> `session.1.csv` -> ... -> `session.5.csv`, 最旧的删除
+use super::{HardwareType, Sensor, SensorSample, SensorType, LOAD_CONTROLLER};
+use e_utils::AnyResult;
+use std::{
+  fs::{self, File},
+  io::{BufWriter, Write},
+  path::{Path, PathBuf},
+  str::FromStr as _,
+  sync::atomic::Ordering,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+/// 轮转保留的历史文件数
+pub const RECORD_BACKUPS: usize = 5;
+
+/// 记录格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+  Csv,
+  Jsonl,
+}
+impl RecordFormat {
+  /// `.csv` 为 CSV, 其余为 JSON Lines
+  pub fn from_path(path: &Path) -> Self {
+    match path.extension().and_then(|v| v.to_str()) {
+      Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::Csv,
+      _ => Self::Jsonl,
+    }
+  }
+}
+
+/// CSV 字段转义
+fn csv_escape(v: &str) -> String {
+  if v.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", v.replace('"', "\"\""))
+  } else {
+    v.to_string()
+  }
+}
+
+impl SensorSample {
+  /// 由查询结果生成采样, 硬件类型优先取传感器的 Parent
+  pub fn from_sensor(timestamp_ms: u64, backend: &str, hw_type: &HardwareType, sensor: &Sensor) -> Self {
+    Self {
+      timestamp_ms,
+      backend: backend.to_string(),
+      hw_type: HardwareType::from_str(&sensor.Parent).unwrap_or_else(|_| hw_type.clone()),
+      sensor_type: sensor.SensorType.clone(),
+      name: sensor.Name.clone(),
+      identifier: sensor.Identifier.clone(),
+      value: sensor.Value,
+      min: sensor.Min,
+      max: sensor.Max,
+      load: LOAD_CONTROLLER.current_load.load(Ordering::SeqCst) as f64,
+      error: String::new(),
+    }
+  }
+  /// CSV 行, 与 [`SensorSample::CSV_HEADER`] 对应
+  pub fn to_csv(&self) -> String {
+    [
+      self.timestamp_ms.to_string(),
+      csv_escape(&self.backend),
+      self.hw_type.to_string(),
+      self.sensor_type.to_string(),
+      csv_escape(&self.name),
+      csv_escape(&self.identifier),
+      self.value.to_string(),
+      self.min.to_string(),
+      self.max.to_string(),
+      self.load.to_string(),
+      csv_escape(&self.error),
+    ]
+    .join(",")
+  }
+}
+
+/// 采样记录器
+#[derive(Debug)]
+pub struct Recorder {
+  pub path: PathBuf,
+  pub format: RecordFormat,
+  /// 轮转大小, 0 不轮转
+  pub max_bytes: u64,
+  written: u64,
+  /// 当前文件的采样行数
+  rows: u64,
+  writer: BufWriter<File>,
+  last_ms: u64,
+}
+impl Recorder {
+  /// 创建记录文件, 已存在时追加
+  pub fn create(path: impl AsRef<Path>, max_bytes: u64) -> AnyResult<Self> {
+    let path = path.as_ref().to_path_buf();
+    if let Some(dir) = path.parent().filter(|v| !v.as_os_str().is_empty()) {
+      fs::create_dir_all(dir)?;
+    }
+    let format = RecordFormat::from_path(&path);
+    let (writer, written) = Self::open(&path, format)?;
+    Ok(Self {
+      path,
+      format,
+      max_bytes,
+      written,
+      rows: 0,
+      writer,
+      last_ms: 0,
+    })
+  }
+  fn open(path: &Path, format: RecordFormat) -> AnyResult<(BufWriter<File>, u64)> {
+    let file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    let mut written = file.metadata()?.len();
+    let mut writer = BufWriter::new(file);
+    if written == 0 && format == RecordFormat::Csv {
+      let header = format!("{}\n", SensorSample::CSV_HEADER.join(","));
+      writer.write_all(header.as_bytes())?;
+      written += header.len() as u64;
+    }
+    Ok((writer, written))
+  }
+  /// 第 n 个历史文件路径, 如 `session.1.csv`
+  pub fn backup_path(&self, n: usize) -> PathBuf {
+    let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match self.path.extension() {
+      Some(ext) => format!("{}.{}.{}", stem, n, ext.to_string_lossy()),
+      None => format!("{}.{}", stem, n),
+    };
+    self.path.with_file_name(name)
+  }
+  fn rotate(&mut self) -> AnyResult<()> {
+    self.writer.flush()?;
+    let _ = fs::remove_file(self.backup_path(RECORD_BACKUPS));
+    for n in (1..RECORD_BACKUPS).rev() {
+      let from = self.backup_path(n);
+      if from.exists() {
+        fs::rename(from, self.backup_path(n + 1))?;
+      }
+    }
+    fs::rename(&self.path, self.backup_path(1))?;
+    (self.writer, self.written) = Self::open(&self.path, self.format)?;
+    self.rows = 0;
+    Ok(())
+  }
+  /// 写入一个采样
+  pub fn write(&mut self, sample: &SensorSample) -> AnyResult<()> {
+    let line = match self.format {
+      RecordFormat::Csv => sample.to_csv(),
+      RecordFormat::Jsonl => serde_json::to_string(sample)?,
+    } + "\n";
+    if self.max_bytes > 0 && self.rows > 0 && self.written + line.len() as u64 > self.max_bytes {
+      self.rotate()?;
+    }
+    self.writer.write_all(line.as_bytes())?;
+    self.written += line.len() as u64;
+    self.rows += 1;
+    Ok(())
+  }
+  /// 写入一次查询的结果, 查询失败时写入错误行
+  ///
+  /// 同一次查询的采样共用时间戳, 不同查询的时间戳保证递增, 回放时据此分组
+  pub fn record(&mut self, backend: &str, hw_type: &HardwareType, sensor_type: &SensorType, res: &AnyResult<Vec<Sensor>>) -> AnyResult<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|v| v.as_millis() as u64).unwrap_or_default();
+    let timestamp_ms = now.max(self.last_ms + 1);
+    self.last_ms = timestamp_ms;
+    match res {
+      Ok(sensors) => {
+        for sensor in sensors {
+          self.write(&SensorSample::from_sensor(timestamp_ms, backend, hw_type, sensor))?;
+        }
+      }
+      Err(e) => self.write(&SensorSample {
+        timestamp_ms,
+        backend: backend.to_string(),
+        hw_type: hw_type.clone(),
+        sensor_type: sensor_type.clone(),
+        error: e.to_string(),
+        ..Default::default()
+      })?,
+    }
+    self.writer.flush()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::api_test::{parse_session, Replay};
+
+  fn sensor(name: &str, value: f64) -> Sensor {
+    Sensor {
+      Name: name.into(),
+      Identifier: "/cpu/0".into(),
+      SensorType: SensorType::Temperature,
+      Parent: "CPU".into(),
+      Value: value,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_record_replay() {
+    let dir = std::env::temp_dir().join(format!("hw-record-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for file in ["session.csv", "session.jsonl"] {
+      let mut recorder = Recorder::create(dir.join(file), 0).unwrap();
+      let (hw, st) = (HardwareType::ALL, SensorType::Temperature);
+      recorder.record("Mock", &hw, &st, &Ok(vec![sensor("Core \"0\", die", 41.5)])).unwrap();
+      recorder.record("Mock", &hw, &st, &Err("Mock 注入错误".into())).unwrap();
+      let samples = parse_session(&fs::read_to_string(dir.join(file)).unwrap()).unwrap();
+      assert_eq!(samples.len(), 2);
+      assert_eq!(
+        (samples[0].name.as_str(), samples[0].hw_type.clone(), samples[0].value),
+        ("Core \"0\", die", HardwareType::CPU, 41.5)
+      );
+      let mut replay = Replay::new(samples);
+      assert_eq!(replay.next(&HardwareType::CPU, &st).unwrap()[0].Value, 41.5);
+      assert_eq!(replay.next(&HardwareType::CPU, &st).unwrap_err().to_string(), "Mock 注入错误");
+    }
+    let _ = fs::remove_dir_all(&dir);
+  }
+
+  #[test]
+  fn test_rotate() {
+    let dir = std::env::temp_dir().join(format!("hw-rotate-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut recorder = Recorder::create(dir.join("burn.csv"), 200).unwrap();
+    for i in 0..20 {
+      recorder
+        .record("OS", &HardwareType::CPU, &SensorType::Temperature, &Ok(vec![sensor("Package", i as f64)]))
+        .unwrap();
+    }
+    assert!(fs::metadata(dir.join("burn.csv")).unwrap().len() <= 200);
+    assert!(recorder.backup_path(1).ends_with("burn.1.csv") && recorder.backup_path(1).exists());
+    assert!(!recorder.backup_path(RECORD_BACKUPS + 1).exists());
+    // 每个文件都带表头, 可单独回放
+    let last = parse_session(&fs::read_to_string(dir.join("burn.csv")).unwrap()).unwrap();
+    assert_eq!(last.last().unwrap().value, 19.0);
+    let _ = fs::remove_dir_all(&dir);
+  }
+}
@@ -0,0 +1,213 @@
+//! 回放后端, 按录制的会话文件重放采样
+//!
+//! `--api Replay --args <硬件> <传感器> <文件>`, 文件为 JSON Lines 或带表头的 CSV, 每行一个 [`SensorSample`],
+//! 相同 `timestamp_ms` 的连续行视为一次查询, `error` 非空的行使该次查询返回错误
+use super::{BackendFuture, HardwareType, Sensor, SensorBackend, SensorType};
+use crate::common::text::csv_fields;
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr as _};
+
+/// 会话中的单个采样
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SensorSample {
+  /// UNIX 毫秒时间戳
+  pub timestamp_ms: u64,
+  pub backend: String,
+  /// 查询的硬件类型
+  pub hw_type: HardwareType,
+  pub sensor_type: SensorType,
+  pub name: String,
+  pub identifier: String,
+  pub value: f64,
+  pub min: f64,
+  pub max: f64,
+  /// 采样时的负载 %
+  pub load: f64,
+  /// 查询错误
+  pub error: String,
+}
+impl SensorSample {
+  /// CSV 表头
+  pub const CSV_HEADER: [&'static str; 11] = [
+    "timestamp_ms",
+    "backend",
+    "hw_type",
+    "sensor_type",
+    "name",
+    "identifier",
+    "value",
+    "min",
+    "max",
+    "load",
+    "error",
+  ];
+  pub fn to_sensor(&self, index: usize) -> Sensor {
+    Sensor {
+      Name: self.name.clone(),
+      Identifier: self.identifier.clone(),
+      _SensorType: self.sensor_type.to_string(),
+      SensorType: self.sensor_type.clone(),
+      Parent: self.hw_type.to_string(),
+      Value: self.value,
+      Min: self.min,
+      Max: self.max,
+      Index: index as i32,
+      data: String::new(),
+    }
+  }
+  /// 按表头解析 CSV 行
+  fn from_csv(header: &[String], fields: &[String]) -> Result<Self, String> {
+    let get = |key: &str| {
+      header
+        .iter()
+        .position(|v| v == key)
+        .and_then(|i| fields.get(i))
+        .map(String::as_str)
+        .unwrap_or_default()
+    };
+    let num = |key: &str| match get(key) {
+      "" => Ok(0.0),
+      v => v.parse::<f64>().map_err(|_| format!("{}={} 不是数字", key, v)),
+    };
+    Ok(Self {
+      timestamp_ms: num("timestamp_ms")? as u64,
+      backend: get("backend").to_string(),
+      hw_type: HardwareType::from_str(get("hw_type")).unwrap_or_default(),
+      sensor_type: SensorType::from_str(get("sensor_type")).unwrap_or_default(),
+      name: get("name").to_string(),
+      identifier: get("identifier").to_string(),
+      value: num("value")?,
+      min: num("min")?,
+      max: num("max")?,
+      load: num("load")?,
+      error: get("error").to_string(),
+    })
+  }
+}
+
+/// 解析会话文件内容, 首个非空行以 `{` 开头时按 JSON Lines, 否则按 CSV
+pub fn parse_session(content: &str) -> AnyResult<Vec<SensorSample>> {
+  let mut lines = content.lines().enumerate().map(|(i, v)| (i + 1, v.trim())).filter(|(_, v)| !v.is_empty());
+  let Some((first_no, first)) = lines.next() else {
+    return Ok(vec![]);
+  };
+  if first.starts_with('{') {
+    std::iter::once((first_no, first))
+      .chain(lines)
+      .map(|(no, line)| serde_json::from_str(line).map_err(|e| format!("第 {} 行: {}", no, e).into()))
+      .collect()
+  } else {
+    let header = csv_fields(first);
+    lines
+      .map(|(no, line)| SensorSample::from_csv(&header, &csv_fields(line)).map_err(|e| format!("第 {} 行: {}", no, e).into()))
+      .collect()
+  }
+}
+
+/// 回放后端
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+  /// 按查询分组的采样
+  pub ticks: Vec<Vec<SensorSample>>,
+  /// 已查询次数
+  pub tick: usize,
+}
+impl Replay {
+  pub const NAME: &'static str = "Replay";
+  /// 相同时间戳的连续采样归为一次查询
+  pub fn new(samples: Vec<SensorSample>) -> Self {
+    let mut ticks: Vec<Vec<SensorSample>> = vec![];
+    for sample in samples {
+      match ticks.last_mut() {
+        Some(tick) if tick[0].timestamp_ms == sample.timestamp_ms => tick.push(sample),
+        _ => ticks.push(vec![sample]),
+      }
+    }
+    Self { ticks, tick: 0 }
+  }
+  pub fn load(path: impl AsRef<Path>) -> AnyResult<Self> {
+    let path = path.as_ref();
+    let replay = Self::new(parse_session(&std::fs::read_to_string(path)?)?);
+    if replay.ticks.is_empty() {
+      return Err(format!("回放文件为空: {}", path.display()).into());
+    }
+    Ok(replay)
+  }
+  /// 注册表构造函数, 首个参数为会话文件
+  pub fn from_args(args: &[String]) -> AnyResult<Box<dyn SensorBackend>> {
+    let path = args.first().ok_or("Replay 缺少会话文件")?;
+    Ok(Box::new(Self::load(path)?))
+  }
+  /// 同步查询, 每次调用推进一次
+  pub fn next(&mut self, hw_type: &HardwareType, sensor_type: &SensorType) -> AnyResult<Vec<Sensor>> {
+    let tick = self.tick;
+    self.tick += 1;
+    let samples = self.ticks.get(tick).ok_or("Replay 数据已结束")?;
+    if let Some(sample) = samples.iter().find(|v| !v.error.is_empty()) {
+      return Err(sample.error.clone().into());
+    }
+    let sensors: Vec<Sensor> = samples
+      .iter()
+      .filter(|v| *hw_type == HardwareType::ALL || v.hw_type == *hw_type)
+      .filter(|v| *sensor_type == SensorType::ALL || v.sensor_type == *sensor_type)
+      .enumerate()
+      .map(|(i, v)| v.to_sensor(i))
+      .collect();
+    if sensors.is_empty() {
+      return Err(format!("Replay No sensors found for {} {}", hw_type, sensor_type).into());
+    }
+    Ok(sensors)
+  }
+}
+impl SensorBackend for Replay {
+  fn name(&self) -> &str {
+    Self::NAME
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move { self.next(&hw_type, &sensor_type) })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const JSONL: &str = r#"{"timestamp_ms":1000,"backend":"OS","hw_type":"CPU","sensor_type":"Temperature","name":"Package","value":45.0}
+{"timestamp_ms":1000,"backend":"OS","hw_type":"CPU","sensor_type":"Clock","name":"Core 0","value":3200.0}
+{"timestamp_ms":2000,"backend":"OS","hw_type":"CPU","sensor_type":"Temperature","name":"Package","value":47.5}
+{"timestamp_ms":3000,"backend":"OS","hw_type":"CPU","sensor_type":"Temperature","error":"OS No sensors found"}
+"#;
+  const CSV: &str = "timestamp_ms,backend,hw_type,sensor_type,name,identifier,value,min,max,load,error
+1000,HWMON,Mainboard,Fan,\"Fan 1, rear\",/hwmon2/fan1,1200,1200,1200,50,
+2000,HWMON,Mainboard,Fan,\"Fan 1, rear\",/hwmon2/fan1,1250,1200,1250,50,
+";
+
+  #[test]
+  fn test_replay_jsonl() {
+    let mut replay = Replay::new(parse_session(JSONL).unwrap());
+    assert_eq!(replay.ticks.len(), 3);
+    let first = replay.next(&HardwareType::CPU, &SensorType::Temperature).unwrap();
+    assert_eq!((first.len(), first[0].Value, first[0].Parent.as_str()), (1, 45.0, "CPU"));
+    assert_eq!(replay.next(&HardwareType::ALL, &SensorType::ALL).unwrap()[0].Value, 47.5);
+    assert_eq!(
+      replay.next(&HardwareType::CPU, &SensorType::Temperature).unwrap_err().to_string(),
+      "OS No sensors found"
+    );
+    assert_eq!(
+      replay.next(&HardwareType::CPU, &SensorType::Temperature).unwrap_err().to_string(),
+      "Replay 数据已结束"
+    );
+  }
+
+  #[test]
+  fn test_replay_csv() {
+    let samples = parse_session(CSV).unwrap();
+    assert_eq!((samples[1].name.as_str(), samples[1].max, samples[1].load), ("Fan 1, rear", 1250.0, 50.0));
+    let mut replay = Replay::new(samples);
+    assert!(replay.next(&HardwareType::Mainboard, &SensorType::Temperature).is_err());
+    assert_eq!(replay.next(&HardwareType::Mainboard, &SensorType::Fan).unwrap()[0].Identifier, "/hwmon2/fan1");
+    assert!(parse_session("timestamp_ms,value\n1000,abc\n").is_err());
+  }
+}
@@ -1,7 +1,7 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 use crate::{
-  api_test::{HardwareType, Sensor, SensorType},
+  api_test::{BackendFuture, ExeBackend, HardwareType, Sensor, SensorBackend, SensorType},
   wmic::HardwareMonitor,
 };
 use csv::StringRecord;
@@ -119,8 +119,6 @@ impl ArgusMonitor {
 }
 
 impl ArgusMonitor {
-  pub const EXE: &'static str = "ArgusMonitor.exe";
-  pub const DIR: &'static str = "plugins/ArgusMonitor";
   fn parse_value(value: Vec<f64>) -> (f64, f64, f64) {
     let temperature = value.iter().sum::<f64>() / value.len() as f64;
     let min = value.iter().min_by(|a, b| a.partial_cmp(b).unwrap()).cloned().unwrap_or(0.0);
@@ -194,6 +192,34 @@ impl ArgusMonitor {
   }
 }
 
+impl SensorBackend for ArgusMonitor {
+  fn name(&self) -> &str {
+    "ArgusMonitor"
+  }
+  fn prepare(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::start() })
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    crate::api_test::wait_ready::<Self>(100)
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move { ArgusMonitor::query(self, hw_type, sensor_type) })
+  }
+  fn teardown(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::shutdown() })
+  }
+}
+
+impl ExeBackend for ArgusMonitor {
+  const EXE: &'static str = "ArgusMonitor.exe";
+  const DIR: &'static str = "plugins/ArgusMonitor";
+  /// 先清理旧日志, 避免读到上次运行的数据
+  fn start() -> AnyResult<()> {
+    Self::clean()?;
+    crate::api_test::start_exe(Self::EXE, Self::DIR)
+  }
+}
+
 impl HardwareMonitor for ArgusMonitor {
   type HWType = HardwareType;
   type SensorType = Sensor;
@@ -13,117 +13,11 @@ use strum::VariantArray;
 pub async fn api(op: Opts, _opts: &mut Value) -> e_utils::AnyResult<String> {
   let mut tester = Tester::from_opts(&op)?;
   match tester.inner {
-    #[cfg(all(feature = "core-temp", target_os = "windows"))]
-    Inner::CoreTemp(_) => {
-      use crate::wmic::HardwareMonitor as _;
+    Inner::Backend(_) => {
       if !tester.core.is_check && !tester.core.is_print && !tester.core.is_data {
         return Err("Task No check Or print Or data".into());
       }
-      crate::core_temp::CoreTemp::clean()?;
-      let pids = crate::common::process::run(crate::core_temp::CoreTemp::EXE, std::env::current_dir()?.join(crate::core_temp::CoreTemp::DIR))?;
-      if pids.is_empty() {
-        return Err(format!("Task {} is empty", crate::core_temp::CoreTemp::EXE).into());
-      }
-      crate::core_temp::CoreTemp::test(100)?;
-      tester.core.core_count = tester.inner.get_cpu_core_count().await.unwrap_or(1);
-      let load_handles = tester.spawn_load().unwrap_or_default();
-      crate::dp(tester.get_test_start());
-      let res = tester.run().await;
-      crate::api_test::LOAD_CONTROLLER.stop_running();
-      crate::common::process::kill_name(crate::core_temp::CoreTemp::EXE)?;
-      crate::core_temp::CoreTemp::stop()?;
-      crate::core_temp::CoreTemp::clean()?;
-      for handle in load_handles {
-        handle.join().map_err(|_| "CoreTemp线程错误")?;
-      }
-      tester = res?;
-    }
-    #[cfg(all(feature = "lhm", target_os = "windows"))]
-    Inner::LHM(_) => {
-      if !tester.core.is_check && !tester.core.is_print && !tester.core.is_data {
-        return Err("Task No check Or print Or data".into());
-      }
-      use crate::wmic::HardwareMonitor as _;
-      let pids = crate::common::process::run(crate::lhm::LHM::EXE, std::env::current_dir()?.join(crate::lhm::LHM::DIR))?;
-      if pids.is_empty() {
-        return Err(format!("Task {} is empty", crate::lhm::LHM::EXE).into());
-      }
-      crate::lhm::LHM::test(100)?;
-      tester.core.core_count = tester.inner.get_cpu_core_count().await.unwrap_or(1);
-      let load_handles = tester.spawn_load().unwrap_or_default();
-      crate::dp(tester.get_test_start());
-      let res = tester.run().await;
-      crate::api_test::LOAD_CONTROLLER.stop_running();
-      crate::common::process::kill_name(crate::lhm::LHM::EXE)?;
-      crate::lhm::LHM::stop()?;
-      crate::lhm::LHM::clean()?;
-      for handle in load_handles {
-        handle.join().map_err(|_| "LHM线程错误")?;
-      }
-      tester = res?;
-    }
-    #[cfg(all(feature = "ohm", target_os = "windows"))]
-    Inner::OHM(_) => {
-      if !tester.core.is_check && !tester.core.is_print && !tester.core.is_data {
-        return Err("Task No check Or print Or data".into());
-      }
-      use crate::wmic::HardwareMonitor as _;
-      let pids = crate::common::process::run(crate::ohm::OHM::EXE, std::env::current_dir()?.join(crate::ohm::OHM::DIR))?;
-      if pids.is_empty() {
-        return Err(format!("Task {} is empty", crate::ohm::OHM::EXE).into());
-      }
-      crate::ohm::OHM::test(100)?;
-      tester.core.core_count = tester.inner.get_cpu_core_count().await.unwrap_or(1);
-      let load_handles = tester.spawn_load().unwrap_or_default();
-      crate::dp(tester.get_test_start());
-      let res = tester.run().await;
-      crate::api_test::LOAD_CONTROLLER.stop_running();
-      crate::common::process::kill_name(crate::ohm::OHM::EXE)?;
-      crate::ohm::OHM::stop()?;
-      crate::ohm::OHM::clean()?;
-      for handle in load_handles {
-        handle.join().map_err(|_| "OHM线程错误")?;
-      }
-      tester = res?;
-    }
-    #[cfg(all(feature = "aida64", target_os = "windows"))]
-    Inner::AIDA64(_) => {
-      if !tester.core.is_check && !tester.core.is_print && !tester.core.is_data {
-        return Err("Task No check Or print Or data".into());
-      }
-      use crate::wmic::HardwareMonitor as _;
-      let pids = crate::common::process::run(crate::aida64::AIDA64::EXE, std::env::current_dir()?.join(crate::aida64::AIDA64::DIR))?;
-      if pids.is_empty() {
-        return Err(format!("Task {} is empty", crate::aida64::AIDA64::EXE).into());
-      }
-      crate::aida64::AIDA64::test(100)?;
-      tester.core.core_count = tester.inner.get_cpu_core_count().await.unwrap_or(1);
-      let load_handles = tester.spawn_load().unwrap_or_default();
-      crate::dp(tester.get_test_start());
-      let res = tester.run().await;
-      crate::api_test::LOAD_CONTROLLER.stop_running();
-      crate::common::process::kill_name(crate::aida64::AIDA64::EXE)?;
-      crate::aida64::AIDA64::stop()?;
-      crate::aida64::AIDA64::clean()?;
-      for handle in load_handles {
-        handle.join().map_err(|_| "OHM线程错误")?;
-      }
-      tester = res?;
-    }
-    #[cfg(feature = "os")]
-    Inner::OS(_) => {
-      if !tester.core.is_check && !tester.core.is_print && !tester.core.is_data {
-        return Err("Task No check Or print Or data".into());
-      }
-      tester.core.core_count = tester.inner.get_cpu_core_count().await?;
-      let load_handles = tester.spawn_load().unwrap_or_default();
-      crate::p(tester.get_test_start());
-      let res = tester.run().await;
-      crate::api_test::LOAD_CONTROLLER.stop_running();
-      for handle in load_handles {
-        handle.join().map_err(|_| "OHM线程错误")?;
-      }
-      tester = res?;
+      tester = tester.execute().await?;
     }
     Inner::OSMore => {
       let more_type = os_more::Type::from_str(&op.task).unwrap_or_default();
@@ -140,6 +34,10 @@ pub async fn api(op: Opts, _opts: &mut Value) -> e_utils::AnyResult<String> {
     Inner::OSSystem => return crate::os_system::os_system_query(&op.task, &op.args).await,
     Inner::OSOffice => return crate::os_office::os_office_query(&op.task, &op.args).await,
     Inner::Disk => return crate::disk::disk_query(&op.task, &op.args, &op.command).await,
+    #[cfg(feature = "plan")]
+    Inner::Plan => return crate::plan::plan_query(&op.task, &op.args, op.full).await,
+    #[cfg(not(feature = "plan"))]
+    Inner::Plan => return Err("请开启特性 plan".into()),
   };
   if tester.core.results.data.is_empty() && tester.core.is_check {
     tester.core.results.res = "FAIL".to_string();
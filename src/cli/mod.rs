@@ -4,24 +4,87 @@ pub use api::*;
 use e_utils::AnyResult;
 use serde::{Deserialize, Serialize};
 use std::ffi::OsString;
-use structopt::{clap::arg_enum, StructOpt};
+use structopt::StructOpt;
 
-arg_enum! {
-    /// Api接口
-    #[derive(Deserialize, Serialize, Debug, StructOpt, Clone, PartialEq, Copy)]
-    pub enum OptsApi {
-        OHM,
-        LHM,
-        AIDA64,
-        OS,
-        OSMore,
-        Drive,
-        FileInfo,
-        OSSystem,
-        OSOffice,
-        Disk,
-        CoreTemp,
+/// Api接口
+///
+/// 未列出的名称按传感器后端注册表查找, 见 [`crate::api_test::register_backend`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptsApi {
+  OHM,
+  LHM,
+  AIDA64,
+  OS,
+  OSMore,
+  Drive,
+  FileInfo,
+  OSSystem,
+  OSOffice,
+  Disk,
+  CoreTemp,
+  HWMON,
+  RAPL,
+  /// 测试计划, 见 [`crate::plan`]
+  Plan,
+  /// 注册的其他传感器后端, 为注册表中的名称
+  Backend(&'static str),
+}
+impl OptsApi {
+  const BUILTIN: [Self; 14] = [
+    Self::OHM,
+    Self::LHM,
+    Self::AIDA64,
+    Self::OS,
+    Self::OSMore,
+    Self::Drive,
+    Self::FileInfo,
+    Self::OSSystem,
+    Self::OSOffice,
+    Self::Disk,
+    Self::CoreTemp,
+    Self::HWMON,
+    Self::RAPL,
+    Self::Plan,
+  ];
+  /// 是否为传感器后端, 与 [`crate::api_test::Inner::from_api`] 对应
+  pub fn is_backend(&self) -> bool {
+    !matches!(
+      self,
+      Self::OSMore | Self::Drive | Self::FileInfo | Self::OSSystem | Self::OSOffice | Self::Disk | Self::Plan
+    )
+  }
+}
+impl std::str::FromStr for OptsApi {
+  type Err = String;
+  /// 名称不区分大小写, 未列出的名称须已在后端注册表中
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    if let Some(api) = Self::BUILTIN.iter().find(|v| v.to_string().eq_ignore_ascii_case(name)) {
+      return Ok(*api);
+    }
+    let names = crate::api_test::backend_names();
+    match names.iter().find(|v| v.eq_ignore_ascii_case(name)) {
+      Some(v) => Ok(Self::Backend(v)),
+      None => Err(format!("{} not supported, 可用: {}", name, names.join(", "))),
     }
+  }
+}
+impl std::fmt::Display for OptsApi {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Backend(name) => f.write_str(name),
+      api => write!(f, "{:?}", api),
+    }
+  }
+}
+impl Serialize for OptsApi {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+impl<'de> Deserialize<'de> for OptsApi {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+  }
 }
 
 /// e-app
@@ -53,6 +116,8 @@ pub enum HardwareType {
   HDD,
   #[strum(message = "内存")]
   RAM,
+  #[strum(message = "网络适配器")]
+  Network,
   #[strum(message = "未知")]
   Unknown,
 }
@@ -95,6 +160,8 @@ pub enum SensorType {
   Multiplexer,
   /// 平均时钟速度传感器 (单位: 兆赫兹, 显示: "{value} MHz")
   ClockAverage,
+  /// 电流传感器 (单位: 安培, 显示: "{value} A")
+  Current,
   /// 未知
   Unknown,
 }
@@ -143,6 +210,98 @@ hw --api AIDA64 --task check --args RAM Load
 # CPU核心电压监控
 hw --api AIDA64 --task check --args CPU Voltage
 ```
+### [X. 📖 点击Rust调用HWMON 获取CPU温度](examples/hwmon.rs)
+### Linux hwmon 监控 (/sys/class/hwmon)
+```bash
+# CPU温度测试 (10次, 目标60°C, 误差±20°C)
+hw --api HWMON --task check --args CPU Temperature -- 10 60 20
+# 所有风扇转速
+hw --api HWMON --task print --args ALL Fan
+```
+### Linux RAPL 功率 (/sys/class/powercap)
+```bash
+# CPU功率测试 (10次, 目标65W, 误差±30W, 负载80%)
+hw --api RAPL --task check --args CPU Power -- 10 65 30 80
+# 所有能量域功率 package/core/uncore/dram
+hw --api RAPL --task print --args ALL Power
+```
+### 网卡速率 (OS)
+```bash
+# 每个网卡收发合计速率 B/s, 排除环回与虚拟网卡
+hw --api OS --task print --args Network Throughput
+# 每个网卡下载/上传速率
+hw --api OS --task print --args Network DataRate
+# 网卡吞吐测试 (10次, 目标100MB/s, 误差±50MB/s, 负载80%)
+hw --api OS --task check --args Network Throughput -- 10 100000000 50000000 80
+```
+### 自定义传感器后端
+```bash
+# 其他 crate 通过 hw::api_test::register_backend 注册的后端, 按名称选择(不区分大小写)
+hw --api MyBoard --task check --args Mainboard Temperature -- 10 40 5
+```
+### 模拟与回放后端
+```bash
+# 脚本取值 (err 表示注入查询错误); JSON 脚本还支持每个传感器的取值序列, 延时与就绪错误
+hw --api Mock --task check --args CPU Temperature 38,42,err,40 -- 4 40 5
+# 回放录制的会话 (JSON Lines 或 CSV), 相同 timestamp_ms 的行为一次采样
+hw --api Replay --task check --args CPU Temperature session.jsonl -- 10 60 20
+```
+### 记录传感器会话
+```bash
+# 将每个采样 (时间戳, 后端, 硬件, 传感器名称/标识, 当前值, 最小, 最大, 负载) 实时写入 CSV 或 JSON Lines
+hw --api OS --task check --args CPU Clock --record burn-in.csv -- 3600 3000 2000 80
+# 超过 10 MB 轮转 (burn-in.1.csv ... burn-in.5.csv), 记录文件可直接用于 Replay 后端
+hw --api HWMON --task print --args CPU Temperature --record logs/temp.jsonl --record-rotate 10 -- 600
+```
+### 判定条件
+```bash
+# 单边上限: 温度不得超过 85°C, 忽略目标与误差
+hw --api HWMON --task check --args CPU Temperature --criteria max=85 -- 600 60 20 0
+# 百分比误差 (3000MHz ±10%), 忽略前 5 次预热采样, 其余采样的 p95 不高于 3400MHz
+hw --api OS --task check --args CPU Clock --criteria pct=10 warmup=5 p95=3400 -- 60 3000 0 100
+# 只判定平均值, 或允许偶发尖峰: 最多连续 3 次且总比例不超过 10%, 回到上限以下 2°C 才算恢复
+hw --api OS --task check --args CPU Temperature --criteria avg -- 60 50 10 0
+hw --api OS --task check --args CPU Temperature --criteria max=80 consecutive=3 ratio=10 hysteresis=2 -- 600 0 0 0
+# 未设置 --criteria 时, 超出 目标 ± 误差 超过 2 次即失败 (violations=2)
+```
+### 测试计划
+```toml
+# station.toml: 按顺序执行各步骤, ${var} 由 [vars] 或命令行 key=value 替换
+name = "Station A"
+timeout = 120            # 默认步骤超时秒数
+report = "report.json"   # 汇总 JSON 报告
+[vars]
+clock = 3000
+nic = "eth0"             # 可被命令行 nic=... 覆盖
+
+[[steps]]
+name = "CPU 主频"
+api = "OS"
+task = "check"
+args = ["CPU", "Clock"]
+command = [10, "${clock}", 2000, 80]
+criteria = ["warmup=2", "p95=4000"]
+retries = 1
+
+[[steps]]
+api = "OSMore"
+task = "CpuName"
+expect = { contains = ["Intel"] }  # 另有 equals / not_contains / min / max
+save = "cpu_name"                  # 后续步骤可引用 ${cpu_name}
+continue_on_fail = true
+
+[[steps]]
+api = "OSMore"
+task = "NetInterface"
+args = ["check-mac", "${nic}"]
+command = ["type=Ethernet"]
+```
+```bash
+# 执行计划 (TOML, 按扩展名支持 YAML/JSON), 步骤失败且未设置 continue_on_fail 时停止, 其余步骤为 SKIP
+hw plan run station.toml nic="*I225-V #1"
+# 只检查文件与变量, 不执行
+hw plan check station.toml nic=eth0
+```
 ### [5. 📖 点击Rust调用OSMore](examples/os_more_base.rs)
 ```bash
 # 获取系统完整信息
@@ -153,6 +312,9 @@ hw --api OSMore --task MemoryTotal
 hw --api OSMore --task HostName
 # 获取OS版本
 hw --api OSMore --task OsVersion
+# Linux SMBIOS: BIOS/主板/内存条 (BiosInfo,SystemInfo,BoardInfo,ChassisInfo,ProcessorInfo,MemoryModules)
+hw --api OSMore --task BoardInfo
+hw --api OSMore --task MemoryModules
 ```
 ### [6. 📖 点击Rust调用微软OFFICE](examples/os_office.rs)
 ```bash
@@ -177,10 +339,17 @@ hw --api FileInfo --task nodes --args target/debug/hw.exe
 ```
 ### [9. 📖 点击Rust调用PING](examples/ping.rs)
 ```bash
-# 测试PING
+# 测试PING 源地址 目标 次数 [重试次数] [最大丢包率%] [最大平均延迟ms], 源地址为 * 时不绑定
 hw --api OSMore --task NetManage  --args ping 127.0.0.1 baidu.com 3
-# 测试PING节点
+hw --api OSMore --task NetManage  --args ping 192.168.1.20 baidu.com 10 3 10 50
+# 测试PING节点 目标 次数 [网口数量] [重试次数] [最大丢包率%] [最大平均延迟ms], --full 输出每次探测的延迟
+# 每个网口绑定自身 IPv4 发送, 没有 IPv4 时失败; Windows 与 macOS 需要管理员/root 权限
 hw --api OSMore --task NetManage --args ping-nodes baidu.com 3 -- ~is_connected Ethernet
+hw --api OSMore --task NetManage --full --args ping-nodes baidu.com 10 2 3 10 50 -- ~is_connected Ethernet
+# 针对屏蔽ICMP的目标进行 TCP连接 / UDP回显 / HTTP GET 探测, 绑定各网口IPv4, 没有 IPv4 时失败; 参数与 ping-nodes 相同, http-probe 先传期望状态码
+hw --api OSMore --task NetManage --args tcp-probe 192.168.1.10:22 3 -- ~is_connected Ethernet
+hw --api OSMore --task NetManage --args udp-echo 192.168.1.10:7 10 2 3 10 50 -- ~is_connected Ethernet
+hw --api OSMore --task NetManage --args http-probe http://192.168.1.10:8080/health 200 3 -- ~is_connected Ethernet
 ```
 ### [10. 📖 点击Rust调用设置DHCP](examples/dhcp.rs)
 ```bash
@@ -189,10 +358,24 @@ hw --api OSMore --task NetManage --args dhcp -- ~is_connected
 ```
 ### [11. 📖 点击Rust调用设置静态IP](examples/static_ip.rs)
 ```bash
-# 设置静态IP
+# 设置静态IP 设置后重新读取网卡校验 IP/前缀/网关, 超时未生效时输出期望与实际的差异
 hw --api OSMore --task NetManage  --args set-ip 192.168.1.100 255.255.255.0 192.168.1.1 -- "以太网"
-# 设置DNS Ethernet=类型 "以太网"=名称   ~is_connected=网卡
+# 设置DNS 同样校验DNS列表 Ethernet=类型 "以太网"=名称   ~is_connected=网卡
 hw --api OSMore --task NetManage  --args set-dns 223.5.5.5 114.114.114.114 "以太网" Ethernet  ~is_connected
+# Linux 通过检测到的网络管理器(NetworkManager/netplan/systemd-networkd/ifupdown)修改配置, 未检测到时使用 ip/dhclient
+hw --api OSMore --task NetManage --args backend
+# 添加/删除多个 IPv4/IPv6 地址(前缀或掩码), 设置 IPv6 网关, IPv4/IPv6 DNS 列表和 MTU
+hw --api OSMore --task NetManage --args addr-add 192.168.1.101/24 10.0.0.2/255.0.0.0 fd00::10/64 -- name=enp3s0
+hw --api OSMore --task NetManage --args addr-del fd00::10/64 -- name=enp3s0
+hw --api OSMore --task NetManage --args set-gateway fd00::1 -- name=enp3s0
+hw --api OSMore --task NetManage --args set-dns 223.5.5.5 2400:3200::1 119.29.29.29 -- name=enp3s0
+hw --api OSMore --task NetManage --args set-mtu 9000 -- name=enp3s0
+# 网络配置快照 保存每个网卡的地址/网关/DNS 获取方式到JSON
+hw --api OSMore --task NetManage --args snapshot net.json
+# 应用期望状态 30秒内网关ping不通则回滚
+hw --api OSMore --task NetManage --args apply desired.json gateway 30
+# TCP连接探测 失败时回滚到已保存的快照
+hw --api OSMore --task NetManage --args apply desired.json tcp:192.168.1.1:80 30 net.json
 ```
 ### [12. 📖 点击Rust调用桌面](examples/desktop.rs)
 ```bash
@@ -242,8 +425,10 @@ hw --api Drive --task print-status --nodes --full
 ```
 ### [14. 📖 点击Rust调用同步时间](examples/sync_datetime.rs)
 ```bash
-# 同步时间
+# 同步时间 Windows 使用 w32tm, 其他系统使用内置 SNTP 客户端并调整系统时钟
 hw --api OSMore --task NetManage --args sync-datetime time.windows.com
+# SNTP 检查时钟偏差, 超过 500ms 失败
+hw --api OSMore --task NetManage --args check-time pool.ntp.org 500
 ```
 ### [15. 📖 点击Rust调用网络接口](examples/net_interfaces.rs)
 ```bash
@@ -253,13 +438,33 @@ hw --api OSMore --task NetManage --args sync-datetime time.windows.com
 # "~Big1000" 速度大于等于10000
 # "~is_connected" 正在连接
 # "~has_dhcp_ip" 有DHCP IP
+# "~auto" 排除虚拟网卡  "~00-1B" MAC前缀
+# 只有 "~" 规则时其他参数忽略, 多个MAC前缀满足其一即可
+# 表达式: and/or/not/() 组合, 相邻条件默认 and
+#   字段 name friendly_name description type mac speed(Mbps) status index flags ipv4 ipv6 ip gateway dns
+#   运算 = != > >= < <= ~(通配) ^=(前缀) $=(后缀) *=(包含)   布尔 connected dhcp has_ip has_gateway virtual
+# select 条件筛选网卡, assert 条件不满足时失败
+hw --api OSMore --task NetInterface --args print -- 'speed>=1000' and 'type=Ethernet' and not virtual
+hw --api OSMore --task NetInterface --args print -- 'description~"*I225*"' assert 'mac^=00-1B'
 
 # 检查MAC重复和初始化
 hw --api OSMore --task NetInterface --args check-mac "*I225-V #1" -- ~has_dhcp_ip
+# 按 JSON 规则文件检查2个以太网口的MAC: 禁止列表, 组播/本地管理位, OUI厂商(IEEE oui.txt/oui.csv)与允许范围
+hw --api OSMore --task NetInterface --args check-mac 2 mac_rules.json -- 'type=Ethernet'
 # 网络接口
 hw --api OSMore --task NetInterface --args print  -- ~has_dhcp_ip
 # 网络接口节点
 hw --api OSMore --task NetInterface --args nodes  -- ~has_dhcp_ip
+# 完整接口数据 包含收发字节/包/错误/丢弃计数与 bond/bridge/VLAN 拓扑(check-mac 不把拓扑共用的MAC视为重复)
+hw --api OSMore --task NetInterface --full --args print -- ~auto
+# 监听600秒内的插拔/速率/地址变化(轮询1000ms), 任一网卡断开超过2次则失败; --full 输出完整接口
+hw --api OSMore --task NetInterface --args watch 600 2 1000 -- 'type=Ethernet'
+# IPv4/IPv6 邻居缓存(IP, MAC, 状态, 接口)与路由表, 可按过滤条件限定接口
+hw --api OSMore --task NetInterface --args neighbors
+hw --api OSMore --task NetInterface --args routes -- 'type=Ethernet'
+# 无线网卡 SSID, BSSID, 信号, 链路质量, 信道/频率与发送速率; check 在任一无线网卡未连接, 信号低于 -65 dBm 或 SSID 不符时失败(负号可省略)
+hw --api OSMore --task NetInterface --args wifi
+hw --api OSMore --task NetInterface --args wifi check 65 "Factory Test"
 ```
 ### [16. 📖 点击Rust调用磁盘](examples/disk.rs)
 ```bash
@@ -275,8 +480,8 @@ hw --api Disk --task check-load --args 10 90
 "#)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Opts {
-  /// API接口
-  #[structopt(required = true, short, long, possible_values = &OptsApi::variants(), case_insensitive = true)]
+  /// API接口: OHM, LHM, AIDA64, OS, OSMore, Drive, FileInfo, OSSystem, OSOffice, Disk, CoreTemp, HWMON, RAPL, Plan, Mock, Replay 或已注册的后端
+  #[structopt(required = true, short, long)]
   pub api: OptsApi,
   /// 任务
   #[structopt(long, required = false, default_value = "")]
@@ -290,9 +495,25 @@ pub struct Opts {
   /// 扩展参数
   #[structopt(long, required = false)]
   pub args: Vec<String>,
+  /// 记录每个采样到文件, `.csv` 为 CSV, 其余为 JSON Lines
+  #[structopt(long)]
+  #[serde(default)]
+  pub record: Option<String>,
+  /// 记录文件轮转大小 MB, 0 不轮转
+  #[structopt(long, default_value = "100")]
+  #[serde(default = "Opts::default_record_rotate")]
+  pub record_rotate: u64,
+  /// 判定条件: min=, max=, pct=, avg, p95=, violations=, consecutive=, ratio=, warmup=, hysteresis=
+  #[structopt(long, required = false)]
+  #[serde(default)]
+  pub criteria: Vec<String>,
   /// 扩展指令
   #[structopt(required = false, last = true)]
   pub command: Vec<String>,
+  /// 截止时间, 计划步骤超时时设置; 到期后测试停止采样并完成清理
+  #[structopt(skip)]
+  #[serde(skip)]
+  pub deadline: Option<std::time::Instant>,
 }
 impl Default for Opts {
   fn default() -> Self {
@@ -303,7 +524,11 @@ impl Default for Opts {
       // verbose: 0,
       args: Vec::new(),
       filter: Vec::new(),
+      record: None,
+      record_rotate: Self::default_record_rotate(),
+      criteria: Vec::new(),
       command: Vec::new(),
+      deadline: None,
     }
   }
 }
@@ -317,15 +542,55 @@ impl Opts {
     I::Item: Into<OsString> + Clone,
   {
     match args {
-      Some(arg) => match Opts::from_iter_safe(arg) {
+      Some(arg) => match Opts::from_iter_safe(Self::plan_args(arg.into_iter().map(Into::into).collect())) {
         Ok(opt) => Ok(opt),
         Err(e) => Err(e.into()),
       },
-      None => Ok(Opts::from_args()),
+      None => Ok(Opts::from_iter(Self::plan_args(std::env::args_os().collect()))),
+    }
+  }
+  /// `hw plan <task> <参数>...` 改写为 `hw --api Plan --task <task> --args <参数>...`
+  fn plan_args(args: Vec<OsString>) -> Vec<OsString> {
+    match args.get(1).and_then(|v| v.to_str()) {
+      Some(v) if v.eq_ignore_ascii_case("plan") && args.len() > 2 => {
+        let mut res: Vec<OsString> = vec![args[0].clone(), "--api".into(), "Plan".into(), "--task".into(), args[2].clone()];
+        if args.len() > 3 {
+          res.push("--args".into());
+          res.extend(args[3..].iter().cloned());
+        }
+        res
+      }
+      _ => args,
     }
   }
+  /// 记录文件默认轮转大小 MB, 与 `--record-rotate` 默认值一致
+  fn default_record_rotate() -> u64 {
+    100
+  }
   /// # 检查空
   pub fn check_empty() -> bool {
     std::env::args().len() == 1
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_opts_serde_default() {
+    let opts: Opts = serde_json::from_str(r#"{"api":"OS","task":"print","full":false,"filter":[],"args":[],"command":[]}"#).unwrap();
+    assert_eq!(opts.record_rotate, Opts::default().record_rotate);
+    assert_eq!(opts.record_rotate, 100);
+  }
+
+  #[test]
+  fn test_opts_api_parse() {
+    assert_eq!("os".parse(), Ok(OptsApi::OS));
+    assert_eq!("mock".parse(), Ok(OptsApi::Backend("Mock")));
+    assert!("Nope".parse::<OptsApi>().is_err());
+    assert_eq!(serde_json::to_string(&OptsApi::Backend("Mock")).unwrap(), r#""Mock""#);
+    assert_eq!(serde_json::from_str::<OptsApi>(r#""hwmon""#).unwrap(), OptsApi::HWMON);
+    assert!(Opts::new(Some(["hw", "--api", "Nope"])).is_err());
+  }
+}
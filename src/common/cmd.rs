@@ -0,0 +1,13 @@
+//! 外部命令工具
+use e_utils::cmd::{Cmd, ExeType};
+
+/// 直接执行程序
+///
+/// Cmd 对无扩展名的程序默认使用 `sh -c` 执行, 后续参数会成为 shell 位置参数而被丢弃, 所以从 PATH 解析为绝对路径后直接执行
+pub fn exe(program: &str) -> Cmd {
+  let path = std::env::var_os("PATH")
+    .and_then(|paths| std::env::split_paths(&paths).map(|dir| dir.join(program)).find(|v| v.is_file()))
+    .map(|v| v.to_string_lossy().to_string())
+    .unwrap_or_else(|| program.to_string());
+  Cmd::new(path).set_type(ExeType::LinuxExe)
+}
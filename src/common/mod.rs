@@ -1,4 +1,10 @@
- #[cfg(feature = "system")]
+pub mod cmd;
+#[cfg(target_os = "linux")]
+pub mod sysfs;
+#[cfg(test)]
+pub mod test_util;
+pub mod text;
+#[cfg(feature = "system")]
 mod system;
 #[cfg(feature = "system")]
 pub use system::*;
\ No newline at end of file
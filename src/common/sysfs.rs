@@ -0,0 +1,34 @@
+//! Linux sysfs 读取工具
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  str::FromStr,
+};
+
+/// 读取属性文件并去除首尾空白
+pub fn read_str(path: impl AsRef<Path>) -> Option<String> {
+  fs::read_to_string(path).ok().map(|v| v.trim().to_string())
+}
+
+/// 读取数值属性
+pub fn read_num<T: FromStr>(path: impl AsRef<Path>) -> Option<T> {
+  read_str(path).and_then(|v| v.parse::<T>().ok())
+}
+
+/// 枚举 `{prefix}{N}` 形式的目录项, 按序号排序 如 thermal_zone0, cpu1
+pub fn indexed_entries(dir: impl AsRef<Path>, prefix: &str) -> Vec<(u32, PathBuf)> {
+  let mut entries: Vec<(u32, PathBuf)> = fs::read_dir(dir)
+    .map(|entries| {
+      entries
+        .flatten()
+        .filter_map(|entry| {
+          let fname = entry.file_name().into_string().ok()?;
+          let index = fname.strip_prefix(prefix)?.parse::<u32>().ok()?;
+          Some((index, entry.path()))
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  entries.sort_by_key(|(index, _)| *index);
+  entries
+}
@@ -0,0 +1,14 @@
+//! 测试工具
+use std::{fs, path::PathBuf};
+
+/// 在临时目录下按 (相对路径, 内容) 创建文件树, 目录名含进程号, 已存在时先清空
+pub fn sysfs_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+  let root = std::env::temp_dir().join(format!("hw-{}-{}", name, std::process::id()));
+  let _ = fs::remove_dir_all(&root);
+  for (file, content) in files {
+    let path = root.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+  }
+  root
+}
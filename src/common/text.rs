@@ -0,0 +1,33 @@
+//! 文本解析
+
+/// 按逗号拆分 CSV 行, 支持双引号包裹的字段
+pub fn csv_fields(line: &str) -> Vec<String> {
+  let (mut fields, mut field, mut quoted) = (vec![], String::new(), false);
+  let mut chars = line.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut field)),
+      _ => field.push(c),
+    }
+  }
+  fields.push(field);
+  fields
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_csv_fields() {
+    assert_eq!(
+      csv_fields(r#"MA-L,001B21,"Intel, Corp","say ""hi""",""#),
+      ["MA-L", "001B21", "Intel, Corp", r#"say "hi""#, ""]
+    );
+  }
+}
@@ -0,0 +1,320 @@
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+use crate::{
+  api_test::{BackendFuture, HardwareType, Sensor, SensorBackend, SensorType},
+  common::sysfs,
+  wmic::HardwareMonitor,
+};
+use e_utils::AnyResult;
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// hwmon 属性类别
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HwmonKind {
+  /// temp*_input 毫摄氏度
+  Temp,
+  /// fan*_input 转/分钟
+  Fan,
+  /// in*_input 毫伏
+  In,
+  /// power*_input 微瓦
+  Power,
+  /// curr*_input 毫安
+  Curr,
+}
+impl HwmonKind {
+  pub const ALL: [HwmonKind; 5] = [HwmonKind::Temp, HwmonKind::Fan, HwmonKind::In, HwmonKind::Power, HwmonKind::Curr];
+  /// 文件前缀
+  pub fn prefix(&self) -> &'static str {
+    match self {
+      HwmonKind::Temp => "temp",
+      HwmonKind::Fan => "fan",
+      HwmonKind::In => "in",
+      HwmonKind::Power => "power",
+      HwmonKind::Curr => "curr",
+    }
+  }
+  /// 对应的传感器类型
+  pub fn sensor_type(&self) -> SensorType {
+    match self {
+      HwmonKind::Temp => SensorType::Temperature,
+      HwmonKind::Fan => SensorType::Fan,
+      HwmonKind::In => SensorType::Voltage,
+      HwmonKind::Power => SensorType::Power,
+      HwmonKind::Curr => SensorType::Current,
+    }
+  }
+  /// sysfs 原始值到传感器单位的换算系数
+  pub fn scale(&self) -> f64 {
+    match self {
+      HwmonKind::Temp | HwmonKind::In | HwmonKind::Curr => 1_000.0,
+      HwmonKind::Fan => 1.0,
+      HwmonKind::Power => 1_000_000.0,
+    }
+  }
+  /// 从传感器类型获取
+  pub fn from_sensor_type(st: &SensorType) -> Vec<HwmonKind> {
+    HwmonKind::ALL
+      .into_iter()
+      .filter(|k| st == &SensorType::ALL || &k.sensor_type() == st)
+      .collect()
+  }
+}
+
+/// hwmon 芯片
+#[derive(Debug, Clone)]
+pub struct HwmonChip {
+  /// 目录名 如 hwmon0
+  pub id: String,
+  /// 驱动名称 如 coretemp
+  pub name: String,
+  /// 属性目录
+  pub path: PathBuf,
+  /// 硬件类型
+  pub hw_type: HardwareType,
+}
+impl HwmonChip {
+  /// 根据驱动名称推断硬件类型
+  pub fn parse_hw_type(name: &str) -> HardwareType {
+    let name = name.to_ascii_lowercase();
+    match name.as_str() {
+      "coretemp" | "k10temp" | "k8temp" | "zenpower" | "fam15h_power" | "cpu_thermal" | "via_cputemp" => HardwareType::CPU,
+      "nvme" | "drivetemp" => HardwareType::HDD,
+      "amdgpu" | "radeon" => HardwareType::GpuAti,
+      "nouveau" | "nvidia" => HardwareType::GpuNvidia,
+      "jc42" | "spd5118" | "ee1004" => HardwareType::RAM,
+      "acpitz" | "pch_cannonlake" | "pch_cometlake" | "pch_skylake" => HardwareType::Mainboard,
+      _ if ["nct", "it8", "w83", "f71", "asus", "dell_smm", "thinkpad"].iter().any(|p| name.starts_with(p)) => HardwareType::SuperIO,
+      _ => HardwareType::Unknown,
+    }
+  }
+  /// 读取属性文件
+  fn read_attr(&self, file: &str) -> Option<String> {
+    sysfs::read_str(self.path.join(file))
+  }
+  /// 读取数值属性
+  fn read_value(&self, file: &str, scale: f64) -> Option<f64> {
+    sysfs::read_num::<f64>(self.path.join(file)).map(|v| v / scale)
+  }
+  /// 枚举某类属性的序号 如 temp1_input -> 1
+  pub fn indexes(&self, kind: HwmonKind) -> Vec<u32> {
+    let mut indexes: Vec<u32> = fs::read_dir(&self.path)
+      .map(|entries| {
+        entries
+          .flatten()
+          .filter_map(|entry| {
+            let fname = entry.file_name().into_string().ok()?;
+            fname.strip_prefix(kind.prefix())?.strip_suffix("_input")?.parse::<u32>().ok()
+          })
+          .collect()
+      })
+      .unwrap_or_default();
+    indexes.sort();
+    indexes
+  }
+  /// 读取传感器
+  pub fn sensors(&self, kinds: &[HwmonKind]) -> Vec<Sensor> {
+    let mut sensors = vec![];
+    for kind in kinds {
+      let st = kind.sensor_type();
+      let scale = kind.scale();
+      for n in self.indexes(*kind) {
+        let attr = format!("{}{}", kind.prefix(), n);
+        let value = match self.read_value(&format!("{attr}_input"), scale) {
+          Some(v) => v,
+          None => {
+            crate::dp(format!("HWMON {}/{} 读取失败", self.id, attr));
+            continue;
+          }
+        };
+        let min = self.read_value(&format!("{attr}_min"), scale).unwrap_or(value);
+        let max = self
+          .read_value(&format!("{attr}_max"), scale)
+          .or_else(|| self.read_value(&format!("{attr}_crit"), scale))
+          .unwrap_or(value);
+        let label = self.read_attr(&format!("{attr}_label")).unwrap_or_else(|| format!("{} {}", self.name, attr));
+        sensors.push(Sensor {
+          Name: label,
+          Identifier: format!("/{}/{}/{}", self.id, self.name, attr),
+          _SensorType: st.to_string(),
+          SensorType: st.clone(),
+          Parent: self.hw_type.to_string(),
+          Value: value,
+          Min: min,
+          Max: max,
+          Index: n as i32,
+          data: String::new(),
+        });
+      }
+    }
+    sensors
+  }
+}
+
+/// Linux hwmon (/sys/class/hwmon)
+#[derive(Debug, Clone)]
+pub struct HWMON {
+  root: PathBuf,
+}
+impl HWMON {
+  /// 指定 sysfs 根目录, 用于测试目录
+  pub fn with_root(root: impl Into<PathBuf>) -> Self {
+    Self { root: root.into() }
+  }
+  /// 获取根目录
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+  /// 枚举芯片
+  pub fn chips(&self) -> AnyResult<Vec<HwmonChip>> {
+    let mut chips = vec![];
+    for entry in fs::read_dir(&self.root)?.flatten() {
+      let id = match entry.file_name().into_string() {
+        Ok(id) if id.starts_with("hwmon") => id,
+        _ => continue,
+      };
+      let dir = entry.path();
+      // 旧驱动的属性位于 device 子目录
+      let path = if dir.join("name").exists() { dir } else { dir.join("device") };
+      let name = match fs::read_to_string(path.join("name")) {
+        Ok(name) => name.trim().to_string(),
+        Err(_) => continue,
+      };
+      chips.push(HwmonChip {
+        hw_type: HwmonChip::parse_hw_type(&name),
+        id,
+        name,
+        path,
+      });
+    }
+    chips.sort_by_key(|chip| chip.id.trim_start_matches("hwmon").parse::<u32>().unwrap_or(u32::MAX));
+    Ok(chips)
+  }
+  /// 查询
+  pub fn query(&self, hw_type: HardwareType, sensor_type: SensorType) -> AnyResult<Vec<Sensor>> {
+    let hw_types = hw_type.clone().all();
+    let kinds = HwmonKind::from_sensor_type(&sensor_type);
+    let sensors: Vec<Sensor> = self
+      .chips()?
+      .into_iter()
+      .filter(|chip| hw_types.contains(&chip.hw_type))
+      .flat_map(|chip| chip.sensors(&kinds))
+      .collect();
+    if sensors.is_empty() {
+      return Err(format!("HWMON No sensors found for {} {}", hw_type, sensor_type).into());
+    }
+    Ok(sensors)
+  }
+}
+
+impl SensorBackend for HWMON {
+  fn name(&self) -> &str {
+    "HWMON"
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    crate::api_test::wait_ready::<Self>(10)
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move { HWMON::query(self, hw_type, sensor_type) })
+  }
+}
+
+impl HardwareMonitor for HWMON {
+  type HWType = HardwareType;
+  type SensorType = Sensor;
+  const CON_QUERY: &'static str = "/sys/class/hwmon";
+  const HW_QUERY: &'static str = "";
+  const SENSOR_QUERY: &'static str = "";
+  fn new() -> AnyResult<Self> {
+    Ok(Self::with_root(Self::CON_QUERY))
+  }
+  fn test(count: u64) -> AnyResult<()> {
+    for i in 1..=count {
+      match Self::new().and_then(|api| api.query(HardwareType::ALL, SensorType::ALL)) {
+        Ok(_) => {
+          crate::dp("HWMON ready");
+          return Ok(());
+        }
+        Err(e) => crate::wp(e.to_string()),
+      }
+      crate::dp(format!("Loading... ({}%/{}%)", i, count));
+      std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+    Err("HWMON load timeout".into())
+  }
+  fn stop() -> AnyResult<()> {
+    Ok(())
+  }
+  fn clean() -> AnyResult<()> {
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::sysfs_tree;
+
+  fn fixture() -> PathBuf {
+    sysfs_tree(
+      "hwmon",
+      &[
+        ("hwmon0/name", "acpitz\n"),
+        ("hwmon0/temp1_input", "27800\n"),
+        ("hwmon0/temp1_crit", "105000\n"),
+        ("hwmon1/name", "coretemp\n"),
+        ("hwmon1/temp1_label", "Package id 0\n"),
+        ("hwmon1/temp1_input", "45000\n"),
+        ("hwmon1/temp1_max", "80000\n"),
+        ("hwmon1/temp1_crit", "100000\n"),
+        ("hwmon1/temp2_label", "Core 0\n"),
+        ("hwmon1/temp2_input", "43500\n"),
+        ("hwmon2/device/name", "nct6775\n"),
+        ("hwmon2/device/fan1_input", "1250\n"),
+        ("hwmon2/device/fan1_min", "300\n"),
+        ("hwmon2/device/in0_input", "1104\n"),
+        ("hwmon2/device/in0_label", "Vcore\n"),
+        ("hwmon2/device/curr1_input", "1500\n"),
+        ("hwmon10/name", "amdgpu\n"),
+        ("hwmon10/power1_input", "35000000\n"),
+      ],
+    )
+  }
+
+  #[test]
+  fn test_hwmon_fixture() {
+    let root = fixture();
+    let api = HWMON::with_root(&root);
+
+    let chips = api.chips().unwrap();
+    let ids: Vec<&str> = chips.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["hwmon0", "hwmon1", "hwmon2", "hwmon10"]);
+
+    let temps = api.query(HardwareType::CPU, SensorType::Temperature).unwrap();
+    assert_eq!(temps.len(), 2);
+    assert_eq!(temps[0].Name, "Package id 0");
+    assert_eq!(temps[0].Value, 45.0);
+    assert_eq!(temps[0].Max, 80.0);
+    assert_eq!(temps[1].Value, 43.5);
+    assert_eq!(temps[1].Max, 43.5);
+
+    let acpi = api.query(HardwareType::Mainboard, SensorType::Temperature).unwrap();
+    assert_eq!(acpi[0].Max, 105.0);
+    assert_eq!(acpi[0].Identifier, "/hwmon0/acpitz/temp1");
+
+    let fans = api.query(HardwareType::ALL, SensorType::Fan).unwrap();
+    assert_eq!((fans[0].Value, fans[0].Min), (1250.0, 300.0));
+
+    let volts = api.query(HardwareType::SuperIO, SensorType::Voltage).unwrap();
+    assert_eq!((volts[0].Name.as_str(), volts[0].Value), ("Vcore", 1.104));
+    assert_eq!(api.query(HardwareType::SuperIO, SensorType::Current).unwrap()[0].Value, 1.5);
+    assert_eq!(api.query(HardwareType::GpuAti, SensorType::Power).unwrap()[0].Value, 35.0);
+    assert_eq!(api.query(HardwareType::ALL, SensorType::ALL).unwrap().len(), 7);
+    assert!(api.query(HardwareType::RAM, SensorType::ALL).is_err());
+
+    fs::remove_dir_all(root).unwrap();
+  }
+}
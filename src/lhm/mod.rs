@@ -1,7 +1,7 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 use crate::{
-  api_test::{HardwareType, Sensor, SensorType},
+  api_test::{BackendFuture, ExeBackend, HardwareType, Sensor, SensorBackend, SensorType},
   wmic::{Hardware, HardwareMonitor},
 };
 use e_utils::AnyResult;
@@ -13,8 +13,6 @@ pub type RawQuery = HashMap<String, Variant>;
 #[derive(Clone, Debug)]
 pub struct LHM(WMIConnection);
 impl LHM {
-  pub const EXE: &'static str = "LibreHardwareMonitor.exe";
-  pub const DIR: &'static str = "plugins/LHM";
   /// 获取WMI连接
   pub fn get(&self) -> &WMIConnection {
     &self.0
@@ -93,6 +91,34 @@ impl LHM {
     Ok(sensors)
   }
 }
+impl SensorBackend for LHM {
+  fn name(&self) -> &str {
+    "LHM"
+  }
+  fn prepare(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::start() })
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    crate::api_test::wait_ready::<Self>(100)
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move {
+      self
+        .a_query(hw_type, sensor_type)
+        .await
+        .map(|v| v.into_iter().filter(|v| v.Name != "Bus Speed").collect())
+    })
+  }
+  fn teardown(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::shutdown() })
+  }
+}
+
+impl ExeBackend for LHM {
+  const EXE: &'static str = "LibreHardwareMonitor.exe";
+  const DIR: &'static str = "plugins/LHM";
+}
+
 impl HardwareMonitor for LHM {
   type HWType = HardwareType;
   type SensorType = Sensor;
@@ -37,10 +37,16 @@ pub use cli::*;
 
 #[cfg(feature = "disk")]
 pub mod disk;
+#[cfg(feature = "plan")]
+pub mod plan;
 #[cfg(all(feature = "core-temp", target_os = "windows"))]
 pub mod core_temp;
 #[cfg(all(feature = "argus-monitor", target_os = "windows"))]
 pub mod argus_monitor;
+#[cfg(all(feature = "hwmon", target_os = "linux"))]
+pub mod hwmon;
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+pub mod rapl;
 pub mod api_test;
 pub mod common;
 pub mod os_more;
@@ -1,7 +1,7 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 use crate::{
-  api_test::{HardwareType, Sensor, SensorType},
+  api_test::{BackendFuture, ExeBackend, HardwareType, Sensor, SensorBackend, SensorType},
   wmic::{Hardware, HardwareMonitor},
 };
 use e_utils::{
@@ -16,8 +16,6 @@ pub type RawQuery = HashMap<String, Variant>;
 #[derive(Clone, Debug)]
 pub struct OHM(WMIConnection);
 impl OHM {
-  pub const EXE: &'static str = "OpenHardwareMonitor.exe";
-  pub const DIR: &'static str = "plugins/OHM";
   /// 获取WMI连接
   pub fn get(&self) -> &WMIConnection {
     &self.0
@@ -96,6 +94,34 @@ impl OHM {
     Ok(sensors)
   }
 }
+impl SensorBackend for OHM {
+  fn name(&self) -> &str {
+    "OHM"
+  }
+  fn prepare(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::start() })
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    crate::api_test::wait_ready::<Self>(100)
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move {
+      self
+        .a_query(hw_type, sensor_type)
+        .await
+        .map(|v| v.into_iter().filter(|v| v.Name != "Bus Speed").collect())
+    })
+  }
+  fn teardown(&mut self) -> BackendFuture<'_, ()> {
+    Box::pin(async { Self::shutdown() })
+  }
+}
+
+impl ExeBackend for OHM {
+  const EXE: &'static str = "OpenHardwareMonitor.exe";
+  const DIR: &'static str = "plugins/OHM";
+}
+
 impl HardwareMonitor for OHM {
   type HWType = HardwareType;
   type SensorType = Sensor;
@@ -0,0 +1,101 @@
+//! Linux cpufreq 核心频率 (/sys/devices/system/cpu)
+use super::CoreClock;
+use crate::common::sysfs;
+use std::path::Path;
+
+/// 默认 sysfs 路径
+pub const CPU_ROOT: &str = "/sys/devices/system/cpu";
+/// 回退使用的 cpuinfo 路径
+pub const CPUINFO_PATH: &str = "/proc/cpuinfo";
+
+/// 从 cpufreq 读取每个核心频率, 单位由 kHz 转为 MHz
+pub fn cpufreq_clocks(root: &Path) -> Vec<CoreClock> {
+  sysfs::indexed_entries(root, "cpu")
+    .into_iter()
+    .filter_map(|(index, dir)| {
+      let dir = dir.join("cpufreq");
+      let value = sysfs::read_num::<f64>(dir.join("scaling_cur_freq"))? / 1000.0;
+      let min = sysfs::read_num::<f64>(dir.join("cpuinfo_min_freq")).map_or(value, |v| v / 1000.0);
+      let max = sysfs::read_num::<f64>(dir.join("cpuinfo_max_freq")).map_or(value, |v| v / 1000.0);
+      Some(CoreClock {
+        index: index as usize,
+        name: format!("cpu{}", index),
+        value,
+        min,
+        max,
+      })
+    })
+    .collect()
+}
+
+/// 解析 /proc/cpuinfo 中的 "cpu MHz", 没有最低最高频率
+pub fn parse_cpuinfo(content: &str) -> Vec<CoreClock> {
+  let mut clocks = vec![];
+  let mut index = None;
+  for line in content.lines() {
+    let Some((key, value)) = line.split_once(':') else {
+      continue;
+    };
+    match key.trim() {
+      "processor" => index = value.trim().parse::<usize>().ok(),
+      "cpu MHz" => {
+        if let (Some(i), Ok(mhz)) = (index.take(), value.trim().parse::<f64>()) {
+          clocks.push(CoreClock {
+            index: i,
+            name: format!("cpu{}", i),
+            value: mhz,
+            min: mhz,
+            max: mhz,
+          });
+        }
+      }
+      _ => {}
+    }
+  }
+  clocks
+}
+
+/// 优先 cpufreq, 不可用时回退到 cpuinfo
+pub fn core_clocks(root: &Path, cpuinfo: &Path) -> Vec<CoreClock> {
+  let clocks = cpufreq_clocks(root);
+  if !clocks.is_empty() {
+    return clocks;
+  }
+  std::fs::read_to_string(cpuinfo).map(|v| parse_cpuinfo(&v)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::sysfs_tree;
+  use std::fs;
+
+  #[test]
+  fn test_cpufreq_fixture() {
+    let root = sysfs_tree(
+      "cpufreq",
+      &[
+        ("cpu0/cpufreq/scaling_cur_freq", "4800000\n"),
+        ("cpu0/cpufreq/cpuinfo_min_freq", "800000\n"),
+        ("cpu0/cpufreq/cpuinfo_max_freq", "5400000\n"),
+        ("cpu1/cpufreq/scaling_cur_freq", "3500000\n"),
+        ("cpu1/cpufreq/cpuinfo_min_freq", "800000\n"),
+        ("cpu1/cpufreq/cpuinfo_max_freq", "4200000\n"),
+        ("cpufreq/boost", "1\n"),
+      ],
+    );
+    let cpuinfo = root.join("cpuinfo");
+    fs::write(&cpuinfo, "processor\t: 0\ncpu MHz\t\t: 2400.000\n\nprocessor\t: 1\ncpu MHz\t\t: 2399.5\n").unwrap();
+
+    let clocks = core_clocks(&root, &cpuinfo);
+    assert_eq!(clocks.len(), 2);
+    assert_eq!((clocks[0].value, clocks[0].min, clocks[0].max), (4800.0, 800.0, 5400.0));
+    assert_eq!((clocks[1].value, clocks[1].max), (3500.0, 4200.0));
+
+    let clocks = core_clocks(&root.join("missing"), &cpuinfo);
+    assert_eq!(clocks.len(), 2);
+    assert_eq!((clocks[1].index, clocks[1].value), (1, 2399.5));
+
+    fs::remove_dir_all(root).unwrap();
+  }
+}
@@ -1,18 +1,32 @@
 #[allow(unused)]
-use crate::api_test::{HardwareType, Sensor, SensorType};
+use crate::api_test::{BackendFuture, HardwareType, Sensor, SensorBackend, SensorType};
+use crate::os_more::net_interface::TrafficSampler;
 use crate::share::bytes_to_gib;
-use e_utils::{
-  cmd::{Cmd, ExeType},
-  AnyRes as _,
-};
 pub use sysinfo::*;
+#[cfg(target_os = "linux")]
+pub mod cpufreq;
+#[cfg(target_os = "linux")]
+pub mod thermal;
+
+/// 单个核心频率 (MHz)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreClock {
+  pub index: usize,
+  pub name: String,
+  pub value: f64,
+  pub min: f64,
+  pub max: f64,
+}
+
+/// 首次查询网络速率时两次采样的间隔
+pub const TRAFFIC_SAMPLE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
 
 /// OS
 #[derive(Debug)]
-pub struct OS(System);
+pub struct OS(System, TrafficSampler);
 impl OS {
   pub fn new() -> Self {
-    Self(System::new())
+    Self(System::new(), TrafficSampler::new())
   }
   /// 获取系统
   pub fn get_mut(&mut self) -> &mut System {
@@ -23,12 +37,72 @@ impl OS {
   }
 }
 
+impl SensorBackend for OS {
+  fn name(&self) -> &str {
+    "OS"
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move { OS::query(self, hw_type, sensor_type) })
+  }
+}
+
 /// CPU
 impl OS {
   /// 获取cpu核心数
   pub fn get_cpu_core_count(&self) -> usize {
     self.0.cpus().len()
   }
+  /// 获取每个核心的频率
+  pub fn core_clocks(&mut self) -> Vec<CoreClock> {
+    #[cfg(target_os = "linux")]
+    {
+      let clocks = cpufreq::core_clocks(std::path::Path::new(cpufreq::CPU_ROOT), std::path::Path::new(cpufreq::CPUINFO_PATH));
+      if !clocks.is_empty() {
+        return clocks;
+      }
+    }
+    self.0.refresh_cpu_specifics(CpuRefreshKind::nothing().with_frequency());
+    let frequency = self.total_clock();
+    self
+      .0
+      .cpus()
+      .iter()
+      .enumerate()
+      .map(|(index, cpu)| {
+        let value = frequency.unwrap_or(cpu.frequency() as f64);
+        CoreClock {
+          index,
+          name: cpu.name().into(),
+          value,
+          min: value,
+          max: value,
+        }
+      })
+      .collect()
+  }
+  /// 通过性能计数器计算当前整体频率
+  #[cfg(target_os = "windows")]
+  fn total_clock(&self) -> Option<f64> {
+    use e_utils::{
+      cmd::{Cmd, ExeType},
+      AnyRes as _,
+    };
+    let max_speed = self.0.cpus().iter().map(|cpu| cpu.frequency()).max().unwrap_or(0);
+    Cmd::new(&format!(
+      "$ProcessorPerformance = (Get-Counter -Counter \"\\Processor Information(_Total)\\% Processor Performance\").CounterSamples.CookedValue; \
+           $CurrentClockSpeed = {}*($ProcessorPerformance/100); \
+           $CurrentClockSpeed",
+      max_speed
+    ))
+    .set_type(ExeType::PowerShell)
+    .output()
+    .and_then(|v| v.stdout.parse::<f64>().any())
+    .ok()
+  }
+  #[cfg(not(target_os = "windows"))]
+  fn total_clock(&self) -> Option<f64> {
+    None
+  }
 }
 /// 接口
 impl OS {
@@ -37,12 +111,21 @@ impl OS {
     let sensor_types = sensor_type.all();
     let res: Vec<Vec<Sensor>> = hw_types
       .into_iter()
-      .flat_map(|hwt| match hwt {
-        HardwareType::CPU => Some(self.query_cpu(&sensor_types, &hwt)),
-        HardwareType::RAM => Some(self.query_memory(&sensor_types, &hwt)),
-        _ => {
+      .flat_map(|hwt| {
+        #[allow(unused_mut)]
+        let mut sensors = match hwt {
+          HardwareType::CPU => self.query_cpu(&sensor_types, &hwt),
+          HardwareType::RAM => self.query_memory(&sensor_types, &hwt),
+          HardwareType::Network => self.query_network(&sensor_types, &hwt),
+          _ => vec![],
+        };
+        #[cfg(target_os = "linux")]
+        sensors.extend(thermal::query_thermal(std::path::Path::new(thermal::THERMAL_ROOT), &sensor_types, &hwt));
+        if sensors.is_empty() {
           crate::dp(format!("OS HW type {} is not supported", hwt));
           None
+        } else {
+          Some(sensors)
         }
       })
       .collect();
@@ -120,44 +203,98 @@ impl OS {
       .flatten()
       .collect()
   }
+  /// 网卡速率, 排除环回与虚拟接口
+  fn query_network(&mut self, sts: &[SensorType], parent: &HardwareType) -> Vec<Sensor> {
+    let sts: Vec<&SensorType> = sts.iter().filter(|st| matches!(st, SensorType::Throughput | SensorType::DataRate)).collect();
+    if sts.is_empty() {
+      crate::dp(format!("OS {} Sensor type is not supported", parent));
+      return vec![];
+    }
+    let counters = || -> Vec<(String, crate::os_more::net_interface::InterfaceStats)> {
+      crate::os_more::net_interface::get_interfaces()
+        .into_iter()
+        .filter(|x| !x.is_virtual())
+        .filter_map(|x| Some((x.friendly_name, x.stats?)))
+        .collect()
+    };
+    // 首次采样后等待一个间隔再采样
+    let fresh: Vec<_> = counters().into_iter().filter(|(name, _)| !self.1.is_primed(name)).collect();
+    if !fresh.is_empty() {
+      fresh.into_iter().for_each(|(name, stats)| {
+        self.1.sample(&name, stats);
+      });
+      std::thread::sleep(TRAFFIC_SAMPLE_INTERVAL);
+    }
+    let mut sensors = vec![];
+    for (index, (name, stats)) in counters().into_iter().enumerate() {
+      let Some(reading) = self.1.sample(&name, stats) else {
+        continue;
+      };
+      for st in &sts {
+        let values = match st {
+          SensorType::Throughput => vec![(name.clone(), "throughput", reading.rate.total, reading.min.total, reading.max.total)],
+          _ => vec![
+            (format!("{} Download", name), "rx", reading.rate.rx, reading.min.rx, reading.max.rx),
+            (format!("{} Upload", name), "tx", reading.rate.tx, reading.min.tx, reading.max.tx),
+          ],
+        };
+        sensors.extend(values.into_iter().map(|(sensor_name, key, value, min, max)| Sensor {
+          Name: sensor_name,
+          Identifier: format!("/nic/{}/{}", name, key),
+          _SensorType: st.to_string(),
+          SensorType: (*st).clone(),
+          Parent: parent.to_string(),
+          Value: value.round(),
+          Min: min.round(),
+          Max: max.round(),
+          Index: index as i32,
+          data: value.round().to_string(),
+        }));
+      }
+    }
+    sensors
+  }
   fn query_cpu(&mut self, sts: &Vec<SensorType>, parent: &HardwareType) -> Vec<Sensor> {
     sts
       .into_iter()
       .flat_map(|st| match st {
-        SensorType::Clock => {
-          self.0.refresh_cpu_specifics(CpuRefreshKind::nothing().with_frequency());
-          let cpu_res = self.0.cpus().iter().map(|cpu| cpu.frequency());
-          let max_speed = cpu_res.max().unwrap_or(0);
-          let frequency = Cmd::new(&format!(
-            "$ProcessorPerformance = (Get-Counter -Counter \"\\Processor Information(_Total)\\% Processor Performance\").CounterSamples.CookedValue; \
-           $CurrentClockSpeed = {}*($ProcessorPerformance/100); \
-           $CurrentClockSpeed",
-            max_speed
-          ))
-          .set_type(ExeType::PowerShell)
-          .output()
-          .and_then(|v| v.stdout.parse::<f64>().any())
-          .unwrap_or(0.0);
-          self.0.refresh_cpu_specifics(CpuRefreshKind::nothing().with_frequency());
-          let res: Vec<Sensor> = self
-            .0
-            .cpus()
-            .iter()
-            .enumerate()
-            .map(|(i, cpu)| Sensor {
-              Name: cpu.name().into(),
-              Identifier: cpu.vendor_id().into(),
+        SensorType::Clock => Some(
+          self
+            .core_clocks()
+            .into_iter()
+            .map(|clock| Sensor {
+              Name: clock.name,
+              Identifier: format!("/cpu/{}/clock", clock.index),
               _SensorType: st.to_string(),
               SensorType: st.clone(),
               Parent: parent.to_string(),
-              Value: frequency,
-              Min: frequency,
-              Max: frequency,
-              Index: i as i32,
-              data: frequency.to_string(),
+              Value: clock.value,
+              Min: clock.min,
+              Max: clock.max,
+              Index: clock.index as i32,
+              data: clock.value.to_string(),
             })
-            .collect();
-          Some(res)
+            .collect(),
+        ),
+        SensorType::ClockAverage => {
+          let clocks = self.core_clocks();
+          if clocks.is_empty() {
+            return None;
+          }
+          let values = clocks.iter().map(|clock| clock.value);
+          let value = (values.clone().sum::<f64>() / clocks.len() as f64).round();
+          Some(vec![Sensor {
+            Name: "CPU Clock Average".into(),
+            Identifier: "/cpu/clock/average".into(),
+            _SensorType: st.to_string(),
+            SensorType: st.clone(),
+            Parent: parent.to_string(),
+            Value: value,
+            Min: values.clone().reduce(f64::min).unwrap_or(value),
+            Max: values.reduce(f64::max).unwrap_or(value),
+            Index: 0,
+            data: value.to_string(),
+          }])
         }
         SensorType::Load => {
           self.0.refresh_cpu_specifics(CpuRefreshKind::nothing().with_cpu_usage());
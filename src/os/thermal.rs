@@ -0,0 +1,218 @@
+//! Linux thermal 子系统 (/sys/class/thermal)
+use crate::{
+  api_test::{HardwareType, Sensor, SensorType},
+  common::sysfs,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 默认 sysfs 路径
+pub const THERMAL_ROOT: &str = "/sys/class/thermal";
+
+/// 温控触发点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TripPoint {
+  /// 类型 critical/hot/passive/active
+  pub kind: String,
+  /// 触发温度 (°C)
+  pub temp: f64,
+}
+
+/// 温度区域 thermal_zone*
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThermalZone {
+  pub index: u32,
+  /// 区域类型 如 x86_pkg_temp, acpitz
+  pub name: String,
+  /// 当前温度 (°C)
+  pub temp: f64,
+  pub trips: Vec<TripPoint>,
+}
+
+/// 散热设备 cooling_device*
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoolingDevice {
+  pub index: u32,
+  /// 设备类型 如 Processor, Fan
+  pub name: String,
+  pub cur_state: u64,
+  pub max_state: u64,
+}
+
+/// 根据区域类型推断硬件类型
+pub fn zone_hw_type(name: &str) -> HardwareType {
+  let name = name.to_ascii_lowercase();
+  if name.contains("pkg_temp") || name.contains("cpu") || name.contains("soc") || name.contains("k10temp") {
+    HardwareType::CPU
+  } else if name.contains("gpu") {
+    HardwareType::GpuAti
+  } else if name.starts_with("acpitz") || name.starts_with("pch_") || name.starts_with("int340") {
+    HardwareType::Mainboard
+  } else if name.contains("nvme") || name.contains("disk") {
+    HardwareType::HDD
+  } else {
+    HardwareType::Unknown
+  }
+}
+
+/// 根据散热设备类型推断硬件类型
+pub fn cooling_hw_type(name: &str) -> HardwareType {
+  let name = name.to_ascii_lowercase();
+  if name == "processor" || name.contains("cpufreq") || name.contains("powerclamp") {
+    HardwareType::CPU
+  } else if name.contains("fan") {
+    HardwareType::Mainboard
+  } else {
+    HardwareType::Unknown
+  }
+}
+
+/// 读取所有温度区域
+pub fn thermal_zones(root: &Path) -> Vec<ThermalZone> {
+  sysfs::indexed_entries(root, "thermal_zone")
+    .into_iter()
+    .filter_map(|(index, dir)| {
+      let name = sysfs::read_str(dir.join("type"))?;
+      let temp = sysfs::read_num::<f64>(dir.join("temp"))? / 1000.0;
+      let trips = (0..)
+        .map_while(|i| {
+          let temp = sysfs::read_num::<f64>(dir.join(format!("trip_point_{i}_temp")))?;
+          let kind = sysfs::read_str(dir.join(format!("trip_point_{i}_type"))).unwrap_or_default();
+          Some(TripPoint { kind, temp: temp / 1000.0 })
+        })
+        // 部分驱动用 0 或负值表示未启用
+        .filter(|trip| trip.temp > 0.0)
+        .collect();
+      Some(ThermalZone { index, name, temp, trips })
+    })
+    .collect()
+}
+
+/// 读取所有散热设备
+pub fn cooling_devices(root: &Path) -> Vec<CoolingDevice> {
+  sysfs::indexed_entries(root, "cooling_device")
+    .into_iter()
+    .filter_map(|(index, dir)| {
+      Some(CoolingDevice {
+        index,
+        name: sysfs::read_str(dir.join("type"))?,
+        cur_state: sysfs::read_num(dir.join("cur_state"))?,
+        max_state: sysfs::read_num(dir.join("max_state"))?,
+      })
+    })
+    .collect()
+}
+
+impl ThermalZone {
+  pub fn to_sensor(&self, st: &SensorType, parent: &HardwareType) -> Sensor {
+    let trips = self.trips.iter().map(|trip| trip.temp);
+    let min = trips.clone().reduce(f64::min).unwrap_or(self.temp);
+    let max = trips.reduce(f64::max).unwrap_or(self.temp);
+    Sensor {
+      Name: self.name.clone(),
+      Identifier: format!("/thermal/thermal_zone{}", self.index),
+      _SensorType: st.to_string(),
+      SensorType: st.clone(),
+      Parent: parent.to_string(),
+      Value: self.temp,
+      Min: min,
+      Max: max,
+      Index: self.index as i32,
+      data: self.temp.to_string(),
+    }
+  }
+}
+
+impl CoolingDevice {
+  /// 当前档位百分比
+  pub fn load(&self) -> f64 {
+    if self.max_state == 0 {
+      0.0
+    } else {
+      (self.cur_state as f64 / self.max_state as f64 * 100.0).round()
+    }
+  }
+  pub fn to_sensor(&self, st: &SensorType, parent: &HardwareType) -> Sensor {
+    Sensor {
+      Name: self.name.clone(),
+      Identifier: format!("/thermal/cooling_device{}", self.index),
+      _SensorType: st.to_string(),
+      SensorType: st.clone(),
+      Parent: parent.to_string(),
+      Value: self.load(),
+      Min: 0.0,
+      Max: 100.0,
+      Index: self.index as i32,
+      data: format!("{}/{}", self.cur_state, self.max_state),
+    }
+  }
+}
+
+/// 查询 parent 硬件下的温度与散热设备传感器
+pub fn query_thermal(root: &Path, sts: &[SensorType], parent: &HardwareType) -> Vec<Sensor> {
+  sts
+    .iter()
+    .flat_map(|st| match st {
+      SensorType::Temperature => thermal_zones(root)
+        .into_iter()
+        .filter(|zone| &zone_hw_type(&zone.name) == parent)
+        .map(|zone| zone.to_sensor(st, parent))
+        .collect(),
+      SensorType::Control => cooling_devices(root)
+        .into_iter()
+        .filter(|dev| &cooling_hw_type(&dev.name) == parent)
+        .map(|dev| dev.to_sensor(st, parent))
+        .collect(),
+      _ => vec![],
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::sysfs_tree;
+  use std::fs;
+
+  #[test]
+  fn test_thermal_fixture() {
+    let root = sysfs_tree(
+      "thermal",
+      &[
+        ("thermal_zone0/type", "acpitz\n"),
+        ("thermal_zone0/temp", "27800\n"),
+        ("thermal_zone0/trip_point_0_temp", "105000\n"),
+        ("thermal_zone0/trip_point_0_type", "critical\n"),
+        ("thermal_zone1/type", "x86_pkg_temp\n"),
+        ("thermal_zone1/temp", "52000\n"),
+        ("thermal_zone1/trip_point_0_temp", "0\n"),
+        ("thermal_zone1/trip_point_0_type", "passive\n"),
+        ("thermal_zone1/trip_point_1_temp", "85000\n"),
+        ("thermal_zone1/trip_point_1_type", "passive\n"),
+        ("thermal_zone1/trip_point_2_temp", "100000\n"),
+        ("thermal_zone1/trip_point_2_type", "critical\n"),
+        ("cooling_device0/type", "Processor\n"),
+        ("cooling_device0/cur_state", "1\n"),
+        ("cooling_device0/max_state", "4\n"),
+        ("cooling_device1/type", "Fan\n"),
+        ("cooling_device1/cur_state", "0\n"),
+        ("cooling_device1/max_state", "0\n"),
+      ],
+    );
+
+    let zones = thermal_zones(&root);
+    assert_eq!(zones.len(), 2);
+    assert_eq!(zones[1].trips.len(), 2);
+
+    let cpu = query_thermal(&root, &[SensorType::Temperature, SensorType::Control], &HardwareType::CPU);
+    assert_eq!(cpu.len(), 2);
+    assert_eq!((cpu[0].Value, cpu[0].Min, cpu[0].Max), (52.0, 85.0, 100.0));
+    assert_eq!((cpu[1].SensorType.clone(), cpu[1].Value), (SensorType::Control, 25.0));
+
+    let board = query_thermal(&root, &[SensorType::Temperature, SensorType::Control], &HardwareType::Mainboard);
+    assert_eq!((board[0].Value, board[0].Max), (27.8, 105.0));
+    assert_eq!((board[1].Value, board[1].data.as_str()), (0.0, "0/0"));
+
+    fs::remove_dir_all(root).unwrap();
+  }
+}
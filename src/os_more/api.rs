@@ -22,6 +22,12 @@ pub enum Type {
   Desktop,
   Drive,
   MemoryManufacturerPartNumber,
+  BiosInfo,
+  SystemInfo,
+  BoardInfo,
+  ChassisInfo,
+  ProcessorInfo,
+  MemoryModules,
 }
 #[allow(unused)]
 pub async fn query_os_more<T: AsRef<str>>(infos: &[Type], args: &[T], filter: &[T], is_full: bool) -> e_utils::AnyResult<Vec<String>> {
@@ -56,8 +62,26 @@ pub async fn query_os_more<T: AsRef<str>>(infos: &[Type], args: &[T], filter: &[
 }
 
 pub mod network {
+  /// 默认的未烧录 MAC, 可由 check-mac 规则文件替代
   pub const MAC_CHECKS: [&str; 4] = ["00-00-00-00-00-00", "88-88-88-88-87-88", "88-88-88-88-88-88", "TO BE FILLED BY O.E.M."];
 }
+/// 读取 args[count] 的 ping 次数, 以及 args[limit], args[limit+1] 的最大丢包率(%)和最大平均延迟(ms)
+#[cfg(feature = "network")]
+fn ping_options<T: AsRef<str>>(args: &[T], count: usize, limit: usize) -> e_utils::AnyResult<crate::os_more::net_manage::PingOptions> {
+  let arg = |i: usize| args.get(i).map(AsRef::as_ref).filter(|v| !v.is_empty());
+  let mut opts = crate::os_more::net_manage::PingOptions::default();
+  if let Some(v) = arg(count) {
+    opts.count = v.parse().map_err(|_| format!("Args Error Count {} ", count))?;
+  }
+  if let Some(v) = arg(limit) {
+    opts.max_loss = v.parse().map_err(|_| format!("Args Error Max Loss {} ", limit))?;
+  }
+  if let Some(v) = arg(limit + 1) {
+    opts.max_avg_rtt = Some(v.parse().map_err(|_| format!("Args Error Max RTT {} ", limit + 1))?);
+  }
+  Ok(opts)
+}
+
 /// 网络查询
 #[cfg(feature = "network")]
 pub async fn network_query<T: AsRef<str>>(info: &super::Type, args: &[T], filter: &[T], is_full: bool) -> e_utils::AnyResult<String> {
@@ -73,7 +97,7 @@ pub async fn network_query<T: AsRef<str>>(info: &super::Type, args: &[T], filter
         )?),
         "print" => {
           if is_full {
-            let ifaces = crate::os_more::net_interface::get_interfaces();
+            let ifaces = crate::os_more::net_interface::query_interfaces(&filter_refs)?;
             let count = ifaces.len();
             for iface in ifaces {
               crate::p(serde_json::to_string_pretty(&iface)?)
@@ -90,62 +114,114 @@ pub async fn network_query<T: AsRef<str>>(info: &super::Type, args: &[T], filter
         }
         "check-mac" => {
           let count = args.get(1).and_then(|x| x.as_ref().parse::<usize>().ok()).unwrap_or(0);
-          let ifaces = crate::os_more::net_interface::get_interfaces_simple(filter_refs)?;
+          // 规则文件替代默认的 MAC_CHECKS
+          let rules = match args.get(2).map(AsRef::as_ref).filter(|v| !v.is_empty()) {
+            Some(path) => crate::os_more::net_interface::MacRules::load(path)?,
+            None => crate::os_more::net_interface::MacRules::default(),
+          };
+          let oui = rules.load_oui()?;
+          let full = crate::os_more::net_interface::query_interfaces(&filter_refs)?;
+          let ifaces: Vec<InterfaceSimple> = full.iter().map(|x| x.to_simple()).collect();
           // 提前检查数量
           if count > 0 && count != ifaces.len() {
             return Err(format!("正确网口数量:{} 实际网口数量:{}", count, ifaces.len()).into());
           }
 
-          // 使用 HashSet 加速 MAC 检查
-          use std::collections::{HashMap, HashSet};
-
-          // 预编译无效 MAC 模式到 HashSet
-          let mac_checks: HashSet<&str> = network::MAC_CHECKS.into();
-
-          // 构建 MAC 地址映射表 (MAC -> 接口列表)
-          let mut mac_map: HashMap<&str, Vec<&InterfaceSimple>> = HashMap::new();
-          for iface in &ifaces {
-            mac_map.entry(&iface.mac_addr).or_default().push(iface);
-          }
-
           // 单次遍历检查所有接口
-          for iface in &ifaces {
+          for (raw, iface) in full.iter().zip(&ifaces) {
             let mac = &iface.mac_addr;
-            // 检查无效 MAC（O(1) 时间）
-            if mac_checks.contains(mac.as_str()) {
+            let vendor = oui.as_ref().and_then(|db| db.vendor(raw.hardware_mac())).unwrap_or_default();
+            // 按规则检查未烧录, 组播/本地管理位, 厂商与范围
+            if let Err(reason) = rules.check(raw.hardware_mac(), oui.as_ref()) {
               return Err(
                 format!(
-                  "FAIL MAC地址未烧录{}, INTERFACE={}, MAC={}, TYPE={}, IP={}, STATUS={}",
-                  mac, iface.friendly_name, mac, iface.if_type, iface.ipv4, iface.network_status
+                  "FAIL {}{}, INTERFACE={}, MAC={}, TYPE={}, IP={}, STATUS={}",
+                  reason, mac, iface.friendly_name, mac, iface.if_type, iface.ipv4, iface.network_status
                 )
                 .into(),
               );
             }
-            // 检查重复 MAC（O(1) 时间）
-            if let Some(entries) = mac_map.get(mac.as_str()) {
-              if entries.iter().any(|e| e.friendly_name != iface.friendly_name) {
-                let repeat_mac = entries.iter().find(|e| e.friendly_name != iface.friendly_name).ok_or("重复MAC地址获取失败")?;
-                return Err(
-                  format!(
-                    "FAIL {}重复MAC地址{}, INTERFACE={}, MAC={}, TYPE={}, IP={}, STATUS={}",
-                    repeat_mac.friendly_name, mac, iface.friendly_name, mac, iface.if_type, iface.ipv4, iface.network_status
-                  )
-                  .into(),
-                );
-              }
+            // 检查重复 MAC, 忽略 bond/bridge/VLAN 共用的 MAC
+            if let Some(repeat) = crate::os_more::net_interface::find_duplicate_mac(&full, raw).filter(|_| rules.unique) {
+              return Err(
+                format!(
+                  "FAIL {}重复MAC地址{}, INTERFACE={}, MAC={}, TYPE={}, IP={}, STATUS={}",
+                  repeat.friendly_name, mac, iface.friendly_name, mac, iface.if_type, iface.ipv4, iface.network_status
+                )
+                .into(),
+              );
             }
 
             // 延迟日志输出到检查通过后
             crate::dp(format!(
-              "PASS, INTERFACE={}, MAC={}, TYPE={}, IP={}, STATUS={}, SPEED={}",
-              iface.friendly_name, mac, iface.if_type, iface.ipv4, iface.network_status, iface.speed_mb
+              "PASS, INTERFACE={}, MAC={}, VENDOR={}, TYPE={}, IP={}, STATUS={}, SPEED={}",
+              iface.friendly_name, mac, vendor, iface.if_type, iface.ipv4, iface.network_status, iface.speed_mb
             ));
           }
           Ok(serde_json::to_string(&ifaces)?)
         }
+        "watch" => {
+          let secs = args.get(1).and_then(|x| x.as_ref().parse::<u64>().ok()).unwrap_or(60);
+          let max_flaps = args.get(2).and_then(|x| x.as_ref().parse::<usize>().ok());
+          let interval = args.get(3).and_then(|x| x.as_ref().parse::<u64>().ok()).unwrap_or(1000);
+          let names: Vec<String> = crate::os_more::net_interface::query_interfaces(&filter_refs)?.into_iter().map(|x| x.name).collect();
+          let flaps = crate::os_more::net_interface::watch_flaps(
+            &names,
+            std::time::Duration::from_secs(secs),
+            std::time::Duration::from_millis(interval),
+            max_flaps,
+            |event| match is_full {
+              true => crate::p(serde_json::to_string(event).unwrap_or_default()),
+              false => crate::p(format!("{} {}", event.interface.name, serde_json::to_string(&event.kind).unwrap_or_default())),
+            },
+          )
+          .await?;
+          Ok(serde_json::to_string(&flaps)?)
+        }
+        "wifi" => {
+          let mut list = crate::os_more::net_interface::get_wifi()?;
+          if !filter_refs.is_empty() {
+            let ifaces = crate::os_more::net_interface::query_interfaces(&filter_refs)?;
+            list.retain(|x| ifaces.iter().any(|v| v.name == x.iface || v.friendly_name == x.iface));
+          }
+          if args.get(1).map(AsRef::as_ref) == Some("check") {
+            if list.is_empty() {
+              return Err("FAIL 没有找到无线网卡".into());
+            }
+            // dBm 均为负数, 参数可省略负号以免被当作选项
+            let min_dbm = -args.get(2).and_then(|x| x.as_ref().parse::<i32>().ok()).unwrap_or(70).abs();
+            let ssid = args.get(3).map(AsRef::as_ref);
+            for info in &list {
+              info.check(min_dbm, ssid)?;
+              crate::dp(format!(
+                "PASS, INTERFACE={}, SSID={}, SIGNAL={} dBm, CHANNEL={}, TX={} Mbps",
+                info.iface,
+                info.ssid.as_deref().unwrap_or_default(),
+                info.signal_dbm.unwrap_or_default(),
+                info.channel.unwrap_or_default(),
+                info.tx_bitrate_mbps.unwrap_or_default()
+              ));
+            }
+          }
+          Ok(serde_json::to_string(&list)?)
+        }
+        "neighbors" | "routes" => {
+          let names: Option<Vec<String>> = match filter_refs.is_empty() {
+            true => None,
+            false => Some(crate::os_more::net_interface::query_interfaces(&filter_refs)?.into_iter().map(|x| x.name).collect()),
+          };
+          let keep = |iface: &String| names.as_ref().map_or(true, |v| v.contains(iface));
+          if task == "neighbors" {
+            let list: Vec<_> = crate::os_more::net_interface::get_neighbors()?.into_iter().filter(|x| keep(&x.iface)).collect();
+            Ok(serde_json::to_string(&list)?)
+          } else {
+            let list: Vec<_> = crate::os_more::net_interface::get_routes()?.into_iter().filter(|x| keep(&x.iface)).collect();
+            Ok(serde_json::to_string(&list)?)
+          }
+        }
         "nodes" => {
           if is_full {
-            Ok(serde_json::to_string(&crate::os_more::net_interface::get_interfaces())?)
+            Ok(serde_json::to_string(&crate::os_more::net_interface::query_interfaces(&filter_refs)?)?)
           } else {
             Ok(serde_json::to_string(&crate::os_more::net_interface::get_interfaces_simple(filter_refs)?)?)
           }
@@ -187,10 +263,13 @@ pub async fn network_query<T: AsRef<str>>(info: &super::Type, args: &[T], filter
         }
         "set-dns" => {
           let mut new = vec![];
-          let primary_dns = args.get(1).ok_or("Args Error Primary DNS 1 ")?.as_ref();
-          let secondary_dns = args.get(2).map(AsRef::as_ref);
+          // 按顺序的 IPv4/IPv6 DNS 列表, 到第一个非地址参数为止
+          let servers: Vec<std::net::IpAddr> = args.iter().skip(1).map_while(|v| v.as_ref().parse().ok()).collect();
+          if servers.is_empty() {
+            return Err("Args Error Primary DNS 1 ".into());
+          }
           for iface in crate::os_more::net_interface::get_interfaces_simple(filter_refs)? {
-            let res = crate::os_more::net_manage::set_static_dns(&iface.friendly_name, &primary_dns, secondary_dns).await?;
+            let res = crate::os_more::net_manage::set_dns_servers(&iface.friendly_name, &servers).await?;
             new.push(serde_json::json!({
               "name": iface.friendly_name,
               "type": iface.if_type,
@@ -200,57 +279,182 @@ pub async fn network_query<T: AsRef<str>>(info: &super::Type, args: &[T], filter
           }
           Ok(serde_json::to_string(&new)?)
         }
+        "addr-add" | "addr-del" => {
+          let mut new = vec![];
+          let nets = args
+            .iter()
+            .skip(1)
+            .map(|v| v.as_ref().parse::<crate::os_more::net_interface::IpNet>())
+            .collect::<Result<Vec<_>, _>>()?;
+          if nets.is_empty() {
+            return Err("Args Error Address 1 ".into());
+          }
+          for iface in crate::os_more::net_interface::get_interfaces_simple(filter_refs)? {
+            for net in &nets {
+              let res = match task {
+                "addr-add" => crate::os_more::net_manage::add_address(&iface.friendly_name, net).await?,
+                _ => crate::os_more::net_manage::del_address(&iface.friendly_name, net).await?,
+              };
+              new.push(serde_json::json!({
+                "name": iface.friendly_name,
+                "type": iface.if_type,
+                "ipRes": res
+              }));
+            }
+          }
+          Ok(serde_json::to_string(&new)?)
+        }
+        "set-gateway" => {
+          let mut new = vec![];
+          let gateway = args.get(1).ok_or("Args Error Gateway 1 ")?.as_ref();
+          let gateway = gateway.parse::<std::net::IpAddr>().map_err(|_| format!("Args Error Gateway 1 {}", gateway))?;
+          for iface in crate::os_more::net_interface::get_interfaces_simple(filter_refs)? {
+            let res = crate::os_more::net_manage::set_gateway(&iface.friendly_name, gateway).await?;
+            new.push(serde_json::json!({
+              "name": iface.friendly_name,
+              "type": iface.if_type,
+              "ipRes": res
+            }));
+          }
+          Ok(serde_json::to_string(&new)?)
+        }
+        "set-mtu" => {
+          let mut new = vec![];
+          let mtu = args.get(1).ok_or("Args Error MTU 1 ")?.as_ref();
+          let mtu = mtu.parse::<u32>().map_err(|_| format!("Args Error MTU 1 {}", mtu))?;
+          for iface in crate::os_more::net_interface::get_interfaces_simple(filter_refs)? {
+            let res = crate::os_more::net_manage::set_mtu(&iface.friendly_name, mtu).await?;
+            new.push(serde_json::json!({
+              "name": iface.friendly_name,
+              "type": iface.if_type,
+              "mtuRes": res
+            }));
+          }
+          Ok(serde_json::to_string(&new)?)
+        }
+        "snapshot" => {
+          let snapshot = crate::os_more::net_manage::NetSnapshot::from_interfaces(&crate::os_more::net_interface::query_interfaces(&filter_refs)?);
+          if let Some(path) = args.get(1).map(AsRef::as_ref) {
+            snapshot.save(path)?;
+          }
+          Ok(serde_json::to_string(&snapshot)?)
+        }
+        "apply" => {
+          let desired = crate::os_more::net_manage::NetSnapshot::load(args.get(1).ok_or("Args Error File 1 ")?.as_ref())?;
+          let res = match args.get(2).map(AsRef::as_ref) {
+            Some(probe) => {
+              let probe: crate::os_more::net_manage::Probe = probe.parse()?;
+              let secs = args.get(3).and_then(|v| v.as_ref().parse::<u64>().ok()).unwrap_or(30);
+              // 未指定快照文件时使用应用前的当前配置
+              let snapshot = match args.get(4).map(AsRef::as_ref) {
+                Some(path) => crate::os_more::net_manage::NetSnapshot::load(path)?,
+                None => crate::os_more::net_manage::NetSnapshot::from_interfaces(&crate::os_more::net_interface::get_interfaces()),
+              };
+              crate::os_more::net_manage::apply_with_rollback(&desired, &snapshot, &probe, std::time::Duration::from_secs(secs)).await?
+            }
+            None => desired.apply().await?,
+          };
+          Ok(serde_json::to_string(&res)?)
+        }
+        "backend" => {
+          let runner = crate::os_more::net_manage::SystemRunner;
+          Ok(crate::os_more::net_manage::NetBackend::detect(&runner).await.to_string())
+        }
         "sync-datetime" => {
           let arg = args.get(1).map(AsRef::as_ref).unwrap_or("time.windows.com");
           let is_register = if args.get(2).map(AsRef::as_ref).unwrap_or("0") == "1" { true } else { false };
           Ok(crate::os_more::net_manage::sync_datetime(&arg, is_register).await?)
         }
+        "check-time" => {
+          let server = args.get(1).ok_or("Args Error Server 1 ")?.as_ref();
+          let max_offset = args.get(2).ok_or("Args Error Max Offset 2 ")?.as_ref();
+          let max_offset = max_offset.parse::<f64>().map_err(|_| format!("Args Error Max Offset 2 {}", max_offset))?;
+          let res = crate::os_more::net_manage::check_time(server, max_offset).await?;
+          crate::p(res.to_string());
+          Ok(serde_json::to_string(&res)?)
+        }
         "ping" => {
           let source = args.get(1).ok_or("Args Error Source 1 ")?.as_ref();
           let target = args.get(2).ok_or("Args Error Target 2 ")?.as_ref();
-          let count = args.get(3).ok_or("Args Error Count 3 ")?.as_ref();
-          let fail_count = args
-            .get(4)
-            .ok_or("Args Error Repeat 4 ")
-            .map(AsRef::as_ref)
-            .unwrap_or_default()
-            .parse::<usize>()
-            .unwrap_or(3);
-          let res = crate::os_more::net_manage::ping(&source, &target, &count, fail_count).await?;
-          crate::p(res);
+          let opts = ping_options(args, 3, 5)?;
+          let fail_count = args.get(4).and_then(|v| v.as_ref().parse::<usize>().ok()).unwrap_or(3);
+          let stats = crate::os_more::net_manage::ping_with(source, target, &opts, fail_count).await?;
+          crate::p(if is_full { serde_json::to_string_pretty(&stats)? } else { stats.to_string() });
           Ok("PASS".to_string())
         }
         "ping-nodes" => {
           let target = args.get(1).ok_or("Args Error Target 1 ")?.as_ref();
-          let secs = args.get(2).ok_or("Args Error run time secs 2 ")?.as_ref();
+          let opts = ping_options(args, 2, 5)?;
           let count: usize = args.get(3).and_then(|v| v.as_ref().parse::<usize>().ok()).unwrap_or(0);
-          let fail_count = args
-            .get(4)
-            .ok_or("Args Error Repeat 4 ")
-            .map(AsRef::as_ref)
-            .unwrap_or_default()
-            .parse::<usize>()
-            .unwrap_or(3);
-          #[cfg(target_os = "windows")]
+          let fail_count = args.get(4).and_then(|v| v.as_ref().parse::<usize>().ok()).unwrap_or(3);
           let faces = crate::os_more::net_interface::get_interfaces_simple(filter_refs)?;
-          #[cfg(not(target_os = "windows"))]
-          let faces = vec![];
           if count > 0 && count != faces.len() {
             return Err(format!("正确网口数量:{} 实际网口数量:{}", count, faces.len()).into());
           }
 
           // 创建一个异步任务列表
-          let handles: Vec<_> = faces
+          let handles = faces
             .iter()
-            .map(|face| async move { crate::os_more::net_manage::ping(&face.ipv4, target, secs, fail_count).await })
-            .collect();
+            .map(|face| {
+              crate::os_more::net_manage::interface_source(&face.friendly_name, &face.ipv4)?;
+              Ok(crate::os_more::net_manage::ping_with(&face.ipv4, target, &opts, fail_count))
+            })
+            .collect::<e_utils::AnyResult<Vec<_>>>()?;
           // 并发执行所有任务并处理结果
           let results = futures::future::try_join_all(handles).await?;
-          // 将所有响应合并为一个字符串
-          let response = results.join("\n");
-          crate::p(response);
+          for (face, stats) in faces.iter().zip(results) {
+            crate::p(if is_full {
+              serde_json::to_string_pretty(&stats)?
+            } else {
+              format!("INTERFACE={}, {}", face.friendly_name, stats)
+            });
+          }
           Ok("PASS".to_string())
         }
+        "tcp-probe" | "udp-echo" | "http-probe" => {
+          use crate::os_more::net_manage::ReachTarget;
+          let address = args.get(1).ok_or("Args Error Target 1 ")?.as_ref().to_string();
+          // http-probe 多一个期望状态码参数, 之后的参数与 ping-nodes 相同
+          let (target, base) = match task {
+            "tcp-probe" => (ReachTarget::Tcp(address), 2),
+            "udp-echo" => (ReachTarget::Udp(address), 2),
+            _ => {
+              let expected = match args.get(2).map(AsRef::as_ref).filter(|v| !v.is_empty()) {
+                Some(v) => v.parse::<u16>().map_err(|_| "Args Error Status 2 ")?,
+                None => 200,
+              };
+              (ReachTarget::Http { url: address, expected }, 3)
+            }
+          };
+          let opts = ping_options(args, base, base + 3)?;
+          let count: usize = args.get(base + 1).and_then(|v| v.as_ref().parse::<usize>().ok()).unwrap_or(0);
+          let fail_count = args.get(base + 2).and_then(|v| v.as_ref().parse::<usize>().ok()).unwrap_or(3);
+          let faces = crate::os_more::net_interface::get_interfaces_simple(filter_refs)?;
+          if count > 0 && count != faces.len() {
+            return Err(format!("正确网口数量:{} 实际网口数量:{}", count, faces.len()).into());
+          }
+          let handles = faces
+            .iter()
+            .map(|face| {
+              crate::os_more::net_manage::interface_source(&face.friendly_name, &face.ipv4)?;
+              Ok(crate::os_more::net_manage::reach_with(&face.ipv4, &target, &opts, fail_count))
+            })
+            .collect::<e_utils::AnyResult<Vec<_>>>()?;
+          let results = futures::future::try_join_all(handles).await?;
+          let mut new = vec![];
+          for (face, stats) in faces.iter().zip(results) {
+            crate::p(if is_full {
+              serde_json::to_string_pretty(&stats)?
+            } else {
+              format!("INTERFACE={}, {}", face.friendly_name, stats)
+            });
+            new.push(serde_json::json!({
+              "name": face.friendly_name,
+              "stats": stats
+            }));
+          }
+          Ok(serde_json::to_string(&new)?)
+        }
         _ => Ok(String::new()),
       };
     }
@@ -322,12 +526,37 @@ pub fn system_query(info: &super::Type) -> e_utils::Result<String> {
       super::Type::MemoryManufacturerPartNumber => {
         system::memory_manufacturer_partnumber().map(|data| data.into_iter().map(|v| format!("{v:?}")).collect::<Vec<_>>().join(","))
       }
+      #[cfg(all(feature = "smbios", target_os = "linux"))]
+      super::Type::BiosInfo => read_smbios().map_or(Ok(String::new()), |v| smbios_json(v.bios)),
+      #[cfg(all(feature = "smbios", target_os = "linux"))]
+      super::Type::SystemInfo => read_smbios().map_or(Ok(String::new()), |v| smbios_json(v.system)),
+      #[cfg(all(feature = "smbios", target_os = "linux"))]
+      super::Type::BoardInfo => read_smbios().map_or(Ok(String::new()), |v| smbios_json(v.baseboard)),
+      #[cfg(all(feature = "smbios", target_os = "linux"))]
+      super::Type::ChassisInfo => read_smbios().map_or(Ok(String::new()), |v| smbios_json(v.chassis)),
+      #[cfg(all(feature = "smbios", target_os = "linux"))]
+      super::Type::ProcessorInfo => read_smbios().map_or(Ok(String::new()), |v| smbios_json(v.processors)),
+      #[cfg(all(feature = "smbios", target_os = "linux"))]
+      super::Type::MemoryModules => read_smbios().map_or(Ok(String::new()), |v| smbios_json(v.memory_modules())),
       _ => Ok(String::new()),
     }
   }
 }
+/// 读取 SMBIOS, 非 root 用户无权读取 DMI 表等失败时告警并返回 None, 避免 ALL 查询整体失败
+#[cfg(all(feature = "system", feature = "smbios", target_os = "linux"))]
+fn read_smbios() -> Option<crate::os_more::smbios::Smbios> {
+  crate::os_more::smbios::Smbios::read()
+    .map_err(|e| crate::wp(format!("读取 SMBIOS 失败: {}", e)))
+    .ok()
+}
+/// SMBIOS 记录转为 JSON
+#[cfg(all(feature = "system", feature = "smbios", target_os = "linux"))]
+fn smbios_json<T: serde::Serialize>(data: T) -> e_utils::Result<String> {
+  serde_json::to_string(&data).map_err(|e| e.to_string().into())
+}
 #[cfg(feature = "system")]
 pub mod system {
+  #[cfg(not(all(feature = "smbios", target_os = "linux")))]
   use e_utils::cmd::Cmd;
 
   /// 获取系统运行时间
@@ -385,6 +614,24 @@ pub mod system {
     Ok(sysinfo::System::long_os_version().unwrap_or("未知".to_string()))
   }
 
+  /// 获取内存条厂商与料号 (Linux 读取 SMBIOS 类型 17, 读取失败时为空)
+  #[cfg(all(feature = "smbios", target_os = "linux"))]
+  pub fn memory_manufacturer_partnumber() -> e_utils::Result<Vec<MemInfo>> {
+    Ok(
+      super::read_smbios()
+        .map(|v| {
+          v.memory_modules()
+            .into_iter()
+            .map(|m| MemInfo {
+              manufacturer: m.manufacturer.clone(),
+              part_number: m.part_number.clone(),
+            })
+            .collect()
+        })
+        .unwrap_or_default(),
+    )
+  }
+  #[cfg(not(all(feature = "smbios", target_os = "linux")))]
   pub fn memory_manufacturer_partnumber() -> e_utils::Result<Vec<MemInfo>> {
     // 执行 WMIC 命令
     let output = Cmd::new("wmic")
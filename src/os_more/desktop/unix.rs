@@ -1,17 +1,18 @@
+use std::os::unix::fs::MetadataExt;
 use std::path::PathBuf;
 use super::ty::DesktopItem;
 
 pub fn get_desktop_items(
-    query_user: Option<String>,
+    query_user: Option<&str>,
     _attr_filter: Option<u32>,
-    filters: &Vec<String>,
+    filters: &[&str],
 ) -> Vec<DesktopItem> {
     let mut items = vec![];
     let mut users = get_system_users();
     
     if let Some(query) = query_user {
         if users.iter().any(|v| v == &query) {
-            users = vec![query];
+            users = vec![query.to_string()];
         }
     }
 
@@ -29,7 +30,7 @@ pub fn get_desktop_items(
 fn process_desktop_entries(
     desktop_path: &PathBuf,
     uname: &str,
-    filters: &Vec<String>,
+    filters: &[&str],
     items: &mut Vec<DesktopItem>,
 ) {
     if let Ok(entries) = std::fs::read_dir(desktop_path) {
@@ -39,7 +40,7 @@ fn process_desktop_entries(
             if entry
                 .file_name()
                 .into_string()
-                .map(|fname| filters.contains(&fname))
+                .map(|fname| filters.contains(&fname.as_str()))
                 .unwrap_or_default()
             {
                 continue;
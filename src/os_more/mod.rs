@@ -5,4 +5,6 @@ pub mod desktop;
 pub mod net_interface;
 #[cfg(feature = "network")]
 pub mod net_manage;
+#[cfg(feature = "smbios")]
+pub mod smbios;
 pub use api::*;
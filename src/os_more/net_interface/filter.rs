@@ -0,0 +1,589 @@
+//! 网络接口过滤表达式
+//!
+//! ```text
+//! [select] EXPR [assert EXPR]
+//! EXPR  := TERM (or TERM)*
+//! TERM  := FACTOR ([and] FACTOR)*        相邻条件默认为 and
+//! FACTOR:= not FACTOR | ( EXPR ) | ATOM
+//! ATOM  := 字段 运算符 值 | 布尔字段 | ~旧规则 | 名称/类型通配
+//! ```
+//! 运算符: `=` `!=` `>` `>=` `<` `<=` `~`(通配 * ?) `^=`(前缀) `$=`(后缀) `*=`(包含)
+//!
+//! 字段: name, friendly_name, description, type, mac, speed, status, index, flags, ipv4, ipv6, ip, gateway, dns
+//!
+//! 布尔字段: connected, dhcp, has_ip, has_gateway, virtual
+//!
+//! select 条件不满足的接口被排除, assert 条件不满足时整个查询失败
+//!
+//! 只由 `~旧规则` 与普通参数组成时按旧规则处理: 普通参数忽略, 多个 MAC 前缀之间为 or
+use super::Interface;
+use e_utils::AnyResult;
+use strum::EnumMessage as _;
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+  Eq,
+  Ne,
+  Gt,
+  Ge,
+  Lt,
+  Le,
+  Glob,
+  Prefix,
+  Suffix,
+  Contains,
+}
+impl Op {
+  /// 按长度优先排列, 保证 `>=` 先于 `>` 匹配
+  const TOKENS: [(&'static str, Op); 10] = [
+    ("!=", Op::Ne),
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    ("^=", Op::Prefix),
+    ("$=", Op::Suffix),
+    ("*=", Op::Contains),
+    ("=", Op::Eq),
+    (">", Op::Gt),
+    ("<", Op::Lt),
+    ("~", Op::Glob),
+  ];
+  fn is_ordering(&self) -> bool {
+    matches!(self, Op::Gt | Op::Ge | Op::Lt | Op::Le)
+  }
+}
+
+/// 接口字段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+  Name,
+  FriendlyName,
+  Description,
+  Type,
+  Mac,
+  Speed,
+  Status,
+  Index,
+  Flags,
+  Ipv4,
+  Ipv6,
+  Ip,
+  Gateway,
+  Dns,
+}
+impl Field {
+  fn parse(v: &str) -> Option<Self> {
+    Some(match v.to_ascii_lowercase().as_str() {
+      "name" => Field::Name,
+      "friendly_name" | "friendly" => Field::FriendlyName,
+      "description" | "desc" => Field::Description,
+      "type" | "if_type" => Field::Type,
+      "mac" | "mac_addr" => Field::Mac,
+      "speed" | "speed_mb" => Field::Speed,
+      "status" | "oper_status" => Field::Status,
+      "index" => Field::Index,
+      "flags" => Field::Flags,
+      "ipv4" => Field::Ipv4,
+      "ipv6" => Field::Ipv6,
+      "ip" => Field::Ip,
+      "gateway" | "gw" => Field::Gateway,
+      "dns" | "dns_servers" => Field::Dns,
+      _ => return None,
+    })
+  }
+  fn is_numeric(&self) -> bool {
+    matches!(self, Field::Speed | Field::Index | Field::Flags)
+  }
+  /// 字段的文本值, 多值字段返回多个
+  fn texts(&self, x: &Interface) -> Vec<String> {
+    match self {
+      Field::Name => vec![x.name.clone()],
+      Field::FriendlyName => vec![x.friendly_name.clone()],
+      Field::Description => vec![x.description.clone()],
+      Field::Type => vec![x.if_type.to_string(), x.if_type.get_message().unwrap_or_default().to_string()],
+      Field::Mac => vec![normalize_mac(&x.mac_addr.to_string())],
+      Field::Speed => vec![x.speed().to_string()],
+      Field::Status => vec![x.oper_status.to_string(), x.oper_status.get_message().unwrap_or_default().to_string()],
+      Field::Index => vec![x.index.to_string()],
+      Field::Flags => vec![x.flags.to_string()],
+      Field::Ipv4 => x.ipv4.iter().map(|v| v.addr.to_string()).collect(),
+      Field::Ipv6 => x.ipv6.iter().map(|v| v.addr.to_string()).collect(),
+      Field::Ip => x
+        .ipv4
+        .iter()
+        .map(|v| v.addr.to_string())
+        .chain(x.ipv6.iter().map(|v| v.addr.to_string()))
+        .collect(),
+      Field::Gateway => x.gateway.iter().map(|v| v.ip_addr.to_string()).collect(),
+      Field::Dns => x.dns_servers.iter().map(|v| v.to_string()).collect(),
+    }
+  }
+  fn number(&self, x: &Interface) -> f64 {
+    match self {
+      Field::Speed => x.speed() as f64,
+      Field::Index => x.index as f64,
+      Field::Flags => x.flags as f64,
+      _ => 0.0,
+    }
+  }
+}
+
+/// 布尔字段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flag {
+  Connected,
+  Dhcp,
+  HasIp,
+  HasGateway,
+  Virtual,
+}
+impl Flag {
+  fn parse(v: &str) -> Option<Self> {
+    Some(match v.to_ascii_lowercase().as_str() {
+      "connected" | "is_connected" => Flag::Connected,
+      "dhcp" | "has_dhcp_ip" => Flag::Dhcp,
+      "has_ip" => Flag::HasIp,
+      "has_gateway" => Flag::HasGateway,
+      "virtual" | "is_virtual" => Flag::Virtual,
+      _ => return None,
+    })
+  }
+  fn matches(&self, x: &Interface) -> bool {
+    match self {
+      Flag::Connected => x.is_connected(),
+      Flag::Dhcp => x.has_dhcp_ip(),
+      Flag::HasIp => x.has_ip(),
+      Flag::HasGateway => x.gateway.is_some(),
+      Flag::Virtual => x.is_virtual(),
+    }
+  }
+}
+
+/// 过滤表达式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+  And(Box<Filter>, Box<Filter>),
+  Or(Box<Filter>, Box<Filter>),
+  Not(Box<Filter>),
+  Cmp(Field, Op, String),
+  Flag(Flag),
+  /// 名称/描述/类型通配
+  Any(String),
+}
+impl Filter {
+  /// 解析表达式
+  pub fn parse<T: AsRef<str>>(args: &[T]) -> AnyResult<Self> {
+    let tokens = tokenize(args)?;
+    if tokens.is_empty() {
+      return Err("过滤表达式为空".into());
+    }
+    let mut parser = Parser { tokens: &tokens, pos: 0 };
+    let filter = parser.expr()?;
+    match parser.tokens.get(parser.pos) {
+      Some(token) => Err(format!("过滤表达式多余内容: {:?}", token).into()),
+      None => Ok(filter),
+    }
+  }
+  /// 接口是否满足条件
+  pub fn matches(&self, x: &Interface) -> bool {
+    match self {
+      Filter::And(a, b) => a.matches(x) && b.matches(x),
+      Filter::Or(a, b) => a.matches(x) || b.matches(x),
+      Filter::Not(a) => !a.matches(x),
+      Filter::Flag(flag) => flag.matches(x),
+      Filter::Any(pattern) => [Field::Name, Field::FriendlyName, Field::Description, Field::Type]
+        .iter()
+        .any(|field| field.texts(x).iter().any(|v| glob_match(pattern, v))),
+      Filter::Cmp(field, op, value) if op.is_ordering() => {
+        let (left, right) = (field.number(x), value.parse::<f64>().unwrap_or_default());
+        match op {
+          Op::Gt => left > right,
+          Op::Ge => left >= right,
+          Op::Lt => left < right,
+          _ => left <= right,
+        }
+      }
+      Filter::Cmp(field, Op::Ne, value) => !Filter::Cmp(*field, Op::Eq, value.clone()).matches(x),
+      Filter::Cmp(field, op, value) => {
+        let value = if *field == Field::Mac { normalize_mac(value) } else { value.to_lowercase() };
+        field.texts(x).iter().any(|text| {
+          let text = text.to_lowercase();
+          match op {
+            Op::Eq if field.is_numeric() => text.parse::<f64>().ok() == value.parse::<f64>().ok(),
+            Op::Eq => text == value,
+            Op::Glob => glob_match(&value, &text),
+            Op::Prefix => text.starts_with(&value),
+            Op::Suffix => text.ends_with(&value),
+            _ => text.contains(&value),
+          }
+        })
+      }
+    }
+  }
+}
+
+/// select 与 assert 两部分
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterfaceFilter {
+  /// 不满足的接口被排除
+  pub select: Option<Filter>,
+  /// 不满足时查询失败
+  pub assert: Option<Filter>,
+}
+impl InterfaceFilter {
+  /// 解析 `[select] EXPR [assert EXPR]`
+  pub fn parse<T: AsRef<str>>(args: &[T]) -> AnyResult<Self> {
+    let args: Vec<&str> = args.iter().map(AsRef::as_ref).filter(|v| !v.trim().is_empty()).collect();
+    let args = match args.first() {
+      Some(v) if v.eq_ignore_ascii_case("select") => &args[1..],
+      _ => &args[..],
+    };
+    let (select, assert) = match args.iter().position(|v| v.eq_ignore_ascii_case("assert")) {
+      Some(i) => (&args[..i], Some(&args[i + 1..])),
+      None => (args, None),
+    };
+    Ok(Self {
+      select: parse_part(select)?,
+      // assert 没有有效条件时报错, 避免写错的检查直接通过
+      assert: assert
+        .map(|v| -> AnyResult<Filter> { Ok(parse_part(v)?.ok_or_else(|| format!("assert 缺少有效条件: {}", v.join(" ")))?) })
+        .transpose()?,
+    })
+  }
+  /// 过滤接口
+  pub fn apply(&self, interfaces: Vec<Interface>) -> AnyResult<Vec<Interface>> {
+    let res: Vec<Interface> = interfaces.into_iter().filter(|x| self.select.as_ref().map_or(true, |f| f.matches(x))).collect();
+    if let Some(assert) = &self.assert {
+      let failed: Vec<String> = res
+        .iter()
+        .filter(|x| !assert.matches(x))
+        .map(|x| format!("{}({})", x.friendly_name, x.mac_addr.to_string().to_ascii_uppercase()))
+        .collect();
+      if !failed.is_empty() {
+        return Err(format!("接口条件检查失败: {}", failed.join(", ")).into());
+      }
+    }
+    Ok(res)
+  }
+}
+
+/// 统一 MAC 格式 去掉分隔符并大写
+fn normalize_mac(v: &str) -> String {
+  v.chars()
+    .filter(|c| c.is_ascii_hexdigit() || *c == '*' || *c == '?')
+    .collect::<String>()
+    .to_ascii_lowercase()
+}
+
+/// 通配匹配 `*` 任意长度 `?` 单个字符, 不区分大小写
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+  let p: Vec<char> = pattern.to_lowercase().chars().collect();
+  let t: Vec<char> = text.to_lowercase().chars().collect();
+  let (mut pi, mut ti) = (0, 0);
+  let mut star: Option<(usize, usize)> = None;
+  while ti < t.len() {
+    if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+      pi += 1;
+      ti += 1;
+    } else if pi < p.len() && p[pi] == '*' {
+      star = Some((pi, ti));
+      pi += 1;
+    } else if let Some((sp, st)) = star {
+      pi = sp + 1;
+      ti = st + 1;
+      star = Some((sp, st + 1));
+    } else {
+      return false;
+    }
+  }
+  p[pi..].iter().all(|c| *c == '*')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  LParen,
+  RParen,
+  And,
+  Or,
+  Not,
+  Atom(Filter),
+}
+
+/// 分词, 每个参数内按空白与括号切分, 引号内容原样保留
+fn tokenize<T: AsRef<str>>(args: &[T]) -> AnyResult<Vec<Token>> {
+  let mut tokens = vec![];
+  for arg in args {
+    let mut chars = arg.as_ref().chars().peekable();
+    loop {
+      while chars.next_if(|c| c.is_whitespace()).is_some() {}
+      let Some(c) = chars.next() else {
+        break;
+      };
+      match c {
+        '(' => tokens.push(Token::LParen),
+        ')' => tokens.push(Token::RParen),
+        _ => {
+          let mut word = String::new();
+          let mut quoted = false;
+          let mut c = Some(c);
+          while let Some(ch) = c {
+            if ch == '"' {
+              quoted = true;
+              loop {
+                match chars.next() {
+                  Some('"') => break,
+                  Some(ch) => word.push(ch),
+                  None => return Err(format!("过滤表达式引号未闭合: {}", arg.as_ref()).into()),
+                }
+              }
+            } else {
+              word.push(ch);
+            }
+            c = chars.next_if(|c| !c.is_whitespace() && *c != ')' && *c != '(');
+          }
+          tokens.push(match word.to_ascii_lowercase().as_str() {
+            "and" | "&&" if !quoted => Token::And,
+            "or" | "||" if !quoted => Token::Or,
+            "not" | "!" if !quoted => Token::Not,
+            _ => Token::Atom(parse_atom(&word)?),
+          });
+        }
+      }
+    }
+  }
+  Ok(tokens)
+}
+
+/// 解析单个条件
+fn parse_atom(word: &str) -> AnyResult<Filter> {
+  if let Some(legacy) = word.strip_prefix('~') {
+    return Ok(parse_legacy(legacy));
+  }
+  if let Some(flag) = Flag::parse(word) {
+    return Ok(Filter::Flag(flag));
+  }
+  // 取最靠前的运算符, 同一位置取最长的
+  let found = Op::TOKENS
+    .iter()
+    .filter_map(|(token, op)| word.find(token).map(|i| (i, token.len(), *op)))
+    .min_by_key(|(i, len, _)| (*i, usize::MAX - len));
+  let Some((i, len, op)) = found.filter(|(i, _, _)| *i > 0) else {
+    return Ok(Filter::Any(word.to_string()));
+  };
+  let name = &word[..i];
+  let value = &word[i + len..];
+  let field = Field::parse(name).ok_or(format!("未知过滤字段: {}", name))?;
+  if op.is_ordering() && (!field.is_numeric() || value.parse::<f64>().is_err()) {
+    return Err(format!("字段 {} 不支持数值比较: {}", name, word).into());
+  }
+  Ok(Filter::Cmp(field, op, value.to_string()))
+}
+
+/// 解析 select 或 assert 部分, 为空或只有被忽略的普通参数时返回 None
+fn parse_part(args: &[&str]) -> AnyResult<Option<Filter>> {
+  let words: Vec<&str> = args.iter().flat_map(|v| v.split_whitespace()).collect();
+  let is_plain = |word: &str| {
+    !matches!(word.to_ascii_lowercase().as_str(), "and" | "&&" | "or" | "||" | "not" | "!")
+      && !word.contains(['(', ')', '"'])
+      && matches!(parse_atom(word), Ok(Filter::Any(_)))
+  };
+  if !words.iter().all(|word| word.starts_with('~') || is_plain(word)) {
+    return Filter::parse(args).map(Some);
+  }
+  // 旧规则: 条件之间为 and, MAC 前缀之间为 or, 普通参数忽略
+  let (macs, rules): (Vec<Filter>, Vec<Filter>) = words
+    .iter()
+    .filter_map(|word| word.strip_prefix('~'))
+    .map(parse_legacy)
+    .partition(|f| matches!(f, Filter::Cmp(Field::Mac, ..)));
+  let macs = macs.into_iter().reduce(|a, b| Filter::Or(Box::new(a), Box::new(b)));
+  Ok(rules.into_iter().chain(macs).reduce(|a, b| Filter::And(Box::new(a), Box::new(b))))
+}
+
+/// 兼容旧的 `~` 规则
+fn parse_legacy(v: &str) -> Filter {
+  let speed = |op, value: &str| Filter::Cmp(Field::Speed, op, value.to_string());
+  match v {
+    "Less100" => speed(Op::Lt, "100"),
+    "100" => speed(Op::Ge, "100"),
+    "1000" => speed(Op::Ge, "1000"),
+    "Big1000" => speed(Op::Ge, "10000"),
+    "is_connected" => Filter::Flag(Flag::Connected),
+    "has_dhcp_ip" => Filter::Flag(Flag::Dhcp),
+    "auto" => Filter::Not(Box::new(Filter::Flag(Flag::Virtual))),
+    _ => Filter::Cmp(Field::Mac, Op::Prefix, v.to_string()),
+  }
+}
+
+struct Parser<'a> {
+  tokens: &'a [Token],
+  pos: usize,
+}
+impl Parser<'_> {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+  fn expr(&mut self) -> AnyResult<Filter> {
+    let mut left = self.term()?;
+    while self.peek() == Some(&Token::Or) {
+      self.pos += 1;
+      left = Filter::Or(Box::new(left), Box::new(self.term()?));
+    }
+    Ok(left)
+  }
+  fn term(&mut self) -> AnyResult<Filter> {
+    let mut left = self.factor()?;
+    loop {
+      match self.peek() {
+        Some(Token::And) => self.pos += 1,
+        Some(Token::Atom(_) | Token::Not | Token::LParen) => {}
+        _ => break,
+      }
+      left = Filter::And(Box::new(left), Box::new(self.factor()?));
+    }
+    Ok(left)
+  }
+  fn factor(&mut self) -> AnyResult<Filter> {
+    let token = self.peek().cloned().ok_or("过滤表达式不完整")?;
+    self.pos += 1;
+    match token {
+      Token::Not => Ok(Filter::Not(Box::new(self.factor()?))),
+      Token::LParen => {
+        let inner = self.expr()?;
+        if self.peek() != Some(&Token::RParen) {
+          return Err("过滤表达式缺少 )".into());
+        }
+        self.pos += 1;
+        Ok(inner)
+      }
+      Token::Atom(atom) => Ok(atom),
+      token => Err(format!("过滤表达式语法错误: {:?}", token).into()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::os_more::net_interface::{Gateway, InterfaceStatus, InterfaceType, Ipv4Net, MacAddr};
+  use std::net::{IpAddr, Ipv4Addr};
+
+  fn iface(name: &str, desc: &str, if_type: InterfaceType, mac: [u8; 6], speed: u64, up: bool) -> Interface {
+    Interface {
+      index: 0,
+      name: name.into(),
+      friendly_name: name.into(),
+      description: desc.into(),
+      if_type,
+      mac_addr: MacAddr::new(mac),
+      ipv4: vec![Ipv4Net::new(Ipv4Addr::new(192, 168, 1, 20), 24)],
+      ipv6: vec![],
+      flags: 0,
+      transmit_speed: speed * 1_000_000,
+      receive_speed: speed * 1_000_000,
+      gateway: up.then(|| Gateway {
+        mac_addr: MacAddr::zero(),
+        ip_addr: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+      }),
+      oper_status: if up { InterfaceStatus::Up } else { InterfaceStatus::Down },
+      dns_servers: vec![],
+      stats: None,
+      topology: Default::default(),
+    }
+  }
+
+  fn fixture() -> Vec<Interface> {
+    vec![
+      iface(
+        "以太网",
+        "Intel(R) Ethernet Controller I225-V",
+        InterfaceType::Ethernet,
+        [0x00, 0x1B, 0x21, 1, 2, 3],
+        1000,
+        true,
+      ),
+      iface(
+        "以太网 2",
+        "Intel(R) Ethernet Controller I225-V #2",
+        InterfaceType::Ethernet,
+        [0x00, 0x1B, 0x21, 1, 2, 4],
+        100,
+        false,
+      ),
+      iface(
+        "WLAN",
+        "Intel(R) Wi-Fi 6 AX201",
+        InterfaceType::Wireless80211,
+        [0xF4, 0x8C, 0x50, 1, 2, 3],
+        866,
+        true,
+      ),
+      iface(
+        "vEthernet (WSL)",
+        "Hyper-V Virtual Ethernet Adapter",
+        InterfaceType::Ethernet,
+        [0x00, 0x15, 0x5D, 1, 2, 3],
+        10000,
+        true,
+      ),
+    ]
+  }
+
+  fn select(args: &[&str]) -> Vec<String> {
+    InterfaceFilter::parse(args)
+      .unwrap()
+      .apply(fixture())
+      .unwrap()
+      .into_iter()
+      .map(|x| x.name)
+      .collect()
+  }
+
+  #[test]
+  fn test_filter_expressions() {
+    assert_eq!(select(&["speed>=1000"]), ["以太网", "vEthernet (WSL)"]);
+    assert_eq!(select(&["type=Ethernet", "and", "not", "virtual"]), ["以太网", "以太网 2"]);
+    assert_eq!(select(&["description~\"*I225*\""]), ["以太网", "以太网 2"]);
+    assert_eq!(select(&["mac^=00-1B"]), ["以太网", "以太网 2"]);
+    assert_eq!(select(&["mac^=001b21010204"]), ["以太网 2"]);
+    assert_eq!(select(&["(type=Wireless80211 or speed<1000)", "connected"]), ["WLAN"]);
+    assert_eq!(select(&["type=无线网络"]), ["WLAN"]);
+    assert_eq!(select(&["gateway=192.168.1.1", "speed!=10000"]), ["以太网", "WLAN"]);
+    assert!(glob_match("I225*", "i225-v #1") && !glob_match("I225?", "I225-V"));
+  }
+
+  #[test]
+  fn test_filter_legacy() {
+    assert_eq!(select(&["~1000", "~is_connected"]), ["以太网", "vEthernet (WSL)"]);
+    assert_eq!(select(&["~auto", "~has_dhcp_ip"]), ["以太网", "WLAN"]);
+    assert_eq!(select(&["~Less100"]), Vec::<String>::new());
+    // MAC 前缀不符合时只排除接口, 不再中断查询
+    assert_eq!(select(&["~F4-8C"]), ["WLAN"]);
+    // 多个 MAC 前缀之间为 or, 与其他旧规则之间为 and
+    assert_eq!(select(&["~00-1B", "~F4-8C"]), ["以太网", "以太网 2", "WLAN"]);
+    assert_eq!(select(&["~00-1B ~F4-8C", "~is_connected"]), ["以太网", "WLAN"]);
+    // 旧用法中的普通参数不参与过滤
+    assert_eq!(select(&["~is_connected", "Ethernet"]), ["以太网", "WLAN", "vEthernet (WSL)"]);
+    assert_eq!(select(&["以太网"]).len(), 4);
+    // 新语法中的普通参数按名称/类型通配
+    assert_eq!(select(&["connected", "Ethernet"]), ["以太网", "vEthernet (WSL)"]);
+  }
+
+  #[test]
+  fn test_filter_assert() {
+    assert_eq!(select(&["select", "type=Ethernet", "~auto", "assert", "mac^=00-1B"]).len(), 2);
+    let err = InterfaceFilter::parse(&["~auto", "assert", "mac^=00-1B"])
+      .unwrap()
+      .apply(fixture())
+      .unwrap_err();
+    assert!(err.to_string().contains("WLAN(F4-8C-50-01-02-03)"));
+    assert!(InterfaceFilter::parse(&["speed>=fast"]).is_err());
+    assert!(InterfaceFilter::parse(&["name>1"]).is_err());
+    assert!(InterfaceFilter::parse(&["(speed>=100"]).is_err());
+    assert!(InterfaceFilter::parse(&["colour=red"]).is_err());
+    assert_eq!(InterfaceFilter::parse::<&str>(&[]).unwrap(), InterfaceFilter::default());
+    // assert 只有普通参数或为空时报错
+    assert!(InterfaceFilter::parse(&["Ethernet", "assert", "foo", "bar"]).is_err());
+    assert!(InterfaceFilter::parse(&["assert"]).is_err());
+  }
+}
@@ -0,0 +1,251 @@
+//! MAC 地址校验: 厂商 (OUI) 查询与规则文件
+//!
+//! 规则文件为 JSON, 所有字段可省略, 省略时与原 `MAC_CHECKS` 行为一致:
+//! ```json
+//! {
+//!   "deny": ["00-00-00-00-00-00", "88-88-88-88-8?-88"],
+//!   "deny_multicast": true,
+//!   "deny_local": true,
+//!   "oui_db": "oui.csv",
+//!   "vendors": ["Intel*"],
+//!   "ranges": [{ "start": "00-1B-21-00-00-00", "end": "00-1B-21-00-FF-FF" }],
+//!   "unique": true
+//! }
+//! ```
+use super::{glob_match, Interface, MacAddr};
+use crate::common::text::csv_fields;
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path};
+
+/// IEEE OUI 数据库
+#[derive(Debug, Clone, Default)]
+pub struct OuiDb(HashMap<[u8; 3], String>);
+impl OuiDb {
+  /// 解析 `oui.txt` 的 `(hex)` 行或 `oui.csv` 的 MA-L 行
+  pub fn parse(content: &str) -> Self {
+    let mut db = HashMap::new();
+    for line in content.lines() {
+      let entry = match line.split_once("(hex)") {
+        Some((oui, vendor)) => parse_oui(oui.trim()).map(|oui| (oui, vendor.trim().to_string())),
+        None => {
+          let fields = csv_fields(line);
+          match fields.as_slice() {
+            [registry, oui, vendor, ..] if registry == "MA-L" => parse_oui(oui).map(|oui| (oui, vendor.clone())),
+            _ => None,
+          }
+        }
+      };
+      if let Some((oui, vendor)) = entry {
+        db.insert(oui, vendor);
+      }
+    }
+    Self(db)
+  }
+  pub fn load(path: impl AsRef<Path>) -> AnyResult<Self> {
+    let path = path.as_ref();
+    let db = Self::parse(&std::fs::read_to_string(path)?);
+    if db.is_empty() {
+      return Err(format!("OUI 数据库为空: {}", path.display()).into());
+    }
+    Ok(db)
+  }
+  pub fn vendor(&self, mac: &MacAddr) -> Option<&str> {
+    self.0.get(&mac.oui()).map(String::as_str)
+  }
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+}
+
+/// `00-1B-21` 或 `001B21`
+fn parse_oui(v: &str) -> Option<[u8; 3]> {
+  let hex: String = v.chars().filter(|c| *c != '-').collect();
+  if hex.len() != 6 {
+    return None;
+  }
+  let n = u32::from_str_radix(&hex, 16).ok()?;
+  Some([(n >> 16) as u8, (n >> 8) as u8, n as u8])
+}
+
+/// 允许的 MAC 范围 (含首尾)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacRange {
+  pub start: String,
+  pub end: String,
+}
+impl MacRange {
+  pub fn bounds(&self) -> Result<(MacAddr, MacAddr), String> {
+    let (start, end): (MacAddr, MacAddr) = (self.start.parse()?, self.end.parse()?);
+    if start > end {
+      return Err(format!("MAC范围错误: {} > {}", self.start, self.end));
+    }
+    Ok((start, end))
+  }
+  pub fn contains(&self, mac: &MacAddr) -> bool {
+    self.bounds().is_ok_and(|(start, end)| (&start..=&end).contains(&mac))
+  }
+}
+
+/// `check-mac` 校验规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MacRules {
+  /// 未烧录等禁止的 MAC, 支持 `*` `?` 通配
+  pub deny: Vec<String>,
+  /// 拒绝组播位
+  pub deny_multicast: bool,
+  /// 拒绝本地管理位
+  pub deny_local: bool,
+  /// OUI 数据库路径 (IEEE oui.txt / oui.csv), 相对路径基于规则文件所在目录
+  pub oui_db: Option<String>,
+  /// 期望厂商, 支持通配, 为空不检查
+  pub vendors: Vec<String>,
+  /// 允许范围, 为空不检查
+  pub ranges: Vec<MacRange>,
+  /// 检查重复 MAC
+  pub unique: bool,
+}
+impl Default for MacRules {
+  fn default() -> Self {
+    Self {
+      deny: crate::os_more::network::MAC_CHECKS.iter().map(|v| v.to_string()).collect(),
+      deny_multicast: false,
+      deny_local: false,
+      oui_db: None,
+      vendors: vec![],
+      ranges: vec![],
+      unique: true,
+    }
+  }
+}
+impl MacRules {
+  /// 加载规则文件并校验范围
+  pub fn load(path: impl AsRef<Path>) -> AnyResult<Self> {
+    let path = path.as_ref();
+    let mut rules: Self = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    for range in &rules.ranges {
+      range.bounds()?;
+    }
+    if let (Some(db), Some(dir)) = (&rules.oui_db, path.parent()) {
+      rules.oui_db = Some(dir.join(db).to_string_lossy().to_string());
+    }
+    Ok(rules)
+  }
+  /// 加载规则中的 OUI 数据库, 配置了期望厂商时必须提供
+  pub fn load_oui(&self) -> AnyResult<Option<OuiDb>> {
+    match &self.oui_db {
+      Some(path) => Ok(Some(OuiDb::load(path)?)),
+      None if !self.vendors.is_empty() => Err("规则配置了 vendors 但缺少 oui_db".into()),
+      None => Ok(None),
+    }
+  }
+  /// 检查单个 MAC, 失败时返回原因
+  pub fn check(&self, mac: &MacAddr, oui: Option<&OuiDb>) -> Result<(), String> {
+    let text = mac.to_string();
+    if self.deny.iter().any(|v| glob_match(&v.replace(':', "-"), &text)) {
+      return Err("MAC地址未烧录".into());
+    }
+    if self.deny_multicast && mac.is_multicast() {
+      return Err("组播MAC地址".into());
+    }
+    if self.deny_local && mac.is_locally_administered() {
+      return Err("本地管理MAC地址".into());
+    }
+    if !self.vendors.is_empty() {
+      match oui.and_then(|db| db.vendor(mac)) {
+        Some(vendor) if self.vendors.iter().any(|v| glob_match(v, vendor)) => {}
+        Some(vendor) => return Err(format!("厂商不符({})", vendor)),
+        None => return Err("未知厂商".into()),
+      }
+    }
+    if !self.ranges.is_empty() && !self.ranges.iter().any(|v| v.contains(mac)) {
+      return Err("MAC地址不在允许范围".into());
+    }
+    Ok(())
+  }
+}
+
+/// 查找与接口出厂 MAC 重复的其他物理接口
+///
+/// bond/bridge/VLAN 逻辑接口沿用成员或父接口的 MAC, 不参与比较; bond 从接口按出厂 MAC 比较
+pub fn find_duplicate_mac<'a>(ifaces: &'a [Interface], iface: &Interface) -> Option<&'a Interface> {
+  if iface.is_logical() {
+    return None;
+  }
+  ifaces
+    .iter()
+    .find(|x| !x.is_logical() && x.name != iface.name && x.hardware_mac() == iface.hardware_mac())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const OUI_TXT: &str = "OUI/MA-L                                                    Organization
+company_id                                                  Organization
+                                                            Address
+
+00-1B-21   (hex)\t\tIntel Corporate
+001B21     (base 16)\t\tIntel Corporate
+\t\t\t\tLot 8, Jalan Hi-Tech 2/3
+";
+  const OUI_CSV: &str = "Registry,Assignment,Organization Name,Organization Address
+MA-L,00E04C,REALTEK SEMICONDUCTOR CORP.,\"No. 2, Innovation Road II Hsinchu  TW 300 \"
+MA-L,3C0754,\"Apple, Inc.\",1 Infinite Loop Cupertino CA US 95014
+";
+
+  fn mac(v: &str) -> MacAddr {
+    v.parse().unwrap()
+  }
+
+  #[test]
+  fn test_mac_addr() {
+    assert_eq!(mac("00:1b:21:aa:bb:cc"), mac("00-1B-21-AA-BB-CC"));
+    assert_eq!(mac("001b.21aa.bbcc").octets(), [0x00, 0x1b, 0x21, 0xaa, 0xbb, 0xcc]);
+    assert!("00-1B-21-AA-BB".parse::<MacAddr>().is_err());
+    assert!(mac("01-00-5E-00-00-01").is_multicast());
+    assert!(mac("02-42-AC-11-00-02").is_locally_administered());
+    assert!(!mac("00-1B-21-AA-BB-CC").is_locally_administered());
+  }
+
+  #[test]
+  fn test_oui_db() {
+    let db = OuiDb::parse(OUI_TXT);
+    assert_eq!(db.len(), 1);
+    assert_eq!(db.vendor(&mac("00-1B-21-AA-BB-CC")), Some("Intel Corporate"));
+    let db = OuiDb::parse(OUI_CSV);
+    assert_eq!(db.vendor(&mac("3C:07:54:00:00:01")), Some("Apple, Inc."));
+    assert_eq!(db.vendor(&mac("00:E0:4C:68:00:01")), Some("REALTEK SEMICONDUCTOR CORP."));
+    assert_eq!(db.vendor(&mac("00:1B:21:00:00:01")), None);
+  }
+
+  #[test]
+  fn test_mac_rules() {
+    let default = MacRules::default();
+    assert_eq!(default.check(&MacAddr::zero(), None), Err("MAC地址未烧录".into()));
+    assert!(default.check(&mac("02-42-AC-11-00-02"), None).is_ok());
+
+    let rules: MacRules = serde_json::from_str(
+      r#"{"deny":["88:88:88:88:8?:88"],"deny_local":true,"vendors":["Intel*"],
+        "ranges":[{"start":"00-1B-21-00-00-00","end":"00-1B-21-00-FF-FF"}]}"#,
+    )
+    .unwrap();
+    assert!(rules.unique && rules.load_oui().is_err());
+    let db = OuiDb::parse(OUI_TXT);
+    assert!(rules.check(&mac("00-1B-21-00-12-34"), Some(&db)).is_ok());
+    assert_eq!(rules.check(&mac("88-88-88-88-87-88"), Some(&db)), Err("MAC地址未烧录".into()));
+    assert_eq!(rules.check(&mac("02-1B-21-00-12-34"), Some(&db)), Err("本地管理MAC地址".into()));
+    assert_eq!(rules.check(&mac("00-E0-4C-00-12-34"), Some(&db)), Err("未知厂商".into()));
+    assert_eq!(rules.check(&mac("00-1B-21-01-00-00"), Some(&db)), Err("MAC地址不在允许范围".into()));
+    assert!(MacRange {
+      start: "00-1B-21-00-FF-FF".into(),
+      end: "00-1B-21-00-00-00".into()
+    }
+    .bounds()
+    .is_err());
+  }
+}
@@ -7,5 +7,35 @@ mod unix;
 #[cfg(target_os = "linux")]
 pub use unix::*;
 
+pub mod filter;
+pub mod mac;
+pub mod stats;
+pub mod table;
 pub mod ty;
-pub use ty::*;
\ No newline at end of file
+pub mod wifi;
+#[cfg(feature = "network")]
+pub mod watch;
+pub use filter::*;
+pub use mac::*;
+pub use stats::*;
+pub use table::*;
+pub use ty::*;
+pub use wifi::*;
+#[cfg(feature = "network")]
+pub use watch::*;
+
+/// 按过滤表达式查询接口, 语法见 [`filter`]
+pub fn query_interfaces<T: AsRef<str>>(filter: &[T]) -> e_utils::AnyResult<Vec<Interface>> {
+  let query = InterfaceFilter::parse(filter)?;
+  let interfaces = query.apply(get_interfaces())?;
+  if interfaces.is_empty() {
+    Err("No interfaces found".into())
+  } else {
+    Ok(interfaces)
+  }
+}
+
+/// 按过滤表达式查询接口简要信息, 语法见 [`filter`]
+pub fn get_interfaces_simple(filter: Vec<&str>) -> e_utils::AnyResult<Vec<InterfaceSimple>> {
+  Ok(query_interfaces(&filter)?.iter().map(|x| x.to_simple()).collect())
+}
@@ -0,0 +1,189 @@
+//! 网络接口流量计数与速率采样
+use super::Interface;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Instant};
+
+/// 接口累计流量计数
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceStats {
+  /// 接收字节
+  pub rx_bytes: u64,
+  /// 发送字节
+  pub tx_bytes: u64,
+  /// 接收包数
+  pub rx_packets: u64,
+  /// 发送包数
+  pub tx_packets: u64,
+  /// 接收错误
+  pub rx_errors: u64,
+  /// 发送错误
+  pub tx_errors: u64,
+  /// 接收丢弃
+  pub rx_dropped: u64,
+  /// 发送丢弃
+  pub tx_dropped: u64,
+}
+impl InterfaceStats {
+  /// 读取 sysfs 接口目录下的 statistics
+  #[cfg(target_os = "linux")]
+  pub fn from_sysfs(dir: &std::path::Path) -> Option<Self> {
+    use crate::common::sysfs;
+    let dir = dir.join("statistics");
+    let read = |name: &str| sysfs::read_num::<u64>(dir.join(name));
+    Some(Self {
+      rx_bytes: read("rx_bytes")?,
+      tx_bytes: read("tx_bytes")?,
+      rx_packets: read("rx_packets").unwrap_or(0),
+      tx_packets: read("tx_packets").unwrap_or(0),
+      rx_errors: read("rx_errors").unwrap_or(0),
+      tx_errors: read("tx_errors").unwrap_or(0),
+      rx_dropped: read("rx_dropped").unwrap_or(0),
+      tx_dropped: read("tx_dropped").unwrap_or(0),
+    })
+  }
+}
+#[cfg(feature = "network")]
+impl From<&sysinfo::NetworkData> for InterfaceStats {
+  /// sysinfo 不提供丢弃计数
+  fn from(v: &sysinfo::NetworkData) -> Self {
+    Self {
+      rx_bytes: v.total_received(),
+      tx_bytes: v.total_transmitted(),
+      rx_packets: v.total_packets_received(),
+      tx_packets: v.total_packets_transmitted(),
+      rx_errors: v.total_errors_on_received(),
+      tx_errors: v.total_errors_on_transmitted(),
+      rx_dropped: 0,
+      tx_dropped: 0,
+    }
+  }
+}
+
+/// 系统未提供计数的接口由 sysinfo Networks 补齐
+pub fn fill_missing_stats(interfaces: &mut [Interface]) {
+  #[cfg(feature = "network")]
+  {
+    if interfaces.iter().all(|x| x.stats.is_some()) {
+      return;
+    }
+    let networks = sysinfo::Networks::new_with_refreshed_list();
+    for x in interfaces.iter_mut().filter(|x| x.stats.is_none()) {
+      x.stats = networks
+        .iter()
+        .find(|(k, _)| **k == x.name || **k == x.friendly_name)
+        .map(|(_, data)| InterfaceStats::from(data));
+    }
+  }
+  #[cfg(not(feature = "network"))]
+  let _ = interfaces;
+}
+
+/// 速率 (B/s)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrafficRate {
+  /// 接收
+  pub rx: f64,
+  /// 发送
+  pub tx: f64,
+  /// 收发合计
+  pub total: f64,
+}
+impl TrafficRate {
+  /// 由两次计数计算速率, 计数器重置时按 0 计算
+  pub fn between(prev: &InterfaceStats, cur: &InterfaceStats, secs: f64) -> Self {
+    if secs <= 0.0 {
+      return Self::default();
+    }
+    let rx = cur.rx_bytes.saturating_sub(prev.rx_bytes) as f64 / secs;
+    let tx = cur.tx_bytes.saturating_sub(prev.tx_bytes) as f64 / secs;
+    Self { rx, tx, total: rx + tx }
+  }
+  fn zip(self, other: Self, f: fn(f64, f64) -> f64) -> Self {
+    Self {
+      rx: f(self.rx, other.rx),
+      tx: f(self.tx, other.tx),
+      total: f(self.total, other.total),
+    }
+  }
+}
+
+/// 单次速率及历史最小最大值
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrafficReading {
+  pub rate: TrafficRate,
+  pub min: TrafficRate,
+  pub max: TrafficRate,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TrafficSample {
+  stats: InterfaceStats,
+  at: Instant,
+  reading: Option<TrafficReading>,
+}
+
+/// 流量采样器, 按接口名保存上次计数
+#[derive(Clone, Debug, Default)]
+pub struct TrafficSampler {
+  samples: HashMap<String, TrafficSample>,
+}
+impl TrafficSampler {
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// 是否已有上次采样
+  pub fn is_primed(&self, name: &str) -> bool {
+    self.samples.contains_key(name)
+  }
+  /// 记录本次计数, 返回与上次采样之间的速率, 首次采样返回 None
+  pub fn sample(&mut self, name: &str, stats: InterfaceStats) -> Option<TrafficReading> {
+    self.sample_at(name, stats, Instant::now())
+  }
+  fn sample_at(&mut self, name: &str, stats: InterfaceStats, at: Instant) -> Option<TrafficReading> {
+    let prev = self.samples.get(name).copied();
+    let reading = prev.map(|prev| {
+      let rate = TrafficRate::between(&prev.stats, &stats, at.duration_since(prev.at).as_secs_f64());
+      match prev.reading {
+        Some(last) => TrafficReading {
+          rate,
+          min: last.min.zip(rate, f64::min),
+          max: last.max.zip(rate, f64::max),
+        },
+        None => TrafficReading { rate, min: rate, max: rate },
+      }
+    });
+    self.samples.insert(name.to_string(), TrafficSample { stats, at, reading });
+    reading
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  #[test]
+  fn test_traffic_sampler() {
+    let stats = |rx_bytes, tx_bytes| InterfaceStats {
+      rx_bytes,
+      tx_bytes,
+      ..Default::default()
+    };
+    let mut sampler = TrafficSampler::new();
+    let t0 = Instant::now();
+    assert_eq!(sampler.sample_at("eth0", stats(1000, 500), t0), None);
+    assert!(sampler.is_primed("eth0") && !sampler.is_primed("eth1"));
+
+    let r = sampler.sample_at("eth0", stats(3000, 1500), t0 + Duration::from_secs(2)).unwrap();
+    assert_eq!((r.rate.rx, r.rate.tx, r.rate.total), (1000.0, 500.0, 1500.0));
+    assert_eq!(r.min, r.max);
+
+    let r = sampler.sample_at("eth0", stats(3000, 5500), t0 + Duration::from_secs(3)).unwrap();
+    assert_eq!((r.rate.rx, r.rate.tx), (0.0, 4000.0));
+    assert_eq!((r.min.rx, r.max.rx, r.max.total), (0.0, 1000.0, 4000.0));
+
+    // 接口重建后计数器归零
+    let r = sampler.sample_at("eth0", stats(10, 10), t0 + Duration::from_secs(4)).unwrap();
+    assert_eq!(r.rate.total, 0.0);
+  }
+}
@@ -0,0 +1,346 @@
+//! 邻居 (ARP/NDP) 缓存与路由表
+//!
+//! Linux 读取 `/proc/net/arp`, `/proc/net/route`, `/proc/net/ipv6_route`, IPv6 邻居通过 `ip -6 neigh` 读取;
+//! Windows 通过 PowerShell `Get-NetNeighbor`/`Get-NetRoute` 读取
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv6Addr};
+use strum::{Display, EnumString};
+
+/// ATF_COM, 已解析
+const ATF_COM: u32 = 0x2;
+/// ATF_PERM, 静态表项
+const ATF_PERM: u32 = 0x4;
+/// RTF_REJECT, 不可达路由
+const RTF_REJECT: u32 = 0x200;
+/// RTF_LOCAL, local 表中的本机地址路由
+const RTF_LOCAL: u32 = 0x8000_0000;
+
+/// 邻居状态, 与 `ip neigh` 的 NUD 状态对应
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum NeighborState {
+  Reachable,
+  Stale,
+  Delay,
+  Probe,
+  Incomplete,
+  Failed,
+  Unreachable,
+  Permanent,
+  Noarp,
+  Unknown,
+}
+
+/// 邻居缓存表项
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Neighbor {
+  pub ip: IpAddr,
+  /// 未解析时为空
+  pub mac: String,
+  pub state: NeighborState,
+  pub iface: String,
+}
+
+/// 路由表项
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+  pub destination: IpAddr,
+  pub prefix_len: u8,
+  /// 直连路由为 None
+  pub gateway: Option<IpAddr>,
+  pub metric: u32,
+  pub iface: String,
+}
+impl Route {
+  /// 是否为默认路由
+  pub fn is_default(&self) -> bool {
+    self.prefix_len == 0
+  }
+}
+
+/// 全零 MAC 视为未解析
+fn neighbor_mac(mac: &str) -> String {
+  match mac.split([':', '-']).all(|v| v.chars().all(|c| c == '0')) {
+    true => String::new(),
+    false => mac.replace('-', ":").to_lowercase(),
+  }
+}
+
+/// 解析 /proc/net/arp
+pub fn parse_arp_table(content: &str) -> Vec<Neighbor> {
+  content
+    .lines()
+    .skip(1)
+    .filter_map(|line| {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      if fields.len() < 6 {
+        return None;
+      }
+      let flags = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).ok()?;
+      let state = match flags {
+        _ if flags & ATF_PERM != 0 => NeighborState::Permanent,
+        _ if flags & ATF_COM != 0 => NeighborState::Reachable,
+        _ => NeighborState::Incomplete,
+      };
+      Some(Neighbor {
+        ip: fields[0].parse().ok()?,
+        mac: neighbor_mac(fields[3]),
+        state,
+        iface: fields[5].to_string(),
+      })
+    })
+    .collect()
+}
+
+/// 解析 `ip neigh show` 输出, 例如 `fe80::1 dev eth0 lladdr 00:11:22:33:44:55 router STALE`
+pub fn parse_ip_neigh(content: &str) -> Vec<Neighbor> {
+  content
+    .lines()
+    .filter_map(|line| {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      let value = |key: &str| fields.iter().position(|v| *v == key).and_then(|i| fields.get(i + 1)).copied();
+      Some(Neighbor {
+        ip: fields.first()?.parse().ok()?,
+        mac: value("lladdr").map(neighbor_mac).unwrap_or_default(),
+        state: fields.last()?.parse().unwrap_or(NeighborState::Unknown),
+        iface: value("dev")?.to_string(),
+      })
+    })
+    .collect()
+}
+
+/// 解析 /proc/net/ipv6_route, 忽略不可达与本机地址路由
+pub fn parse_ipv6_routes(content: &str) -> Vec<Route> {
+  let addr = |v: &str| u128::from_str_radix(v, 16).ok().map(Ipv6Addr::from);
+  content
+    .lines()
+    .filter_map(|line| {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      if fields.len() < 10 {
+        return None;
+      }
+      let flags = u32::from_str_radix(fields[8], 16).ok()?;
+      if flags & (RTF_REJECT | RTF_LOCAL) != 0 {
+        return None;
+      }
+      let gateway = addr(fields[4])?;
+      Some(Route {
+        destination: IpAddr::V6(addr(fields[0])?),
+        prefix_len: u8::from_str_radix(fields[1], 16).ok()?,
+        gateway: (!gateway.is_unspecified()).then_some(IpAddr::V6(gateway)),
+        metric: u32::from_str_radix(fields[5], 16).ok()?,
+        iface: fields[9].to_string(),
+      })
+    })
+    .collect()
+}
+
+/// 解析 /proc/net/route
+#[cfg(target_os = "linux")]
+pub fn parse_ipv4_routes(content: &str) -> Vec<Route> {
+  super::parse_routes(content)
+    .into_iter()
+    .map(|v| Route {
+      destination: IpAddr::V4(v.destination),
+      prefix_len: u32::from_be_bytes(v.mask.octets()).count_ones() as u8,
+      gateway: (!v.gateway.is_unspecified()).then_some(IpAddr::V4(v.gateway)),
+      metric: v.metric,
+      iface: v.iface,
+    })
+    .collect()
+}
+
+/// `Get-NetNeighbor` 输出
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WinNeighbor {
+  #[serde(rename = "IPAddress")]
+  ip_address: String,
+  #[serde(default)]
+  link_layer_address: Option<String>,
+  state: String,
+  interface_alias: String,
+}
+
+/// `Get-NetRoute` 输出
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WinRoute {
+  destination_prefix: String,
+  next_hop: String,
+  route_metric: u32,
+  interface_alias: String,
+}
+
+/// 解析 `Get-NetNeighbor | ConvertTo-Json`
+pub fn parse_win_neighbors(content: &str) -> e_utils::AnyResult<Vec<Neighbor>> {
+  let list: Vec<WinNeighbor> = serde_json::from_str(content)?;
+  Ok(
+    list
+      .into_iter()
+      .filter_map(|v| {
+        Some(Neighbor {
+          ip: v.ip_address.parse().ok()?,
+          mac: v.link_layer_address.as_deref().map(neighbor_mac).unwrap_or_default(),
+          state: v.state.parse().unwrap_or(NeighborState::Unknown),
+          iface: v.interface_alias,
+        })
+      })
+      .collect(),
+  )
+}
+
+/// 解析 `Get-NetRoute | ConvertTo-Json`
+pub fn parse_win_routes(content: &str) -> e_utils::AnyResult<Vec<Route>> {
+  let list: Vec<WinRoute> = serde_json::from_str(content)?;
+  Ok(
+    list
+      .into_iter()
+      .filter_map(|v| {
+        let (dest, prefix) = v.destination_prefix.split_once('/')?;
+        let gateway: IpAddr = v.next_hop.parse().ok()?;
+        Some(Route {
+          destination: dest.parse().ok()?,
+          prefix_len: prefix.parse().ok()?,
+          gateway: (!gateway.is_unspecified()).then_some(gateway),
+          metric: v.route_metric,
+          iface: v.interface_alias,
+        })
+      })
+      .collect(),
+  )
+}
+
+/// 执行 PowerShell 并以数组输出 JSON, 单个对象也包装为数组
+#[cfg(target_os = "windows")]
+fn powershell_json(command: &str, fields: &str) -> e_utils::AnyResult<String> {
+  use e_utils::cmd::{Cmd, ExeType};
+  let script = format!("ConvertTo-Json -Compress -InputObject @({} | Select-Object {})", command, fields);
+  Ok(Cmd::new(&script).set_type(ExeType::PowerShell).output()?.stdout)
+}
+
+/// 读取邻居缓存
+pub fn get_neighbors() -> e_utils::AnyResult<Vec<Neighbor>> {
+  #[cfg(target_os = "linux")]
+  {
+    let mut res = parse_arp_table(&std::fs::read_to_string(super::PROC_NET_ARP)?);
+    match crate::common::cmd::exe("ip").args(["-6", "neigh", "show"]).output() {
+      Ok(v) => res.extend(parse_ip_neigh(&v.stdout)),
+      Err(e) => crate::wp(format!("读取 IPv6 邻居失败: {}", e)),
+    }
+    Ok(res)
+  }
+  #[cfg(target_os = "windows")]
+  return parse_win_neighbors(&powershell_json(
+    "Get-NetNeighbor",
+    "IPAddress,LinkLayerAddress,@{n='State';e={\"$($_.State)\"}},InterfaceAlias",
+  )?);
+  #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+  Err("不支持的系统".into())
+}
+
+/// 读取 IPv4/IPv6 路由表
+pub fn get_routes() -> e_utils::AnyResult<Vec<Route>> {
+  #[cfg(target_os = "linux")]
+  {
+    let mut res = parse_ipv4_routes(&std::fs::read_to_string(super::PROC_NET_ROUTE)?);
+    // 未启用 IPv6 时文件不存在
+    res.extend(parse_ipv6_routes(&std::fs::read_to_string("/proc/net/ipv6_route").unwrap_or_default()));
+    Ok(res)
+  }
+  #[cfg(target_os = "windows")]
+  return parse_win_routes(&powershell_json("Get-NetRoute", "DestinationPrefix,NextHop,RouteMetric,InterfaceAlias")?);
+  #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+  Err("不支持的系统".into())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ARP: &str = "IP address       HW type     Flags       HW address            Mask     Device\n\
+    192.0.2.1        0x1         0x2         02:FC:00:00:00:05     *        eth0\n\
+    192.0.2.77       0x1         0x0         00:00:00:00:00:00     *        eth0\n\
+    192.0.2.9        0x1         0x6         02:fc:00:00:00:09     *        eth1\n";
+  const IP_NEIGH: &str = "fe80::1 dev eth0 lladdr 02:fc:00:00:00:05 router STALE\n\
+    fd00::77 dev eth0 FAILED\n\
+    fd00::5 dev eth1 lladdr 02:fc:00:00:00:06 REACHABLE\n";
+  const IPV6_ROUTE: &str = "\
+fd000000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fd000000000000000000000000000001 00000400 00000001 00000000 00000003     eth0
+fd000000000000000000000000000002 80 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000003 00000000 80200001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+
+  #[test]
+  fn test_parse_neighbors() {
+    let neighbors = parse_arp_table(ARP);
+    let states: Vec<(&str, NeighborState)> = neighbors.iter().map(|v| (v.mac.as_str(), v.state)).collect();
+    assert_eq!(
+      states,
+      [
+        ("02:fc:00:00:00:05", NeighborState::Reachable),
+        ("", NeighborState::Incomplete),
+        ("02:fc:00:00:00:09", NeighborState::Permanent),
+      ]
+    );
+    assert_eq!(neighbors[2].iface, "eth1");
+
+    let neighbors = parse_ip_neigh(IP_NEIGH);
+    assert_eq!(neighbors.len(), 3);
+    assert_eq!(
+      neighbors[0],
+      Neighbor {
+        ip: "fe80::1".parse().unwrap(),
+        mac: "02:fc:00:00:00:05".into(),
+        state: NeighborState::Stale,
+        iface: "eth0".into(),
+      }
+    );
+    assert_eq!((neighbors[1].mac.as_str(), neighbors[1].state), ("", NeighborState::Failed));
+
+    let json = r#"[{"IPAddress":"192.168.1.1","LinkLayerAddress":"A4-91-B1-00-11-22","State":"Reachable","InterfaceAlias":"Ethernet"},
+      {"IPAddress":"ff02::1","LinkLayerAddress":"33-33-00-00-00-01","State":"Permanent","InterfaceAlias":"Ethernet"}]"#;
+    let neighbors = parse_win_neighbors(json).unwrap();
+    assert_eq!((neighbors[0].mac.as_str(), neighbors[1].state), ("a4:91:b1:00:11:22", NeighborState::Permanent));
+  }
+
+  #[test]
+  fn test_parse_routes() {
+    let routes = parse_ipv6_routes(IPV6_ROUTE);
+    assert_eq!(routes.len(), 2);
+    assert_eq!(
+      routes[0],
+      Route {
+        destination: "fd00::".parse().unwrap(),
+        prefix_len: 64,
+        gateway: None,
+        metric: 256,
+        iface: "eth0".into(),
+      }
+    );
+    assert!(routes[1].is_default());
+    assert_eq!((routes[1].gateway, routes[1].metric), (Some("fd00::1".parse().unwrap()), 1024));
+
+    let json = r#"[{"DestinationPrefix":"0.0.0.0/0","NextHop":"192.168.1.1","RouteMetric":0,"InterfaceAlias":"Ethernet"},
+      {"DestinationPrefix":"192.168.1.0/24","NextHop":"0.0.0.0","RouteMetric":256,"InterfaceAlias":"Ethernet"}]"#;
+    let routes = parse_win_routes(json).unwrap();
+    assert_eq!((routes[0].is_default(), routes[1].gateway, routes[1].prefix_len), (true, None, 24));
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_parse_ipv4_routes() {
+    let content = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+      enp3s0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+      enp3s0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n";
+    let routes = parse_ipv4_routes(content);
+    assert_eq!(
+      routes.iter().map(|v| (v.destination.to_string(), v.prefix_len, v.gateway)).collect::<Vec<_>>(),
+      [
+        ("0.0.0.0".to_string(), 0, Some("192.168.1.1".parse().unwrap())),
+        ("192.168.1.0".to_string(), 24, None),
+      ]
+    );
+  }
+}
@@ -35,6 +35,36 @@ pub struct Interface {
   pub oper_status: InterfaceStatus,
   /// DNS servers for the network interface
   pub dns_servers: Vec<IpAddr>,
+  /// Traffic counters of the network interface
+  #[serde(default)]
+  pub stats: Option<super::InterfaceStats>,
+  /// Bond/bridge/VLAN topology of the network interface
+  #[serde(default)]
+  pub topology: InterfaceTopology,
+}
+
+/// 逻辑接口类型
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Display)]
+pub enum LinkKind {
+  Bond,
+  Bridge,
+  Vlan,
+}
+
+/// 接口拓扑, 物理网卡且不属于任何 bond/bridge 时全部为空
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceTopology {
+  /// 逻辑接口类型
+  pub kind: Option<LinkKind>,
+  /// 所属 bond/bridge
+  pub master: Option<String>,
+  /// bond 从接口或 bridge 端口
+  pub slaves: Vec<String>,
+  pub vlan_id: Option<u16>,
+  /// VLAN 父接口
+  pub vlan_parent: Option<String>,
+  /// 出厂 MAC, bond 从接口的当前 MAC 会被改为 bond 的 MAC
+  pub perm_mac_addr: Option<MacAddr>,
 }
 
 impl Interface {
@@ -75,6 +105,22 @@ impl Interface {
       false
     }
   }
+  /// 是否为环回或虚拟接口 (Hyper-V/Docker/网桥/隧道等)
+  pub fn is_virtual(&self) -> bool {
+    const VIRTUAL_NAMES: [&str; 4] = ["vEthernet", "vNIC", "vSwitch", "虚拟"];
+    const VIRTUAL_PREFIXES: [&str; 9] = ["veth", "docker", "br-", "virbr", "vnet", "tun", "tap", "wg", "lo"];
+    self.if_type == InterfaceType::Loopback
+      || VIRTUAL_NAMES.iter().any(|v| self.friendly_name.contains(v))
+      || (cfg!(target_os = "linux") && VIRTUAL_PREFIXES.iter().any(|v| self.name.starts_with(v)))
+  }
+  /// 出厂 MAC, 读取不到时为当前 MAC
+  pub fn hardware_mac(&self) -> &MacAddr {
+    self.topology.perm_mac_addr.as_ref().unwrap_or(&self.mac_addr)
+  }
+  /// 是否为 bond/bridge/VLAN 逻辑接口, 其 MAC 沿用成员或父接口
+  pub fn is_logical(&self) -> bool {
+    self.topology.kind.is_some()
+  }
   pub fn has_ip(&self) -> bool {
     !self.ipv4.is_empty() || !self.ipv6.is_empty()
   }
@@ -114,6 +160,8 @@ impl Interface {
       oper_status: self.oper_status,
       is_connected: self.is_connected(),
       dns_servers: self.dns_servers.iter().map(|x| x.to_string()).collect(),
+      master: self.topology.master.clone(),
+      vlan_id: self.topology.vlan_id,
     }
   }
 }
@@ -154,6 +202,10 @@ pub struct InterfaceSimple {
   pub is_connected: bool,
   /// DNS servers for the network interface
   pub dns_servers: Vec<String>,
+  /// Bond/bridge the network interface belongs to
+  pub master: Option<String>,
+  /// VLAN id of the network interface
+  pub vlan_id: Option<u16>,
 }
 
 /// Structure of default Gateway information
@@ -246,6 +298,21 @@ impl From<i32> for InterfaceStatus {
     InterfaceStatus::Unknown
   }
 }
+#[cfg(target_os = "linux")]
+impl InterfaceStatus {
+  /// 从 /sys/class/net/*/operstate 解析
+  pub fn from_operstate(v: &str) -> Self {
+    match v.trim() {
+      "up" => InterfaceStatus::Up,
+      "down" => InterfaceStatus::Down,
+      "testing" => InterfaceStatus::Testing,
+      "dormant" => InterfaceStatus::Dormant,
+      "notpresent" => InterfaceStatus::NotPresent,
+      "lowerlayerdown" => InterfaceStatus::LowerLayerDown,
+      _ => InterfaceStatus::Unknown,
+    }
+  }
+}
 /// 网络接口类型
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Display, EnumMessage, EnumProperty, EnumIter)]
 pub enum InterfaceType {
@@ -378,8 +445,25 @@ impl From<u32> for InterfaceType {
   }
 }
 
+/// 从 /sys/class/net/*/type (ARPHRD_*) 转换
+#[cfg(target_os = "linux")]
+impl From<u32> for InterfaceType {
+  fn from(v: u32) -> Self {
+    for variant in Self::iter() {
+      if let Some(value_str) = variant.get_str("unix") {
+        if let Ok(value) = value_str.parse::<u32>() {
+          if value == v {
+            return variant;
+          }
+        }
+      }
+    }
+    InterfaceType::Unknown(v)
+  }
+}
+
 /// Structure of IPv4 Network
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Ipv4Net {
   /// IPv4 Address
   pub addr: Ipv4Addr,
@@ -409,7 +493,7 @@ impl Ipv4Net {
 }
 
 /// Structure of IPv6 Network
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Ipv6Net {
   /// IPv6 Address
   pub addr: Ipv6Addr,
@@ -438,6 +522,96 @@ impl Ipv6Net {
   }
 }
 
+/// 解析 `地址/前缀` 或 `地址/掩码`, 无前缀时为主机地址
+impl std::str::FromStr for Ipv4Net {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (addr, prefix) = s.split_once('/').unwrap_or((s, "32"));
+    let addr: Ipv4Addr = addr.trim().parse().map_err(|_| format!("无效IPv4地址: {}", s))?;
+    match (prefix.parse::<u8>(), prefix.parse::<Ipv4Addr>()) {
+      (Ok(prefix), _) if prefix <= 32 => Ok(Ipv4Net::new(addr, prefix)),
+      (_, Ok(netmask)) if u32::from(netmask).leading_ones() + u32::from(netmask).trailing_zeros() == 32 => {
+        Ok(Ipv4Net::new_with_netmask(addr, netmask))
+      }
+      _ => Err(format!("无效IPv4前缀或掩码: {}", s)),
+    }
+  }
+}
+impl std::fmt::Display for Ipv4Net {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}/{}", self.addr, self.prefix_len)
+  }
+}
+
+/// 解析 `地址/前缀`, 无前缀时为主机地址
+impl std::str::FromStr for Ipv6Net {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (addr, prefix) = s.split_once('/').unwrap_or((s, "128"));
+    let addr: Ipv6Addr = addr.trim().parse().map_err(|_| format!("无效IPv6地址: {}", s))?;
+    match prefix.parse::<u8>() {
+      Ok(prefix) if prefix <= 128 => Ok(Ipv6Net::new(addr, prefix)),
+      _ => Err(format!("无效IPv6前缀: {}", s)),
+    }
+  }
+}
+impl std::fmt::Display for Ipv6Net {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}/{}", self.addr, self.prefix_len)
+  }
+}
+
+/// IPv4 或 IPv6 网络地址, 序列化为 `地址/前缀`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum IpNet {
+  V4(Ipv4Net),
+  V6(Ipv6Net),
+}
+impl IpNet {
+  pub fn addr(&self) -> IpAddr {
+    match self {
+      IpNet::V4(v) => IpAddr::V4(v.addr),
+      IpNet::V6(v) => IpAddr::V6(v.addr),
+    }
+  }
+  pub fn prefix_len(&self) -> u8 {
+    match self {
+      IpNet::V4(v) => v.prefix_len,
+      IpNet::V6(v) => v.prefix_len,
+    }
+  }
+}
+impl std::str::FromStr for IpNet {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.contains(':') {
+      s.parse().map(IpNet::V6)
+    } else {
+      s.parse().map(IpNet::V4)
+    }
+  }
+}
+impl From<IpNet> for String {
+  fn from(v: IpNet) -> Self {
+    v.to_string()
+  }
+}
+impl TryFrom<String> for IpNet {
+  type Error = String;
+  fn try_from(v: String) -> Result<Self, Self::Error> {
+    v.parse()
+  }
+}
+impl std::fmt::Display for IpNet {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      IpNet::V4(v) => v.fmt(f),
+      IpNet::V6(v) => v.fmt(f),
+    }
+  }
+}
+
 fn ipv4_netmask_to_prefix(netmask: Ipv4Addr) -> u8 {
   let netmask = u32::from(netmask);
   let prefix = (!netmask).leading_zeros() as u8;
@@ -499,7 +673,7 @@ pub fn htonl(val: u32) -> u32 {
 }
 
 /// Structure of MAC address
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct MacAddr(u8, u8, u8, u8, u8, u8);
 
 impl MacAddr {
@@ -536,6 +710,34 @@ impl MacAddr {
     let o6: u8 = u8::from_str_radix(&fields[5], 0x10).unwrap_or(0);
     MacAddr(o1, o2, o3, o4, o5, o6)
   }
+  /// 厂商标识 (OUI), 前 3 字节
+  pub fn oui(&self) -> [u8; 3] {
+    [self.0, self.1, self.2]
+  }
+  /// 组播位 (首字节 bit0)
+  pub fn is_multicast(&self) -> bool {
+    self.0 & 0x01 != 0
+  }
+  /// 本地管理位 (首字节 bit1), 随机/虚拟 MAC
+  pub fn is_locally_administered(&self) -> bool {
+    self.0 & 0x02 != 0
+  }
+}
+
+/// 解析 MAC 地址, 支持 `:` `-` `.` 分隔或无分隔的 12 位十六进制
+impl std::str::FromStr for MacAddr {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let hex: String = s.chars().filter(|c| !matches!(c, ':' | '-' | '.')).collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+      return Err(format!("MAC地址格式错误: {}", s));
+    }
+    let mut octets = [0u8; 6];
+    for (i, v) in octets.iter_mut().enumerate() {
+      *v = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|e| e.to_string())?;
+    }
+    Ok(MacAddr::new(octets))
+  }
 }
 
 impl std::fmt::Display for MacAddr {
@@ -1,231 +1,445 @@
-// use libc as c;
-// use std::ffi::CStr;
-// use std::io;
-// use std::mem;
-// use std::net::IpAddr;
-// use std::net::Ipv4Addr;
-// use std::net::Ipv6Addr;
-// use std::ptr;
-// use std::ptr::NonNull;
-// use super::r#type::Interface;
-
-// #[cfg(any(target_os = "android", target_os = "linux"))]
-// use crate::linux::*;
-
-// // Yes, wrong for Solaris's vile offspring. Don't complain, send patches.
-// #[cfg(not(any(target_os = "android", target_os = "linux")))]
-// use crate::bsd::*;
-
-
-// /// Returns an iterator that produces the list of interfaces that the
-// /// operating system considers "up", that is, configured and active.
-// pub fn interfaces() -> io::Result<InterfaceUp> {
-//   let mut base = ptr::null_mut();
-
-//   if 0 != unsafe { c::getifaddrs(&mut base) } {
-//     return Err(io::Error::last_os_error());
-//   }
-
-//   let base = NonNull::new(base);
-//   let iter = Iter(base);
-
-//   Ok(InterfaceUp { base, iter })
-// }
-
-// pub struct InterfaceUp {
-//   base: Option<NonNull<c::ifaddrs>>,
-//   iter: Iter,
-// }
-
-// impl Iterator for InterfaceUp {
-//   type Item = Interface;
-
-//   fn next(&mut self) -> Option<Self::Item> {
-//     self.iter.find_map(|curr| to_interface(self.base, curr))
-//   }
-// }
-
-// impl Drop for InterfaceUp {
-//   fn drop(&mut self) {
-//     if let Some(mut base) = self.base {
-//       unsafe { c::freeifaddrs(base.as_mut()) };
-//     }
-//   }
-// }
-
-// struct Iter(Option<NonNull<c::ifaddrs>>);
-
-// impl Iterator for Iter {
-//   type Item = NonNull<c::ifaddrs>;
-
-//   fn next(&mut self) -> Option<Self::Item> {
-//     let curr = self.0?;
-//     let next = unsafe { curr.as_ref().ifa_next };
-//     mem::replace(&mut self.0, NonNull::new(next))
-//   }
-// }
-
-// fn ip(addr: NonNull<c::sockaddr>) -> Option<IpAddr> {
-//   let family = unsafe { addr.as_ref().sa_family };
-
-//   match family as _ {
-//     c::AF_INET => {
-//       let addr = unsafe { &*(addr.as_ptr() as *mut c::sockaddr_in) };
-//       let addr = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
-//       Some(IpAddr::V4(addr))
-//     }
-//     c::AF_INET6 => {
-//       let addr = unsafe { &*(addr.as_ptr() as *mut c::sockaddr_in6) };
-//       let [b0, b1, b2, b3, b4, b5, b6, b7, b8, b9, b10, b11, b12, b13, b14, b15] =
-//         addr.sin6_addr.s6_addr;
-//       let s0 = 256 * b0 as u16 + b1 as u16;
-//       let s1 = 256 * b2 as u16 + b3 as u16;
-//       let s2 = 256 * b4 as u16 + b5 as u16;
-//       let s3 = 256 * b6 as u16 + b7 as u16;
-//       let s4 = 256 * b8 as u16 + b9 as u16;
-//       let s5 = 256 * b10 as u16 + b11 as u16;
-//       let s6 = 256 * b12 as u16 + b13 as u16;
-//       let s7 = 256 * b14 as u16 + b15 as u16;
-//       let addr = Ipv6Addr::new(s0, s1, s2, s3, s4, s5, s6, s7);
-//       Some(IpAddr::V6(addr))
-//     }
-//     _ => None,
-//   }
-// }
-
-// fn to_interface(base: Option<NonNull<c::ifaddrs>>, curr: NonNull<c::ifaddrs>) -> Option<Interface> {
-//   let curr = unsafe { curr.as_ref() };
-//   let addr = NonNull::new(curr.ifa_addr)?;
-
-//   if is_link(addr) {
-//     return None;
-//   }
-
-//   let address = ip(addr)?;
-//   let netmask = NonNull::new(curr.ifa_netmask).and_then(ip)?;
-
-//   let name = unsafe { CStr::from_ptr(curr.ifa_name) };
-//   let mac = Iter(base)
-//     .find_map(|link| mac_of(name, link))
-//     .unwrap_or_default();
-//   let name = name.to_string_lossy().into_owned();
-
-//   let flags = From::from(curr.ifa_flags);
-
-//   let scope_id = address.is_ipv6().then(|| {
-//     let addr = addr.as_ptr() as *const c::sockaddr_in6;
-//     unsafe { (*addr).sin6_scope_id }
-//   });
-
-//   Some(Interface {
-//     name,
-//     flags,
-//     mac,
-//     address,
-//     scope_id,
-//     netmask,
-//   })
-// }
-
-// #[cfg(any(target_os = "android", target_os = "linux"))]
-// mod linux {
-//   use libc as c;
-//   use std::ffi::CStr;
-//   use std::ptr::NonNull;
-
-//   pub(crate) fn is_link(addr: NonNull<c::sockaddr>) -> bool {
-//     c::AF_PACKET == unsafe { addr.as_ref().sa_family } as _
-//   }
-
-//   pub(crate) fn mac_of(name: &CStr, link: NonNull<c::ifaddrs>) -> Option<[u8; 6]> {
-//     let link = unsafe { link.as_ref() };
-//     let addr = NonNull::new(link.ifa_addr)?;
-
-//     if !is_link(addr) {
-//       return None;
-//     }
-
-//     let ok = unsafe { CStr::from_ptr(link.ifa_name) }
-//       .to_bytes()
-//       .strip_prefix(name.to_bytes())
-//       .filter(|suffix| suffix.is_empty() || suffix.starts_with(b":"))
-//       .is_some();
-
-//     if !ok {
-//       return None;
-//     }
-
-//     let addr = link.ifa_addr as *const _ as *const c::sockaddr_ll;
-//     let addr = unsafe { &*addr };
-
-//     if addr.sll_halen != 6 {
-//       return None;
-//     }
-
-//     let [b0, b1, b2, b3, b4, b5, _, _] = addr.sll_addr;
-
-//     Some([b0, b1, b2, b3, b4, b5])
-//   }
-// }
-
-
-// #[cfg(all(unix, not(any(target_os = "android", target_os = "linux"))))]
-// mod bsd {
-//     use libc as c;
-//     use std::ffi::CStr;
-//     use std::ptr::NonNull;
-
-//     pub(crate) fn is_link(addr: NonNull<c::sockaddr>) -> bool {
-//         c::AF_LINK == unsafe { addr.as_ref().sa_family } as _
-//     }
-
-//     pub(crate) fn mac_of(
-//         name: &CStr,
-//         link: NonNull<c::ifaddrs>,
-//     ) -> Option<[u8; 6]> {
-//         let link = unsafe { link.as_ref() };
-//         let addr = NonNull::new(link.ifa_addr)?;
-
-//         if !is_link(addr) {
-//             return None;
-//         }
-
-//         let ok = unsafe { CStr::from_ptr(link.ifa_name) }
-//             .to_bytes()
-//             .strip_prefix(name.to_bytes())
-//             .filter(|suffix| suffix.is_empty() || suffix.starts_with(b":"))
-//             .is_some();
-
-//         if !ok {
-//             return None;
-//         }
-
-//         let addr = link.ifa_addr as *const _ as *const c::sockaddr_dl;
-//         let addr = unsafe { &*addr };
-
-//         if addr.sdl_alen != 6 {
-//             return None;
-//         }
-
-//         // sdl data contains both the if name and link-level address.
-//         // See: https://illumos.org/man/3socket/sockaddr_dl
-//         let start = addr.sdl_nlen as usize; // length of the if name.
-//         let end = start + addr.sdl_alen as usize;
-//         let data = unsafe {
-//             std::slice::from_raw_parts(
-//                 &addr.sdl_data as *const _ as *const u8,
-//                 end,
-//             )
-//         };
-
-//         if let [b0, b1, b2, b3, b4, b5] = data[start..end] {
-//             Some([b0, b1, b2, b3, b4, b5])
-//         } else {
-//             None
-//         }
-//     }
-// }
-
-// pub fn get_default_gateway_macaddr() -> [u8; 6] {
-//     MacAddr::zero().octets()
-//   }
\ No newline at end of file
+use super::stats::{fill_missing_stats, InterfaceStats};
+use super::get_local_ipaddr;
+use super::ty::{Gateway, Interface, InterfaceStatus, InterfaceTopology, InterfaceType, Ipv4Net, Ipv6Net, LinkKind, MacAddr};
+use crate::common::sysfs;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+/// 网络接口 sysfs 路径
+pub const SYS_NET_ROOT: &str = "/sys/class/net";
+/// IPv4 路由表
+pub const PROC_NET_ROUTE: &str = "/proc/net/route";
+/// ARP 缓存
+pub const PROC_NET_ARP: &str = "/proc/net/arp";
+/// VLAN 配置, 未加载 8021q 模块时不存在
+pub const PROC_NET_VLAN: &str = "/proc/net/vlan/config";
+/// DNS 配置, systemd-resolved 的上游配置优先
+pub const RESOLV_CONF_PATHS: [&str; 2] = ["/run/systemd/resolve/resolv.conf", "/etc/resolv.conf"];
+
+/// RTF_GATEWAY
+const RTF_GATEWAY: u32 = 0x2;
+
+/// /proc/net/route 中的路由
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteEntry {
+  pub iface: String,
+  pub destination: Ipv4Addr,
+  pub gateway: Ipv4Addr,
+  pub flags: u32,
+  pub metric: u32,
+  pub mask: Ipv4Addr,
+}
+
+/// /proc/net/route 中的地址为主机字节序的十六进制
+fn parse_route_addr(v: &str) -> Option<Ipv4Addr> {
+  u32::from_str_radix(v, 16).ok().map(|v| Ipv4Addr::from(v.to_ne_bytes()))
+}
+
+/// 解析 /proc/net/route
+pub fn parse_routes(content: &str) -> Vec<RouteEntry> {
+  content
+    .lines()
+    .skip(1)
+    .filter_map(|line| {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+      if fields.len() < 8 {
+        return None;
+      }
+      Some(RouteEntry {
+        iface: fields[0].to_string(),
+        destination: parse_route_addr(fields[1])?,
+        gateway: parse_route_addr(fields[2])?,
+        flags: u32::from_str_radix(fields[3], 16).ok()?,
+        metric: fields[6].parse().ok()?,
+        mask: parse_route_addr(fields[7])?,
+      })
+    })
+    .collect()
+}
+
+/// 各接口的默认网关, 多条默认路由取 metric 最小的
+pub fn default_gateways(routes: &[RouteEntry]) -> HashMap<String, Ipv4Addr> {
+  let mut res: HashMap<String, (u32, Ipv4Addr)> = HashMap::new();
+  for route in routes {
+    if route.destination.is_unspecified() && route.flags & RTF_GATEWAY != 0 {
+      let entry = res.entry(route.iface.clone()).or_insert((route.metric, route.gateway));
+      if route.metric < entry.0 {
+        *entry = (route.metric, route.gateway);
+      }
+    }
+  }
+  res.into_iter().map(|(k, (_, v))| (k, v)).collect()
+}
+
+/// 从 /proc/net/arp 查找 IP 对应的 MAC
+pub fn parse_arp_mac(content: &str, ip: Ipv4Addr) -> Option<MacAddr> {
+  content.lines().skip(1).find_map(|line| {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    (fields.len() >= 4 && fields[0].parse::<Ipv4Addr>().ok()? == ip).then(|| MacAddr::from_hex_format(fields[3]))
+  })
+}
+
+/// 解析 /proc/net/vlan/config, 返回 接口 -> (VLAN id, 父接口)
+pub fn parse_vlan_config(content: &str) -> HashMap<String, (u16, String)> {
+  content
+    .lines()
+    .filter_map(|line| {
+      let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+      match fields.as_slice() {
+        [name, id, parent] => Some((name.to_string(), (id.parse().ok()?, parent.to_string()))),
+        _ => None,
+      }
+    })
+    .collect()
+}
+
+/// 列出目录下的文件名并排序
+fn dir_names(dir: &Path) -> Vec<String> {
+  let mut names: Vec<String> = fs::read_dir(dir)
+    .map(|entries| entries.flatten().map(|v| v.file_name().to_string_lossy().into_owned()).collect())
+    .unwrap_or_default();
+  names.sort();
+  names
+}
+
+/// 从 sysfs 读取 bond/bridge 关系, VLAN 由 /proc/net/vlan/config 提供
+pub fn read_topology(dir: &Path, vlan: Option<&(u16, String)>) -> InterfaceTopology {
+  let bond_slaves = sysfs::read_str(dir.join("bonding/slaves"));
+  let kind = match () {
+    _ if bond_slaves.is_some() => Some(LinkKind::Bond),
+    _ if dir.join("bridge").is_dir() => Some(LinkKind::Bridge),
+    _ if vlan.is_some() => Some(LinkKind::Vlan),
+    _ => None,
+  };
+  InterfaceTopology {
+    kind,
+    master: fs::read_link(dir.join("master"))
+      .ok()
+      .and_then(|v| v.file_name().map(|v| v.to_string_lossy().into_owned())),
+    slaves: match kind {
+      Some(LinkKind::Bond) => bond_slaves.unwrap_or_default().split_whitespace().map(String::from).collect(),
+      Some(LinkKind::Bridge) => dir_names(&dir.join("brif")),
+      _ => vec![],
+    },
+    vlan_id: vlan.map(|v| v.0),
+    vlan_parent: vlan.map(|v| v.1.clone()),
+    perm_mac_addr: sysfs::read_str(dir.join("bonding_slave/perm_hwaddr")).map(|v| MacAddr::from_hex_format(&v)),
+  }
+}
+
+/// 解析 resolv.conf 中的 nameserver
+pub fn parse_resolv_conf(content: &str) -> Vec<IpAddr> {
+  content
+    .lines()
+    .filter_map(|line| {
+      let mut fields = line.split_whitespace();
+      (fields.next()? == "nameserver").then_some(())?;
+      // 去掉 IPv6 链路本地地址的 %scope
+      fields.next()?.split('%').next()?.parse().ok()
+    })
+    .collect()
+}
+
+/// 读取 DNS 服务器
+pub fn dns_servers() -> Vec<IpAddr> {
+  RESOLV_CONF_PATHS
+    .iter()
+    .filter_map(|path| fs::read_to_string(path).ok())
+    .map(|content| parse_resolv_conf(&content))
+    .find(|v| !v.is_empty())
+    .unwrap_or_default()
+}
+
+/// 通过 getifaddrs 获取各接口的 IPv4/IPv6 地址
+pub fn interface_addrs() -> HashMap<String, (Vec<Ipv4Net>, Vec<Ipv6Net>)> {
+  let mut res: HashMap<String, (Vec<Ipv4Net>, Vec<Ipv6Net>)> = HashMap::new();
+  let mut base: *mut libc::ifaddrs = std::ptr::null_mut();
+  if unsafe { libc::getifaddrs(&mut base) } != 0 {
+    return res;
+  }
+  let mut cur = base;
+  while !cur.is_null() {
+    let ifa = unsafe { &*cur };
+    cur = ifa.ifa_next;
+    if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
+      continue;
+    }
+    let name = unsafe { CStr::from_ptr(ifa.ifa_name) }.to_string_lossy().into_owned();
+    match unsafe { (*ifa.ifa_addr).sa_family } as i32 {
+      libc::AF_INET => {
+        let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+        let ip = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
+        let netmask = if ifa.ifa_netmask.is_null() {
+          Ipv4Addr::BROADCAST
+        } else {
+          let mask = unsafe { &*(ifa.ifa_netmask as *const libc::sockaddr_in) };
+          Ipv4Addr::from(u32::from_be(mask.sin_addr.s_addr))
+        };
+        res.entry(name).or_default().0.push(Ipv4Net::new_with_netmask(ip, netmask));
+      }
+      libc::AF_INET6 => {
+        let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+        let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+        let netmask = if ifa.ifa_netmask.is_null() {
+          Ipv6Addr::from(u128::MAX)
+        } else {
+          let mask = unsafe { &*(ifa.ifa_netmask as *const libc::sockaddr_in6) };
+          Ipv6Addr::from(mask.sin6_addr.s6_addr)
+        };
+        res.entry(name).or_default().1.push(Ipv6Net::new_with_netmask(ip, netmask));
+      }
+      _ => {}
+    }
+  }
+  unsafe { libc::freeifaddrs(base) };
+  res
+}
+
+/// 从 sysfs 读取接口, 地址/路由/ARP/VLAN/DNS 由调用方提供
+pub fn read_interfaces(
+  root: &Path,
+  addrs: &HashMap<String, (Vec<Ipv4Net>, Vec<Ipv6Net>)>,
+  routes: &str,
+  arp: &str,
+  vlans: &str,
+  dns_servers: &[IpAddr],
+) -> Vec<Interface> {
+  let gateways = default_gateways(&parse_routes(routes));
+  let vlans = parse_vlan_config(vlans);
+  let mut interfaces: Vec<Interface> = fs::read_dir(root)
+    .map(|entries| entries.flatten().collect::<Vec<_>>())
+    .unwrap_or_default()
+    .into_iter()
+    .filter_map(|entry| {
+      let name = entry.file_name().into_string().ok()?;
+      let dir = entry.path();
+      let index = sysfs::read_num::<u32>(dir.join("ifindex"))?;
+      let flags = sysfs::read_str(dir.join("flags"))
+        .and_then(|v| u32::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+        .unwrap_or(0);
+      let if_type = if dir.join("wireless").exists() || dir.join("phy80211").exists() {
+        InterfaceType::Wireless80211
+      } else {
+        InterfaceType::from(sysfs::read_num::<u32>(dir.join("type")).unwrap_or(0))
+      };
+      let mut oper_status = InterfaceStatus::from_operstate(&sysfs::read_str(dir.join("operstate")).unwrap_or_default());
+      // 环回与部分虚拟接口 operstate 为 unknown, 以 carrier 判断
+      if oper_status == InterfaceStatus::Unknown && sysfs::read_num::<u8>(dir.join("carrier")) == Some(1) {
+        oper_status = InterfaceStatus::Up;
+      }
+      // 断开时 speed 读取失败或为 -1
+      let speed = sysfs::read_num::<i64>(dir.join("speed")).filter(|v| *v > 0).unwrap_or(0) as u64 * 1_000_000;
+      let description = fs::read_link(dir.join("device/driver"))
+        .ok()
+        .and_then(|v| v.file_name().map(|v| v.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| name.clone());
+      let (ipv4, ipv6) = addrs.get(&name).cloned().unwrap_or_default();
+      let gateway = gateways.get(&name).map(|ip| Gateway {
+        mac_addr: parse_arp_mac(arp, *ip).unwrap_or_else(MacAddr::zero),
+        ip_addr: IpAddr::V4(*ip),
+      });
+      Some(Interface {
+        index,
+        friendly_name: name.clone(),
+        description,
+        if_type,
+        mac_addr: sysfs::read_str(dir.join("address"))
+          .map(|v| MacAddr::from_hex_format(&v))
+          .unwrap_or_else(MacAddr::zero),
+        ipv4,
+        ipv6,
+        flags,
+        transmit_speed: speed,
+        receive_speed: speed,
+        gateway,
+        oper_status,
+        dns_servers: if if_type == InterfaceType::Loopback { vec![] } else { dns_servers.to_vec() },
+        stats: InterfaceStats::from_sysfs(&dir),
+        topology: read_topology(&dir, vlans.get(&name)),
+        name,
+      })
+    })
+    .collect();
+  interfaces.sort_by_key(|x| x.index);
+  interfaces
+}
+
+/// 获取网络接口 (sysfs + getifaddrs + /proc/net/route + /proc/net/vlan + resolv.conf)
+pub fn get_interfaces() -> Vec<Interface> {
+  let mut interfaces = read_interfaces(
+    Path::new(SYS_NET_ROOT),
+    &interface_addrs(),
+    &fs::read_to_string(PROC_NET_ROUTE).unwrap_or_default(),
+    &fs::read_to_string(PROC_NET_ARP).unwrap_or_default(),
+    &fs::read_to_string(PROC_NET_VLAN).unwrap_or_default(),
+    &dns_servers(),
+  );
+  fill_missing_stats(&mut interfaces);
+  interfaces
+}
+
+pub fn get_default_gateway_macaddr() -> [u8; 6] {
+  match get_default_gateway() {
+    Ok(gateway) => gateway.mac_addr.octets(),
+    Err(_) => MacAddr::zero().octets(),
+  }
+}
+
+/// Get default Gateway
+pub fn get_default_gateway() -> Result<Gateway, String> {
+  let local_ip: IpAddr = match get_local_ipaddr() {
+    Ok(local_ip) => local_ip,
+    Err(_) => return Err(String::from("Local IP address not found")),
+  };
+  for iface in get_interfaces() {
+    let matched = match local_ip {
+      IpAddr::V4(local_ipv4) => iface.ipv4.iter().any(|x| x.addr == local_ipv4),
+      IpAddr::V6(local_ipv6) => iface.ipv6.iter().any(|x| x.addr == local_ipv6),
+    };
+    if let (true, Some(gateway)) = (matched, iface.gateway) {
+      return Ok(gateway);
+    }
+  }
+  Err(String::from("Default Gateway not found"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::sysfs_tree;
+
+  const ROUTES: &str = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+    enp3s0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+    enp3s0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0\n\
+    wlp2s0\t00000000\t010AA8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n";
+  const ARP: &str = "IP address       HW type     Flags       HW address            Mask     Device\n\
+    192.168.1.1      0x1         0x2         a4:91:b1:00:11:22     *        enp3s0\n";
+
+  #[test]
+  fn test_net_interface_fixture() {
+    let root = sysfs_tree(
+      "net",
+      &[
+        ("lo/ifindex", "1\n"),
+        ("lo/type", "772\n"),
+        ("lo/flags", "0x9\n"),
+        ("lo/operstate", "unknown\n"),
+        ("lo/carrier", "1\n"),
+        ("lo/address", "00:00:00:00:00:00\n"),
+        ("enp3s0/ifindex", "2\n"),
+        ("enp3s0/type", "1\n"),
+        ("enp3s0/flags", "0x1003\n"),
+        ("enp3s0/operstate", "up\n"),
+        ("enp3s0/address", "00:1b:21:aa:bb:cc\n"),
+        ("enp3s0/speed", "1000\n"),
+        ("enp3s0/statistics/rx_bytes", "123456\n"),
+        ("enp3s0/statistics/tx_bytes", "654321\n"),
+        ("enp3s0/statistics/rx_dropped", "7\n"),
+        ("wlp2s0/ifindex", "3\n"),
+        ("wlp2s0/type", "1\n"),
+        ("wlp2s0/flags", "0x1003\n"),
+        ("wlp2s0/operstate", "down\n"),
+        ("wlp2s0/address", "f4:8c:50:01:02:03\n"),
+        ("wlp2s0/speed", "-1\n"),
+        ("wlp2s0/wireless/status", "0\n"),
+      ],
+    );
+    let mut addrs = HashMap::new();
+    addrs.insert(
+      "enp3s0".to_string(),
+      (
+        vec![Ipv4Net::new_with_netmask(Ipv4Addr::new(192, 168, 1, 20), Ipv4Addr::new(255, 255, 255, 0))],
+        vec![],
+      ),
+    );
+    let dns = parse_resolv_conf("# comment\nnameserver 192.168.1.1\nnameserver fe80::1%enp3s0\nsearch lan\n");
+    assert_eq!(dns.len(), 2);
+
+    let ifaces = read_interfaces(&root, &addrs, ROUTES, ARP, "", &dns);
+    let names: Vec<&str> = ifaces.iter().map(|x| x.name.as_str()).collect();
+    assert_eq!(names, ["lo", "enp3s0", "wlp2s0"]);
+
+    let (lo, eth, wlan) = (&ifaces[0], &ifaces[1], &ifaces[2]);
+    assert_eq!((lo.if_type, lo.oper_status), (InterfaceType::Loopback, InterfaceStatus::Up));
+    assert!(lo.is_virtual() && lo.dns_servers.is_empty());
+
+    assert_eq!(eth.if_type, InterfaceType::Ethernet);
+    assert_eq!((eth.speed(), eth.ipv4[0].prefix_len), (1000, 24));
+    assert_eq!(eth.mac_addr.to_string().to_ascii_uppercase(), "00-1B-21-AA-BB-CC");
+    let gateway = eth.gateway.as_ref().unwrap();
+    assert_eq!(gateway.ip_addr, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)));
+    assert_eq!(gateway.mac_addr.address(), "a4:91:b1:00:11:22");
+    assert!(eth.is_connected() && eth.has_dhcp_ip());
+    let stats = eth.stats.unwrap();
+    assert_eq!((stats.rx_bytes, stats.tx_bytes, stats.rx_dropped, stats.tx_errors), (123456, 654321, 7, 0));
+    assert_eq!(lo.stats, None);
+
+    assert_eq!((wlan.if_type, wlan.speed()), (InterfaceType::Wireless80211, 0));
+    assert!(!wlan.is_connected() && !wlan.has_dhcp_ip());
+    assert_eq!(wlan.gateway.as_ref().unwrap().mac_addr.octets(), [0; 6]);
+
+    fs::remove_dir_all(root).unwrap();
+  }
+
+  #[test]
+  fn test_topology_fixture() {
+    let bond_mac = "00:1b:21:aa:bb:01\n";
+    let root = sysfs_tree(
+      "net-topo",
+      &[
+        ("eth0/ifindex", "2\n"),
+        ("eth0/address", bond_mac),
+        ("eth0/bonding_slave/perm_hwaddr", "00:1b:21:aa:bb:01\n"),
+        ("eth1/ifindex", "3\n"),
+        ("eth1/address", bond_mac),
+        ("eth1/bonding_slave/perm_hwaddr", "00:1b:21:aa:bb:02\n"),
+        ("bond0/ifindex", "4\n"),
+        ("bond0/address", bond_mac),
+        ("bond0/bonding/slaves", "eth0 eth1\n"),
+        ("bond0.100/ifindex", "5\n"),
+        ("bond0.100/address", bond_mac),
+        ("eth2/ifindex", "6\n"),
+        ("eth2/address", "00:1b:21:aa:bb:03\n"),
+        ("eth2/brport/state", "3\n"),
+        ("br0/ifindex", "7\n"),
+        ("br0/address", "00:1b:21:aa:bb:03\n"),
+        ("br0/bridge/stp_state", "0\n"),
+        ("br0/brif/eth2/state", "3\n"),
+        ("eth3/ifindex", "8\n"),
+        ("eth3/address", "00:1b:21:aa:bb:02\n"),
+      ],
+    );
+    for (slave, master) in [("eth0", "bond0"), ("eth1", "bond0"), ("eth2", "br0")] {
+      std::os::unix::fs::symlink(format!("../{}", master), root.join(slave).join("master")).unwrap();
+    }
+    let vlans = "VLAN Dev name\t | VLAN ID\nName-Type: VLAN_NAME_TYPE_RAW_PLUS_VID_NO_PAD\nbond0.100      | 100  | bond0\n";
+    let ifaces = read_interfaces(&root, &HashMap::new(), "", "", vlans, &[]);
+    let find = |name: &str| ifaces.iter().find(|x| x.name == name).unwrap();
+
+    let bond = &find("bond0").topology;
+    assert_eq!((bond.kind, bond.slaves.join(",")), (Some(LinkKind::Bond), "eth0,eth1".to_string()));
+    let eth1 = find("eth1");
+    assert_eq!(eth1.topology.master.as_deref(), Some("bond0"));
+    assert_eq!(eth1.hardware_mac().address(), "00:1b:21:aa:bb:02");
+    let vlan = &find("bond0.100").topology;
+    assert_eq!(
+      (vlan.kind, vlan.vlan_id, vlan.vlan_parent.as_deref()),
+      (Some(LinkKind::Vlan), Some(100), Some("bond0"))
+    );
+    let br = &find("br0").topology;
+    assert_eq!((br.kind, br.slaves.join(",")), (Some(LinkKind::Bridge), "eth2".to_string()));
+    assert_eq!(find("eth2").topology.master.as_deref(), Some("br0"));
+
+    // bond/bridge/VLAN 共用 MAC 不算重复, eth3 与 eth1 的出厂 MAC 重复
+    let duplicates: Vec<(&str, &str)> = ifaces
+      .iter()
+      .filter_map(|x| super::super::find_duplicate_mac(&ifaces, x).map(|v| (x.name.as_str(), v.name.as_str())))
+      .collect();
+    assert_eq!(duplicates, [("eth1", "eth3"), ("eth3", "eth1")]);
+
+    fs::remove_dir_all(root).unwrap();
+  }
+}
@@ -0,0 +1,281 @@
+//! 网络接口变化监听
+//!
+//! Linux 订阅 netlink `RTM_NEWLINK`/`RTM_NEWADDR` 等消息, 收到后重新读取接口并比较;
+//! 其他系统按间隔轮询
+use super::{get_interfaces, Interface, InterfaceStatus, IpNet};
+use futures::{Stream, StreamExt as _};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::BTreeMap,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// 接口变化类型
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InterfaceEventKind {
+  Added,
+  Removed,
+  /// 连接状态变化, 插拔网线
+  Status {
+    from: InterfaceStatus,
+    to: InterfaceStatus,
+  },
+  /// 速率变化 (Mbps), 重新协商
+  Speed {
+    from: u64,
+    to: u64,
+  },
+  AddressAdded {
+    addr: IpNet,
+  },
+  AddressRemoved {
+    addr: IpNet,
+  },
+}
+
+/// 接口变化事件
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InterfaceEvent {
+  /// Unix 毫秒
+  pub time: u64,
+  #[serde(flatten)]
+  pub kind: InterfaceEventKind,
+  /// 变化后的接口, 移除时为移除前的接口
+  pub interface: Interface,
+}
+impl InterfaceEvent {
+  pub fn new(interface: &Interface, kind: InterfaceEventKind) -> Self {
+    Self {
+      time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+      kind,
+      interface: interface.clone(),
+    }
+  }
+  /// 是否为断开连接, 用于统计闪断
+  pub fn is_link_down(&self) -> bool {
+    matches!(self.kind, InterfaceEventKind::Status { from: InterfaceStatus::Up, to } if to != InterfaceStatus::Up)
+  }
+}
+
+fn addrs(x: &Interface) -> Vec<IpNet> {
+  x.ipv4.iter().cloned().map(IpNet::V4).chain(x.ipv6.iter().cloned().map(IpNet::V6)).collect()
+}
+
+/// 比较两次读取的接口, 按名称匹配
+pub fn diff_interfaces(prev: &[Interface], cur: &[Interface]) -> Vec<InterfaceEvent> {
+  let mut events = vec![];
+  for x in prev.iter().filter(|x| cur.iter().all(|v| v.name != x.name)) {
+    events.push(InterfaceEvent::new(x, InterfaceEventKind::Removed));
+  }
+  for x in cur {
+    let Some(old) = prev.iter().find(|v| v.name == x.name) else {
+      events.push(InterfaceEvent::new(x, InterfaceEventKind::Added));
+      continue;
+    };
+    if old.oper_status != x.oper_status {
+      events.push(InterfaceEvent::new(
+        x,
+        InterfaceEventKind::Status {
+          from: old.oper_status,
+          to: x.oper_status,
+        },
+      ));
+    }
+    if old.speed() != x.speed() {
+      events.push(InterfaceEvent::new(
+        x,
+        InterfaceEventKind::Speed {
+          from: old.speed(),
+          to: x.speed(),
+        },
+      ));
+    }
+    let (old_addrs, new_addrs) = (addrs(old), addrs(x));
+    for addr in old_addrs.iter().filter(|v| !new_addrs.contains(v)) {
+      events.push(InterfaceEvent::new(x, InterfaceEventKind::AddressRemoved { addr: addr.clone() }));
+    }
+    for addr in new_addrs.iter().filter(|v| !old_addrs.contains(v)) {
+      events.push(InterfaceEvent::new(x, InterfaceEventKind::AddressAdded { addr: addr.clone() }));
+    }
+  }
+  events
+}
+
+/// netlink 路由消息订阅
+#[cfg(target_os = "linux")]
+struct NetlinkMonitor(std::os::fd::OwnedFd);
+#[cfg(target_os = "linux")]
+impl NetlinkMonitor {
+  fn open(timeout: Duration) -> std::io::Result<Self> {
+    use std::os::fd::{AsRawFd as _, FromRawFd as _};
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_ROUTE) };
+    if fd < 0 {
+      return Err(std::io::Error::last_os_error());
+    }
+    let monitor = Self(unsafe { std::os::fd::OwnedFd::from_raw_fd(fd) });
+    let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    addr.nl_groups = (libc::RTMGRP_LINK | libc::RTMGRP_IPV4_IFADDR | libc::RTMGRP_IPV6_IFADDR) as u32;
+    let tv = libc::timeval {
+      tv_sec: timeout.as_secs() as libc::time_t,
+      tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+    };
+    let fd = monitor.0.as_raw_fd();
+    unsafe {
+      if libc::bind(fd, &addr as *const _ as *const libc::sockaddr, std::mem::size_of::<libc::sockaddr_nl>() as u32) != 0
+        || libc::setsockopt(
+          fd,
+          libc::SOL_SOCKET,
+          libc::SO_RCVTIMEO,
+          &tv as *const _ as *const libc::c_void,
+          std::mem::size_of::<libc::timeval>() as u32,
+        ) != 0
+      {
+        return Err(std::io::Error::last_os_error());
+      }
+    }
+    Ok(monitor)
+  }
+  /// 等待链路或地址消息, 超时返回 false
+  fn wait(&self) -> bool {
+    use std::os::fd::AsRawFd as _;
+    let mut buf = [0u8; 8192];
+    let len = unsafe { libc::recv(self.0.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+    if len < std::mem::size_of::<libc::nlmsghdr>() as isize {
+      return false;
+    }
+    let ty = u16::from_ne_bytes([buf[4], buf[5]]);
+    [libc::RTM_NEWLINK, libc::RTM_DELLINK, libc::RTM_NEWADDR, libc::RTM_DELADDR].contains(&ty)
+  }
+}
+
+/// 监听接口变化, 轮询间隔同时作为 netlink 等待超时, 丢弃返回的流后后台线程随之退出
+pub fn watch_interfaces(interval: Duration) -> impl Stream<Item = InterfaceEvent> {
+  let (tx, rx) = tokio::sync::mpsc::channel(64);
+  std::thread::spawn(move || {
+    #[cfg(target_os = "linux")]
+    let monitor = NetlinkMonitor::open(interval)
+      .map_err(|e| crate::wp(format!("netlink 订阅失败, 改为轮询: {}", e)))
+      .ok();
+    let mut prev = get_interfaces();
+    while !tx.is_closed() {
+      #[cfg(target_os = "linux")]
+      match &monitor {
+        Some(monitor) => {
+          monitor.wait();
+        }
+        None => std::thread::sleep(interval),
+      }
+      #[cfg(not(target_os = "linux"))]
+      std::thread::sleep(interval);
+      let cur = get_interfaces();
+      for event in diff_interfaces(&prev, &cur) {
+        if tx.blocking_send(event).is_err() {
+          return;
+        }
+      }
+      prev = cur;
+    }
+  });
+  futures::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|v| (v, rx)) })
+}
+
+/// 在时长内监听指定接口, 统计各接口断开次数, 超过 max_flaps 时立即失败
+pub async fn watch_flaps(
+  names: &[String],
+  duration: Duration,
+  interval: Duration,
+  max_flaps: Option<usize>,
+  mut on_event: impl FnMut(&InterfaceEvent),
+) -> e_utils::AnyResult<BTreeMap<String, usize>> {
+  let mut flaps: BTreeMap<String, usize> = names.iter().map(|v| (v.clone(), 0)).collect();
+  let mut stream = Box::pin(watch_interfaces(interval));
+  let deadline = tokio::time::Instant::now() + duration;
+  while let Ok(Some(event)) = tokio::time::timeout_at(deadline, stream.next()).await {
+    let Some(count) = flaps.get_mut(&event.interface.name) else {
+      continue;
+    };
+    on_event(&event);
+    if event.is_link_down() {
+      *count += 1;
+      if let Some(max) = max_flaps.filter(|max| *count > *max) {
+        return Err(format!("FAIL {} 闪断 {} 次, 超过 {} 次", event.interface.name, count, max).into());
+      }
+    }
+  }
+  Ok(flaps)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::os_more::net_interface::{InterfaceType, MacAddr};
+
+  fn iface(name: &str, status: InterfaceStatus, speed: u64, ipv4: &[&str]) -> Interface {
+    Interface {
+      index: 0,
+      name: name.into(),
+      friendly_name: name.into(),
+      description: String::new(),
+      if_type: InterfaceType::Ethernet,
+      mac_addr: MacAddr::zero(),
+      ipv4: ipv4.iter().map(|v| v.parse().unwrap()).collect(),
+      ipv6: vec![],
+      flags: 0,
+      transmit_speed: speed * 1_000_000,
+      receive_speed: speed * 1_000_000,
+      gateway: None,
+      oper_status: status,
+      dns_servers: vec![],
+      stats: None,
+      topology: Default::default(),
+    }
+  }
+
+  #[test]
+  fn test_diff_interfaces() {
+    let prev = [
+      iface("enp3s0", InterfaceStatus::Up, 1000, &["192.168.1.20/24"]),
+      iface("enp4s0", InterfaceStatus::Up, 1000, &[]),
+    ];
+    let cur = [
+      iface("enp3s0", InterfaceStatus::Down, 100, &["10.0.0.2/8"]),
+      iface("enp5s0", InterfaceStatus::Up, 1000, &[]),
+    ];
+    let events = diff_interfaces(&prev, &cur);
+    let kinds: Vec<(&str, &InterfaceEventKind)> = events.iter().map(|v| (v.interface.name.as_str(), &v.kind)).collect();
+    assert_eq!(
+      kinds,
+      [
+        ("enp4s0", &InterfaceEventKind::Removed),
+        (
+          "enp3s0",
+          &InterfaceEventKind::Status {
+            from: InterfaceStatus::Up,
+            to: InterfaceStatus::Down
+          }
+        ),
+        ("enp3s0", &InterfaceEventKind::Speed { from: 1000, to: 100 }),
+        (
+          "enp3s0",
+          &InterfaceEventKind::AddressRemoved {
+            addr: "192.168.1.20/24".parse().unwrap()
+          }
+        ),
+        (
+          "enp3s0",
+          &InterfaceEventKind::AddressAdded {
+            addr: "10.0.0.2/8".parse().unwrap()
+          }
+        ),
+        ("enp5s0", &InterfaceEventKind::Added),
+      ]
+    );
+    assert!(events[1].is_link_down() && !events[2].is_link_down());
+    assert!(diff_interfaces(&cur, &cur).is_empty());
+
+    let json = serde_json::to_value(&events[1]).unwrap();
+    assert_eq!((json["kind"].as_str(), json["to"].as_str()), (Some("status"), Some("Down")));
+  }
+}
@@ -0,0 +1,263 @@
+//! 无线网卡连接信息
+//!
+//! Linux 读取 `/proc/net/wireless` 的链路质量与信号, 并通过 `iw dev <name> link` (nl80211) 读取 SSID, BSSID, 频率与速率;
+//! Windows 解析 `netsh wlan show interfaces`
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 无线接口信息, 未连接时连接相关字段为 None
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WifiInfo {
+  pub iface: String,
+  pub ssid: Option<String>,
+  pub bssid: Option<String>,
+  /// 信号强度 dBm
+  pub signal_dbm: Option<i32>,
+  /// 链路质量 %
+  pub link_quality: Option<u8>,
+  pub channel: Option<u32>,
+  pub frequency_mhz: Option<u32>,
+  /// 发送速率 Mbps
+  pub tx_bitrate_mbps: Option<f64>,
+}
+impl WifiInfo {
+  pub fn is_connected(&self) -> bool {
+    self.ssid.is_some()
+  }
+  /// 检查已连接且信号不低于 min_dbm, 指定 ssid 时需一致
+  pub fn check(&self, min_dbm: i32, ssid: Option<&str>) -> Result<(), String> {
+    let Some(current) = &self.ssid else {
+      return Err(format!("FAIL {} 未连接", self.iface));
+    };
+    if let Some(ssid) = ssid.filter(|v| v != current) {
+      return Err(format!("FAIL {} SSID={}, 期望 {}", self.iface, current, ssid));
+    }
+    match self.signal_dbm {
+      Some(signal) if signal >= min_dbm => Ok(()),
+      Some(signal) => Err(format!("FAIL {} 信号 {} dBm 低于 {} dBm", self.iface, signal, min_dbm)),
+      None => Err(format!("FAIL {} 无法读取信号强度", self.iface)),
+    }
+  }
+}
+
+/// 信道转频率 (MHz), 2.4G 与 5G
+pub fn channel_to_freq(channel: u32) -> Option<u32> {
+  match channel {
+    14 => Some(2484),
+    1..=13 => Some(2407 + channel * 5),
+    32..=177 => Some(5000 + channel * 5),
+    _ => None,
+  }
+}
+
+/// 频率 (MHz) 转信道, 包括 6G
+pub fn freq_to_channel(freq: u32) -> Option<u32> {
+  match freq {
+    2484 => Some(14),
+    2412..=2472 => Some((freq - 2407) / 5),
+    5160..=5885 => Some((freq - 5000) / 5),
+    5955..=7115 => Some((freq - 5950) / 5),
+    _ => None,
+  }
+}
+
+/// 解析 /proc/net/wireless, 返回 接口 -> (链路质量, 信号 dBm)
+///
+/// 链路质量按常见的最大值 70 换算为百分比
+pub fn parse_proc_wireless(content: &str) -> HashMap<String, (u8, i32)> {
+  content
+    .lines()
+    .skip(2)
+    .filter_map(|line| {
+      let (name, rest) = line.split_once(':')?;
+      let fields: Vec<&str> = rest.split_whitespace().collect();
+      let num = |i: usize| fields.get(i)?.trim_end_matches('.').parse::<f64>().ok();
+      let quality = (num(1)? * 100.0 / 70.0).min(100.0) as u8;
+      Some((name.trim().to_string(), (quality, num(2)? as i32)))
+    })
+    .collect()
+}
+
+/// 解析 `iw dev <name> link` 输出
+pub fn parse_iw_link(iface: &str, content: &str) -> WifiInfo {
+  let mut info = WifiInfo {
+    iface: iface.to_string(),
+    ..Default::default()
+  };
+  for line in content.lines().map(str::trim) {
+    if let Some(rest) = line.strip_prefix("Connected to ") {
+      info.bssid = rest.split_whitespace().next().map(str::to_lowercase);
+      continue;
+    }
+    let Some((key, value)) = line.split_once(':') else {
+      continue;
+    };
+    let value = value.trim();
+    let first = value.split_whitespace().next().unwrap_or_default();
+    match key {
+      "SSID" => info.ssid = Some(value.to_string()),
+      "freq" => info.frequency_mhz = first.parse::<f64>().ok().map(|v| v as u32),
+      "signal" => info.signal_dbm = first.parse().ok(),
+      "tx bitrate" => info.tx_bitrate_mbps = first.parse().ok(),
+      _ => {}
+    }
+  }
+  info.channel = info.frequency_mhz.and_then(freq_to_channel);
+  info
+}
+
+/// 解析 `netsh wlan show interfaces` 输出, 支持英文与中文系统
+pub fn parse_netsh_wlan(content: &str) -> Vec<WifiInfo> {
+  let mut res: Vec<WifiInfo> = vec![];
+  for line in content.lines() {
+    let Some((key, value)) = line.split_once(" : ") else {
+      continue;
+    };
+    let (key, value) = (key.trim(), value.trim());
+    if matches!(key, "Name" | "名称") {
+      res.push(WifiInfo {
+        iface: value.to_string(),
+        ..Default::default()
+      });
+      continue;
+    }
+    let Some(info) = res.last_mut() else {
+      continue;
+    };
+    let num = || value.trim_end_matches('%').trim().parse::<f64>().ok();
+    match key {
+      "SSID" => info.ssid = Some(value.to_string()),
+      "BSSID" | "AP BSSID" => info.bssid = Some(value.to_lowercase()),
+      "Channel" | "信道" | "通道" => info.channel = num().map(|v| v as u32),
+      "Transmit rate (Mbps)" | "传输速率 (Mbps)" | "传输速率(Mbps)" => info.tx_bitrate_mbps = num(),
+      "Signal" | "信号" => {
+        // Windows 只提供百分比, 按 quality = 2 * (dBm + 100) 近似换算
+        info.link_quality = num().map(|v| v as u8);
+        info.signal_dbm = num().map(|v| (v / 2.0 - 100.0) as i32);
+      }
+      _ => {}
+    }
+  }
+  for info in &mut res {
+    info.frequency_mhz = info.channel.and_then(channel_to_freq);
+  }
+  res
+}
+
+/// 读取所有无线接口
+pub fn get_wifi() -> e_utils::AnyResult<Vec<WifiInfo>> {
+  #[cfg(target_os = "linux")]
+  {
+    let proc = parse_proc_wireless(&std::fs::read_to_string("/proc/net/wireless").unwrap_or_default());
+    let mut names: Vec<String> = std::fs::read_dir(super::SYS_NET_ROOT)?
+      .flatten()
+      .filter(|v| v.path().join("wireless").exists() || v.path().join("phy80211").exists())
+      .map(|v| v.file_name().to_string_lossy().to_string())
+      .collect();
+    names.sort();
+    Ok(
+      names
+        .into_iter()
+        .map(|name| {
+          let mut info = match crate::common::cmd::exe("iw").args(["dev", &name, "link"]).output() {
+            Ok(v) => parse_iw_link(&name, &v.stdout),
+            Err(e) => {
+              crate::wp(format!("{} 读取 nl80211 信息失败: {}", name, e));
+              WifiInfo {
+                iface: name.clone(),
+                ..Default::default()
+              }
+            }
+          };
+          if let Some((quality, level)) = proc.get(&name) {
+            info.link_quality = Some(*quality);
+            info.signal_dbm = info.signal_dbm.or(Some(*level));
+          }
+          info
+        })
+        .collect(),
+    )
+  }
+  #[cfg(target_os = "windows")]
+  {
+    use e_utils::cmd::Cmd;
+    let output = Cmd::new("netsh").args(["wlan", "show", "interfaces"]).output()?;
+    Ok(parse_netsh_wlan(&output.stdout))
+  }
+  #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+  Err("不支持的系统".into())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PROC_WIRELESS: &str = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+wlp2s0: 0000   56.  -54.  -256        0      0      0      0     12        0
+";
+  const IW_LINK: &str = "Connected to A4:91:B1:00:11:22 (on wlp2s0)
+\tSSID: Factory Test 5G
+\tfreq: 5180.0
+\tRX: 48213 bytes (311 packets)
+\tTX: 12034 bytes (98 packets)
+\tsignal: -54 dBm
+\trx bitrate: 866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2
+\ttx bitrate: 780.0 MBit/s VHT-MCS 8 80MHz short GI VHT-NSS 2
+";
+  const NETSH: &str = "
+There is 1 interface on the system:
+
+    Name                   : Wi-Fi
+    Description            : Intel(R) Wi-Fi 6 AX201 160MHz
+    State                  : connected
+    SSID                   : Factory Test
+    BSSID                  : A4:91:B1:00:11:22
+    Radio type             : 802.11ax
+    Channel                : 6
+    Receive rate (Mbps)    : 286.8
+    Transmit rate (Mbps)   : 286.8
+    Signal                 : 80%
+    Profile                : Factory Test
+";
+
+  #[test]
+  fn test_parse_linux() {
+    assert_eq!(parse_proc_wireless(PROC_WIRELESS).get("wlp2s0"), Some(&(80, -54)));
+    let info = parse_iw_link("wlp2s0", IW_LINK);
+    assert_eq!(
+      info,
+      WifiInfo {
+        iface: "wlp2s0".into(),
+        ssid: Some("Factory Test 5G".into()),
+        bssid: Some("a4:91:b1:00:11:22".into()),
+        signal_dbm: Some(-54),
+        link_quality: None,
+        channel: Some(36),
+        frequency_mhz: Some(5180),
+        tx_bitrate_mbps: Some(780.0),
+      }
+    );
+    assert!(!parse_iw_link("wlp2s0", "Not connected.\n").is_connected());
+  }
+
+  #[test]
+  fn test_parse_netsh() {
+    let list = parse_netsh_wlan(NETSH);
+    assert_eq!(list.len(), 1);
+    let info = &list[0];
+    assert_eq!((info.iface.as_str(), info.ssid.as_deref()), ("Wi-Fi", Some("Factory Test")));
+    assert_eq!((info.channel, info.frequency_mhz), (Some(6), Some(2437)));
+    assert_eq!((info.link_quality, info.signal_dbm, info.tx_bitrate_mbps), (Some(80), Some(-60), Some(286.8)));
+  }
+
+  #[test]
+  fn test_check() {
+    let info = parse_iw_link("wlp2s0", IW_LINK);
+    assert!(info.check(-70, None).is_ok());
+    assert!(info.check(-70, Some("Factory Test 5G")).is_ok());
+    assert!(info.check(-70, Some("Office")).is_err());
+    assert_eq!(info.check(-50, None), Err("FAIL wlp2s0 信号 -54 dBm 低于 -50 dBm".into()));
+    assert_eq!(WifiInfo::default().check(-70, None), Err("FAIL  未连接".into()));
+  }
+}
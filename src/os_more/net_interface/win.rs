@@ -1,5 +1,5 @@
 use super::get_local_ipaddr;
-use super::ty::{allocate, deallocate, htonl, Gateway, Interface, InterfaceSimple, InterfaceStatus, InterfaceType, Ipv4Net, Ipv6Net, MacAddr};
+use super::ty::{allocate, deallocate, htonl, Gateway, Interface, InterfaceStatus, InterfaceType, Ipv4Net, Ipv6Net, MacAddr};
 use core::ffi::c_void;
 use libc::{c_char, strlen, wchar_t, wcslen};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
@@ -8,120 +8,6 @@ use windows::Win32::{
   NetworkManagement::IpHelper::{GetAdaptersAddresses, SendARP, AF_INET, AF_INET6, AF_UNSPEC, GAA_FLAG_INCLUDE_GATEWAYS, IP_ADAPTER_ADDRESSES_LH},
   Networking::WinSock::{SOCKADDR_IN, SOCKADDR_IN6},
 };
-///  "~Less100" => x.speed() < 100,
-/// "~100" => x.speed() >= 100,
-/// "~1000" => x.speed() >= 1000,
-/// "~Big1000" => x.speed() >= 10000,
-/// // 状态过滤
-/// "~is_connected" => x.is_connected(),
-/// "~has_dhcp_ip" => x.has_dhcp_ip(),
-pub fn get_interfaces_simple(filter: Vec<&str>) -> e_utils::AnyResult<Vec<InterfaceSimple>> {
-  // 如果没有过滤条件，直接返回所有接口
-  if filter.is_empty() {
-    let interfaces = get_interfaces();
-    let result = interfaces.iter().map(|x| x.to_simple()).collect::<Vec<_>>();
-    return if result.is_empty() { Err("No interfaces found".into()) } else { Ok(result) };
-  }
-
-  // 获取接口列表一次，避免重复调用
-  let interfaces = get_interfaces();
-  if interfaces.is_empty() {
-    return Err("No interfaces found".into());
-  }
-
-  // 预处理过滤条件，分离特殊过滤器和正则表达式
-  let mut has_speed_filter = false;
-  let mut has_connected_filter = false;
-  let mut has_dhcp_filter = false;
-  let mut has_auto_filter = false;
-  let mut mac_filters = Vec::new();
-
-  for &f in &filter {
-    match f {
-      "~Less100" | "~100" | "~1000" | "~Big1000" => has_speed_filter = true,
-      "~is_connected" => has_connected_filter = true,
-      "~has_dhcp_ip" => has_dhcp_filter = true,
-      "~auto" => has_auto_filter = true,
-      _ if f.starts_with('~') => {
-        mac_filters.push(f.trim_start_matches('~'));
-      }
-      _ => {} // 忽略其他特殊过滤器
-    }
-  }
-
-  let mut res = vec![];
-  // 处理所有接口
-  for x in &interfaces {
-    if has_auto_filter {
-      let friendly_name = &x.friendly_name;
-      if x.if_type == InterfaceType::Loopback
-        || friendly_name.contains("vEthernet")
-        || friendly_name.contains("vNIC")
-        || friendly_name.contains("vSwitch")
-        || friendly_name.contains("虚拟")
-      {
-        continue;
-      }
-    }
-    if mac_filters.len() > 0 {
-      if !mac_filters
-        .iter()
-        .any(|f| x.mac_addr.to_string().to_ascii_uppercase().starts_with(&f.to_ascii_uppercase()))
-      {
-        return Err(
-          format!(
-            "MAC地址过滤失败: {} 不符合规则 [{}]",
-            x.mac_addr.to_string().to_ascii_uppercase(),
-            mac_filters.join(", ")
-          )
-          .into(),
-        );
-      }
-    }
-    if apply_remaining_filters(x, has_speed_filter, has_connected_filter, has_dhcp_filter, &filter) {
-      res.push(x.to_simple());
-    }
-  }
-
-  if res.len() == 0 {
-    Err("No interfaces found".into())
-  } else {
-    Ok(res)
-  }
-}
-
-// 提取过滤逻辑到单独的函数，避免代码重复
-#[inline]
-fn apply_remaining_filters(x: &Interface, has_speed_filter: bool, has_connected_filter: bool, has_dhcp_filter: bool, all_filters: &[&str]) -> bool {
-  // 检查速度过滤器（需要计算速度）
-  if has_speed_filter {
-    // 只在有速度过滤器时计算速度
-    let speed = x.speed();
-
-    for &f in all_filters {
-      match f {
-        "~Less100" if speed >= 100 => return false,
-        "~100" if speed < 100 => return false,
-        "~1000" if speed < 1000 => return false,
-        "~Big1000" if speed < 10000 => return false,
-        _ => {}
-      }
-    }
-  }
-
-  // 检查连接状态
-  if has_connected_filter && !x.is_connected() {
-    return false;
-  }
-
-  // 检查DHCP状态
-  if has_dhcp_filter && !x.has_dhcp_ip() {
-    return false;
-  }
-
-  true
-}
-
 // Get network interfaces using the IP Helper API
 // Reference: https://docs.microsoft.com/en-us/windows/win32/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
 pub fn get_interfaces() -> Vec<Interface> {
@@ -278,6 +164,8 @@ pub fn get_interfaces() -> Vec<Interface> {
         gateway: default_gateway,
         oper_status: InterfaceStatus::from(unsafe { (*cur).OperStatus }),
         dns_servers,
+        stats: None,
+        topology: Default::default(),
       };
       interfaces.push(interface);
       cur = unsafe { (*cur).Next };
@@ -290,6 +178,7 @@ pub fn get_interfaces() -> Vec<Interface> {
   unsafe {
     deallocate(mem as *mut u8, dwsize as usize);
   }
+  super::stats::fill_missing_stats(&mut interfaces);
   return interfaces;
 }
 
@@ -0,0 +1,214 @@
+//! 多地址, 网关与 MTU 配置
+//!
+//! 直接修改运行时配置, 不写入网络管理器
+use super::{exec_cmd, poll_config, sudo, ConfigDiff, VERIFY_TIMEOUT};
+use crate::os_more::net_interface::{get_interfaces, Interface, IpNet};
+use e_utils::{cmd::Cmd, AnyResult};
+use std::net::IpAddr;
+
+/// MTU 范围, IPv4 最小 68
+pub const MTU_RANGE: std::ops::RangeInclusive<u32> = 68..=65535;
+
+/// 只有 netsh 需要引号, 其他命令直接传参
+fn cmd_name(adapter_name: &str) -> String {
+  if cfg!(target_os = "windows") {
+    format!("\"{}\"", adapter_name)
+  } else {
+    adapter_name.to_string()
+  }
+}
+
+/// 比较接口地址, present 为 false 时要求地址已移除
+pub fn diff_address(iface: &Interface, net: &IpNet, present: bool) -> Vec<ConfigDiff> {
+  let actual: Vec<IpNet> = iface
+    .ipv4
+    .iter()
+    .cloned()
+    .map(IpNet::V4)
+    .chain(iface.ipv6.iter().cloned().map(IpNet::V6))
+    .collect();
+  let found = match present {
+    true => actual.contains(net),
+    false => actual.iter().any(|v| v.addr() == net.addr()),
+  };
+  if found == present {
+    return vec![];
+  }
+  let expected = if present { net.to_string() } else { format!("!{}", net.addr()) };
+  let actual = actual.iter().map(ToString::to_string).collect::<Vec<_>>().join(",");
+  vec![ConfigDiff::new("address", expected, actual)]
+}
+
+async fn verify(adapter_name: &str, check: impl Fn(&Interface) -> Vec<ConfigDiff>) -> AnyResult<()> {
+  let diffs = poll_config(adapter_name, VERIFY_TIMEOUT, get_interfaces, check).await;
+  if diffs.is_empty() {
+    Ok(())
+  } else {
+    Err(format!("{} 配置未生效 -> {}", adapter_name, serde_json::to_string(&diffs)?).into())
+  }
+}
+
+/// 添加 IPv4/IPv6 地址
+pub async fn add_address(adapter_name: &str, net: &IpNet) -> AnyResult<String> {
+  let name = &cmd_name(adapter_name);
+  let res = match net {
+    IpNet::V4(v) if cfg!(target_os = "windows") => {
+      exec_cmd(Cmd::new("netsh").args(["interface", "ipv4", "add", "address", name, &v.addr.to_string(), &v.netmask.to_string()])).await?
+    }
+    IpNet::V6(_) if cfg!(target_os = "windows") => {
+      exec_cmd(Cmd::new("netsh").args(["interface", "ipv6", "add", "address", name, &net.to_string()])).await?
+    }
+    IpNet::V4(v) if cfg!(target_os = "macos") => {
+      exec_cmd(sudo().args(["ifconfig", name, "inet", &v.addr.to_string(), "netmask", &v.netmask.to_string(), "alias"])).await?
+    }
+    IpNet::V6(v) if cfg!(target_os = "macos") => {
+      exec_cmd(sudo().args(["ifconfig", name, "inet6", &v.addr.to_string(), "prefixlen", &v.prefix_len.to_string(), "alias"])).await?
+    }
+    _ => exec_cmd(sudo().args(["ip", "addr", "add", &net.to_string(), "dev", name])).await?,
+  };
+  crate::dp(&res);
+  verify(adapter_name, |iface| diff_address(iface, net, true)).await?;
+  Ok(format!("成功添加地址: {} {}", adapter_name, net))
+}
+
+/// 删除 IPv4/IPv6 地址
+pub async fn del_address(adapter_name: &str, net: &IpNet) -> AnyResult<String> {
+  let name = &cmd_name(adapter_name);
+  let addr = &net.addr().to_string();
+  let res = match net {
+    IpNet::V4(_) if cfg!(target_os = "windows") => exec_cmd(Cmd::new("netsh").args(["interface", "ipv4", "delete", "address", name, addr])).await?,
+    IpNet::V6(_) if cfg!(target_os = "windows") => exec_cmd(Cmd::new("netsh").args(["interface", "ipv6", "delete", "address", name, addr])).await?,
+    IpNet::V4(_) if cfg!(target_os = "macos") => exec_cmd(sudo().args(["ifconfig", name, "inet", addr, "-alias"])).await?,
+    IpNet::V6(_) if cfg!(target_os = "macos") => exec_cmd(sudo().args(["ifconfig", name, "inet6", addr, "-alias"])).await?,
+    _ => exec_cmd(sudo().args(["ip", "addr", "del", &net.to_string(), "dev", name])).await?,
+  };
+  crate::dp(&res);
+  verify(adapter_name, |iface| diff_address(iface, net, false)).await?;
+  Ok(format!("成功删除地址: {} {}", adapter_name, net))
+}
+
+/// 设置默认网关, 接口信息只有 IPv4 网关, IPv6 网关不做校验
+pub async fn set_gateway(adapter_name: &str, gateway: IpAddr) -> AnyResult<String> {
+  let name = &cmd_name(adapter_name);
+  let gw = &gateway.to_string();
+  let res = match () {
+    _ if cfg!(target_os = "windows") => {
+      let (family, dest) = if gateway.is_ipv4() { ("ipv4", "0.0.0.0/0") } else { ("ipv6", "::/0") };
+      exec_cmd(Cmd::new("netsh").args(["interface", family, "add", "route", dest, name, gw])).await?
+    }
+    _ if cfg!(target_os = "macos") => {
+      let family = if gateway.is_ipv4() { "-inet" } else { "-inet6" };
+      exec_cmd(sudo().args(["route", "add", family, "default", gw])).await?
+    }
+    _ => {
+      let family = if gateway.is_ipv4() { "-4" } else { "-6" };
+      exec_cmd(sudo().args(["ip", family, "route", "replace", "default", "via", gw, "dev", name])).await?
+    }
+  };
+  crate::dp(&res);
+  if gateway.is_ipv4() {
+    verify(adapter_name, |iface| {
+      let actual = iface.gateway.as_ref().map(|v| v.ip_addr);
+      match actual == Some(gateway) {
+        true => vec![],
+        false => vec![ConfigDiff::new("gateway", gateway, actual.map(|v| v.to_string()).unwrap_or_default())],
+      }
+    })
+    .await?;
+  }
+  Ok(format!("成功设置网关: {} {}", adapter_name, gateway))
+}
+
+/// 读取 MTU, 仅 Linux 支持
+pub fn read_mtu(adapter_name: &str) -> Option<u32> {
+  #[cfg(target_os = "linux")]
+  return crate::common::sysfs::read_num(std::path::Path::new("/sys/class/net").join(adapter_name).join("mtu"));
+  #[cfg(not(target_os = "linux"))]
+  {
+    let _ = adapter_name;
+    None
+  }
+}
+
+/// 设置 MTU
+pub async fn set_mtu(adapter_name: &str, mtu: u32) -> AnyResult<String> {
+  if !MTU_RANGE.contains(&mtu) {
+    return Err(format!("MTU 超出范围 {:?}: {}", MTU_RANGE, mtu).into());
+  }
+  let name = &cmd_name(adapter_name);
+  let value = &mtu.to_string();
+  let res = match () {
+    _ if cfg!(target_os = "windows") => {
+      let res = exec_cmd(Cmd::new("netsh").args(["interface", "ipv4", "set", "subinterface", name, &format!("mtu={}", mtu), "store=persistent"])).await?;
+      // IPv6 最小 MTU 为 1280, 禁用 IPv6 时也会失败
+      let _ = exec_cmd(Cmd::new("netsh").args(["interface", "ipv6", "set", "subinterface", name, &format!("mtu={}", mtu), "store=persistent"])).await;
+      res
+    }
+    _ if cfg!(target_os = "macos") => exec_cmd(sudo().args(["ifconfig", name, "mtu", value])).await?,
+    _ => exec_cmd(sudo().args(["ip", "link", "set", "dev", name, "mtu", value])).await?,
+  };
+  crate::dp(&res);
+  match read_mtu(adapter_name) {
+    Some(actual) if actual != mtu => Err(
+      format!(
+        "{} 配置未生效 -> {}",
+        adapter_name,
+        serde_json::to_string(&[ConfigDiff::new("mtu", mtu, actual)])?
+      )
+      .into(),
+    ),
+    _ => Ok(format!("成功设置MTU: {} {}", adapter_name, mtu)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::os_more::net_interface::{InterfaceStatus, InterfaceType, Ipv4Net, Ipv6Net, MacAddr};
+
+  #[test]
+  fn test_ipnet_parse() {
+    let net: IpNet = "192.168.1.20/255.255.255.0".parse().unwrap();
+    assert_eq!(net, "192.168.1.20/24".parse().unwrap());
+    assert_eq!(net.to_string(), "192.168.1.20/24");
+    assert_eq!("10.0.0.1".parse::<Ipv4Net>().unwrap().prefix_len, 32);
+    assert_eq!("fd00::10/64".parse::<IpNet>().unwrap().to_string(), "fd00::10/64");
+    assert_eq!("fd00::10".parse::<IpNet>().unwrap().prefix_len(), 128);
+    for v in ["192.168.1.20/33", "192.168.1.20/255.0.255.0", "192.168.1/24", "fd00::10/129", "fd00::zz/64"] {
+      assert!(v.parse::<IpNet>().is_err(), "{}", v);
+    }
+    assert_eq!(super::super::parse_prefix("255.255.255.0"), Some(24));
+    assert_eq!(super::super::parse_prefix("255.0.255.0"), None);
+  }
+
+  #[test]
+  fn test_diff_address() {
+    let iface = Interface {
+      index: 2,
+      name: "enp3s0".into(),
+      friendly_name: "enp3s0".into(),
+      description: String::new(),
+      if_type: InterfaceType::Ethernet,
+      mac_addr: MacAddr::zero(),
+      ipv4: vec!["192.168.1.20/24".parse().unwrap()],
+      ipv6: vec!["fd00::10/64".parse::<Ipv6Net>().unwrap()],
+      flags: 0,
+      transmit_speed: 0,
+      receive_speed: 0,
+      gateway: None,
+      oper_status: InterfaceStatus::Up,
+      dns_servers: vec![],
+      stats: None,
+      topology: Default::default(),
+    };
+    let net = |v: &str| v.parse::<IpNet>().unwrap();
+    assert!(diff_address(&iface, &net("192.168.1.20/24"), true).is_empty());
+    assert!(diff_address(&iface, &net("fd00::10/64"), true).is_empty());
+    assert!(diff_address(&iface, &net("fd00::11/64"), false).is_empty());
+    assert_eq!(
+      diff_address(&iface, &net("192.168.1.20/16"), true),
+      [ConfigDiff::new("address", "192.168.1.20/16", "192.168.1.20/24,fd00::10/64")]
+    );
+    assert_eq!(diff_address(&iface, &net("fd00::10/48"), false)[0].expected, "!fd00::10");
+  }
+}
@@ -0,0 +1,95 @@
+//! ifupdown `/etc/network/interfaces`
+use super::{cidr, InterfaceConfig};
+use crate::os_more::net_manage::AddressMode;
+
+pub const IFUPDOWN_FILE: &str = "/etc/network/interfaces";
+/// 段落起始关键字
+const STANZAS: [&str; 4] = ["iface", "auto", "mapping", "source"];
+
+fn is_stanza(word: &str) -> bool {
+  word.starts_with("allow-") || STANZAS.contains(&word)
+}
+
+/// 生成网卡的 IPv4 段落
+pub fn ifupdown_stanza(config: &InterfaceConfig) -> String {
+  let name = &config.name;
+  let method = match config.ip_mode {
+    AddressMode::Dhcp => "dhcp",
+    AddressMode::Static => "static",
+    AddressMode::None => "manual",
+  };
+  let mut stanza = format!("auto {}\niface {} inet {}\n", name, name, method);
+  if config.ip_mode == AddressMode::Static {
+    if let Some(cidr) = cidr(config) {
+      stanza.push_str(&format!("    address {}\n", cidr));
+    }
+    if let Some(gateway) = &config.gateway {
+      stanza.push_str(&format!("    gateway {}\n", gateway));
+    }
+  }
+  if config.dns_mode == AddressMode::Static && !config.dns.is_empty() {
+    stanza.push_str(&format!("    dns-nameservers {}\n", config.dns.join(" ")));
+  }
+  stanza
+}
+
+/// 移除原有的该网卡 IPv4 段落及 auto/allow-* 声明, 追加新段落
+pub fn ifupdown_merge(existing: &str, config: &InterfaceConfig) -> String {
+  let name = config.name.as_str();
+  let mut lines: Vec<String> = vec![];
+  let mut skip = false;
+  for line in existing.lines() {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.first() {
+      Some(first) if is_stanza(first) => skip = *first == "iface" && words.get(1) == Some(&name) && words.get(2) == Some(&"inet"),
+      _ => {}
+    }
+    if skip {
+      continue;
+    }
+    match words.first() {
+      Some(first) if (*first == "auto" || first.starts_with("allow-")) && words.contains(&name) => {
+        let rest: Vec<&str> = words.into_iter().filter(|v| *v != name).collect();
+        if rest.len() > 1 {
+          lines.push(rest.join(" "));
+        }
+      }
+      _ => lines.push(line.to_string()),
+    }
+  }
+  while lines.last().is_some_and(|v| v.trim().is_empty()) {
+    lines.pop();
+  }
+  let mut merged = lines.join("\n");
+  if !merged.is_empty() {
+    merged.push_str("\n\n");
+  }
+  merged.push_str(&ifupdown_stanza(config));
+  merged
+}
+
+/// 只修改网卡 IPv4 段落中的 dns-nameservers, 没有该段落时返回 None
+pub fn ifupdown_patch_dns(existing: &str, config: &InterfaceConfig) -> Option<String> {
+  let name = config.name.as_str();
+  let mut lines: Vec<&str> = existing.lines().collect();
+  let start = lines.iter().position(|line| {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    words.first() == Some(&"iface") && words.get(1) == Some(&name) && words.get(2) == Some(&"inet")
+  })?;
+  let end = lines[start + 1..]
+    .iter()
+    .position(|line| line.split_whitespace().next().is_some_and(is_stanza))
+    .map_or(lines.len(), |i| start + 1 + i);
+  let mut stanza: Vec<&str> = lines[start..end]
+    .iter()
+    .filter(|line| line.split_whitespace().next() != Some("dns-nameservers"))
+    .copied()
+    .collect();
+  let dns = format!("    dns-nameservers {}", config.dns.join(" "));
+  if config.dns_mode == AddressMode::Static && !config.dns.is_empty() {
+    let at = stanza.iter().rposition(|v| !v.trim().is_empty()).map_or(stanza.len(), |i| i + 1);
+    stanza.insert(at, &dns);
+  }
+  lines.splice(start..end, stanza);
+  Some(lines.iter().map(|line| format!("{}\n", line)).collect())
+}
@@ -0,0 +1,469 @@
+//! Linux 网络配置后端
+//!
+//! 检测当前生效的网络管理器并通过它修改配置, 避免与其冲突:
+//! - NetworkManager: `nmcli` 修改连接配置
+//! - netplan: 写入 `/etc/netplan/90-hw-<网卡>.yaml`
+//! - systemd-networkd: 写入 `/etc/systemd/network/10-hw-<网卡>.network`
+//! - ifupdown: 替换 `/etc/network/interfaces` 中的网卡段落
+//!
+//! 只修改 DNS 时保留已有的地址配置, 只替换其中的 DNS 配置项
+//!
+//! 配置文件由纯函数生成, 命令通过 [`CmdRunner`] 执行, 测试使用 [`RecordRunner`]
+use super::{exec_cmd, parse_prefix, sudo, InterfaceConfig};
+use crate::os_more::net_interface::get_interfaces;
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, collections::HashMap, fmt, future::Future};
+
+mod ifupdown;
+mod netplan;
+mod networkd;
+mod nmcli;
+pub use ifupdown::*;
+pub use netplan::*;
+pub use networkd::*;
+pub use nmcli::*;
+
+/// 命令执行与文件读写
+pub trait CmdRunner {
+  /// 执行命令, 退出码非 0 时返回错误
+  fn run(&self, program: &str, args: &[String]) -> impl Future<Output = AnyResult<String>>;
+  fn read_file(&self, path: &str) -> Option<String>;
+  /// 写入文件, 与 run 使用相同的权限
+  fn write_file(&self, path: &str, content: &str) -> impl Future<Output = AnyResult<()>>;
+  /// 列出目录下的文件名
+  fn list_dir(&self, path: &str) -> Vec<String>;
+}
+
+/// 通过 sudo 执行系统命令与写入文件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemRunner;
+impl CmdRunner for SystemRunner {
+  async fn run(&self, program: &str, args: &[String]) -> AnyResult<String> {
+    exec_cmd(sudo().args([program]).args(args)).await
+  }
+  fn read_file(&self, path: &str) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+  }
+  /// 先写入临时文件, 再通过 sudo install 放到目标位置
+  async fn write_file(&self, path: &str, content: &str) -> AnyResult<()> {
+    let tmp = std::env::temp_dir().join(format!("hw-{}-{}", std::process::id(), path.rsplit('/').next().unwrap_or_default()));
+    std::fs::write(&tmp, content)?;
+    // netplan 要求配置文件不可被其他用户读取
+    let mode = if path.starts_with(NETPLAN_DIR) { "600" } else { "644" };
+    let res = self
+      .run(
+        "install",
+        &["-D".into(), "-m".into(), mode.into(), tmp.to_string_lossy().to_string(), path.into()],
+      )
+      .await;
+    let _ = std::fs::remove_file(&tmp);
+    res.map(|_| ())
+  }
+  fn list_dir(&self, path: &str) -> Vec<String> {
+    std::fs::read_dir(path)
+      .map(|dir| dir.flatten().map(|v| v.file_name().to_string_lossy().to_string()).collect())
+      .unwrap_or_default()
+  }
+}
+
+/// 记录命令不执行, 文件读写保存在内存中
+#[derive(Debug, Default)]
+pub struct RecordRunner {
+  /// 命令行 -> 输出, 未设置的命令输出为空
+  pub responses: HashMap<String, String>,
+  pub files: RefCell<HashMap<String, String>>,
+  pub calls: RefCell<Vec<String>>,
+}
+impl RecordRunner {
+  pub fn new() -> Self {
+    Self::default()
+  }
+  pub fn respond(mut self, cmdline: &str, stdout: &str) -> Self {
+    self.responses.insert(cmdline.into(), stdout.into());
+    self
+  }
+  pub fn file(self, path: &str, content: &str) -> Self {
+    self.files.borrow_mut().insert(path.into(), content.into());
+    self
+  }
+}
+impl CmdRunner for RecordRunner {
+  async fn run(&self, program: &str, args: &[String]) -> AnyResult<String> {
+    let cmdline = std::iter::once(program).chain(args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ");
+    let res = self.responses.get(&cmdline).cloned().unwrap_or_default();
+    self.calls.borrow_mut().push(cmdline);
+    Ok(res)
+  }
+  fn read_file(&self, path: &str) -> Option<String> {
+    self.files.borrow().get(path).cloned()
+  }
+  async fn write_file(&self, path: &str, content: &str) -> AnyResult<()> {
+    self.files.borrow_mut().insert(path.into(), content.into());
+    Ok(())
+  }
+  fn list_dir(&self, path: &str) -> Vec<String> {
+    let prefix = format!("{}/", path.trim_end_matches('/'));
+    self.files.borrow().keys().filter_map(|k| k.strip_prefix(&prefix)).map(String::from).collect()
+  }
+}
+
+/// 要修改的配置部分
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ConfigPart {
+  /// 地址与 DNS 整体重写
+  All,
+  /// 只修改 DNS
+  Dns,
+}
+
+/// 网络管理器
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NetBackend {
+  NetworkManager,
+  Netplan,
+  Networkd,
+  Ifupdown,
+  /// 未检测到网络管理器, 直接使用 ip/dhclient/resolv.conf
+  Iproute,
+}
+impl fmt::Display for NetBackend {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      NetBackend::NetworkManager => "NetworkManager",
+      NetBackend::Netplan => "netplan",
+      NetBackend::Networkd => "systemd-networkd",
+      NetBackend::Ifupdown => "ifupdown",
+      NetBackend::Iproute => "iproute",
+    })
+  }
+}
+impl NetBackend {
+  /// 检测网络管理器
+  ///
+  /// netplan 桌面版由 NetworkManager 渲染, 所以优先检测 NetworkManager
+  pub async fn detect<R: CmdRunner>(runner: &R) -> Self {
+    let output = |program: &'static str, args: &'static [&'static str]| async move {
+      let args: Vec<String> = args.iter().map(|v| v.to_string()).collect();
+      runner.run(program, &args).await.map(|v| v.trim().to_string()).unwrap_or_default()
+    };
+    if output("nmcli", &["-t", "-f", "RUNNING", "general"]).await == "running" {
+      NetBackend::NetworkManager
+    } else if runner.list_dir(NETPLAN_DIR).iter().any(|v| v.ends_with(".yaml")) {
+      NetBackend::Netplan
+    } else if output("systemctl", &["is-active", "systemd-networkd"]).await == "active" {
+      NetBackend::Networkd
+    } else if runner
+      .read_file(IFUPDOWN_FILE)
+      .is_some_and(|v| v.lines().any(|l| l.trim_start().starts_with("iface ")))
+    {
+      NetBackend::Ifupdown
+    } else {
+      NetBackend::Iproute
+    }
+  }
+  /// 写入配置并使其生效, part 为 Dns 时只修改 DNS 相关配置
+  pub async fn apply<R: CmdRunner>(&self, runner: &R, config: &InterfaceConfig, part: ConfigPart) -> AnyResult<String> {
+    let name = &config.name;
+    let args = |v: &[&str]| v.iter().map(|v| v.to_string()).collect::<Vec<String>>();
+    match self {
+      NetBackend::NetworkManager => {
+        let mut conn = runner
+          .run("nmcli", &args(&["-g", "GENERAL.CONNECTION", "device", "show", name]))
+          .await?
+          .trim()
+          .to_string();
+        if conn.is_empty() {
+          conn = format!("hw-{}", name);
+          runner.run("nmcli", &nmcli_add_args(&conn, name)).await?;
+        }
+        let props = match part {
+          ConfigPart::All => nmcli_args(config),
+          ConfigPart::Dns => nmcli_dns_args(config),
+        };
+        runner.run("nmcli", &[args(&["connection", "modify", &conn]), props].concat()).await?;
+        runner.run("nmcli", &args(&["connection", "up", &conn])).await?;
+      }
+      NetBackend::Netplan => {
+        let path = netplan_path(name);
+        let yaml = match part {
+          ConfigPart::All => netplan_yaml(config),
+          ConfigPart::Dns => netplan_patch_dns(runner.read_file(&path).as_deref(), config),
+        };
+        runner.write_file(&path, &yaml).await?;
+        runner.run("netplan", &args(&["apply"])).await?;
+      }
+      NetBackend::Networkd => {
+        let (path, unit) = match part {
+          ConfigPart::All => (networkd_path(name), networkd_unit(config)),
+          ConfigPart::Dns => {
+            // 修改当前生效的配置文件, 本工具的文件排序在前, 存在时即为生效的文件
+            let path = match runner.read_file(&networkd_path(name)) {
+              Some(_) => networkd_path(name),
+              None => {
+                networkd_file(&runner.run("networkctl", &args(&["status", name])).await?).ok_or(format!("{} 没有 systemd-networkd 配置文件", name))?
+              }
+            };
+            let existing = runner.read_file(&path).ok_or(format!("读取 {} 失败", path))?;
+            let unit = networkd_patch_dns(&existing, config);
+            (path, unit)
+          }
+        };
+        runner.write_file(&path, &unit).await?;
+        runner.run("networkctl", &args(&["reload"])).await?;
+        runner.run("networkctl", &args(&["reconfigure", name])).await?;
+      }
+      NetBackend::Ifupdown => {
+        let existing = runner.read_file(IFUPDOWN_FILE).unwrap_or_default();
+        let content = match part {
+          ConfigPart::All => ifupdown_merge(&existing, config),
+          ConfigPart::Dns => ifupdown_patch_dns(&existing, config).ok_or(format!("{} 中没有 {} 的 inet 段落", IFUPDOWN_FILE, name))?,
+        };
+        runner.write_file(IFUPDOWN_FILE, &content).await?;
+        // 网卡未启用时 ifdown 会失败
+        let _ = runner.run("ifdown", &args(&[name])).await;
+        runner.run("ifup", &args(&[name])).await?;
+      }
+      NetBackend::Iproute => return Err(format!("{} 未检测到网络管理器", name).into()),
+    }
+    Ok(format!("{}={}", name, self))
+  }
+}
+
+/// 静态地址的 CIDR 形式
+fn cidr(config: &InterfaceConfig) -> Option<String> {
+  let prefix = config.netmask.as_deref().and_then(parse_prefix)?;
+  config.address.as_ref().map(|v| format!("{}/{}", v, prefix))
+}
+
+/// 通过检测到的网络管理器修改网卡配置, 没有网络管理器时返回 None
+pub async fn configure_managed(adapter_name: &str, part: ConfigPart, update: impl FnOnce(&mut InterfaceConfig)) -> AnyResult<Option<String>> {
+  let runner = SystemRunner;
+  let backend = NetBackend::detect(&runner).await;
+  if backend == NetBackend::Iproute {
+    return Ok(None);
+  }
+  let iface = get_interfaces()
+    .into_iter()
+    .find(|v| v.friendly_name == adapter_name || v.name == adapter_name)
+    .ok_or(format!("未找到网卡: {}", adapter_name))?;
+  let mut config = InterfaceConfig::from_interface(&iface);
+  config.name = iface.name;
+  update(&mut config);
+  crate::dp(format!("{} 通过 {} 配置: {:?}", adapter_name, backend, config));
+  backend.apply(&runner, &config, part).await.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::os_more::net_manage::AddressMode;
+
+  fn config(ip_mode: AddressMode, dns_mode: AddressMode) -> InterfaceConfig {
+    let is_static = ip_mode == AddressMode::Static;
+    InterfaceConfig {
+      name: "enp3s0".into(),
+      ip_mode,
+      address: is_static.then(|| "192.168.1.20".into()),
+      netmask: is_static.then(|| "255.255.255.0".into()),
+      gateway: is_static.then(|| "192.168.1.1".into()),
+      dns_mode,
+      dns: match dns_mode {
+        AddressMode::Static => vec!["223.5.5.5".into(), "114.114.114.114".into()],
+        _ => vec![],
+      },
+    }
+  }
+
+  #[test]
+  fn test_generators() {
+    let fixed = config(AddressMode::Static, AddressMode::Static);
+    let dhcp = config(AddressMode::Dhcp, AddressMode::Dhcp);
+    let dual_stack = InterfaceConfig {
+      dns: vec!["223.5.5.5".into(), "2400:3200::1".into()],
+      ..fixed.clone()
+    };
+    assert!(nmcli_args(&dual_stack)
+      .join(" ")
+      .ends_with("ipv4.dns 223.5.5.5 ipv4.ignore-auto-dns yes ipv6.dns 2400:3200::1 ipv6.ignore-auto-dns yes"));
+    assert_eq!(
+      nmcli_args(&fixed).join(" "),
+      "ipv4.method manual ipv4.addresses 192.168.1.20/24 ipv4.gateway 192.168.1.1 ipv4.dns 223.5.5.5,114.114.114.114 ipv4.ignore-auto-dns yes"
+    );
+    assert_eq!(
+      nmcli_args(&dhcp),
+      [
+        "ipv4.method",
+        "auto",
+        "ipv4.addresses",
+        "",
+        "ipv4.gateway",
+        "",
+        "ipv4.dns",
+        "",
+        "ipv4.ignore-auto-dns",
+        "no"
+      ]
+    );
+
+    assert_eq!(
+      networkd_unit(&fixed),
+      "[Match]\nName=enp3s0\n\n[Network]\nAddress=192.168.1.20/24\nGateway=192.168.1.1\nDNS=223.5.5.5\nDNS=114.114.114.114\n"
+    );
+    let dhcp_static_dns = config(AddressMode::Dhcp, AddressMode::Static);
+    assert!(networkd_unit(&dhcp_static_dns).ends_with("DHCP=ipv4\nDNS=223.5.5.5\nDNS=114.114.114.114\n\n[DHCPv4]\nUseDNS=false\n"));
+
+    assert_eq!(
+      netplan_yaml(&fixed),
+      "network:
+  version: 2
+  ethernets:
+    enp3s0:
+      dhcp4: false
+      addresses: [192.168.1.20/24]
+      routes:
+        - to: default
+          via: 192.168.1.1
+      nameservers:
+        addresses: [223.5.5.5, 114.114.114.114]
+"
+    );
+    assert!(netplan_yaml(&dhcp_static_dns).ends_with("      dhcp4-overrides:\n        use-dns: false\n"));
+
+    assert_eq!(
+      ifupdown_stanza(&fixed),
+      "auto enp3s0\niface enp3s0 inet static\n    address 192.168.1.20/24\n    gateway 192.168.1.1\n    dns-nameservers 223.5.5.5 114.114.114.114\n"
+    );
+    assert_eq!(ifupdown_stanza(&dhcp), "auto enp3s0\niface enp3s0 inet dhcp\n");
+  }
+
+  #[test]
+  fn test_ifupdown_merge() {
+    let existing = "source /etc/network/interfaces.d/*
+
+auto lo enp3s0
+iface lo inet loopback
+
+# 有线
+iface enp3s0 inet static
+    address 10.0.0.2
+    netmask 255.0.0.0
+iface enp3s0 inet6 auto
+
+allow-hotplug enp3s0
+";
+    let merged = ifupdown_merge(existing, &config(AddressMode::Dhcp, AddressMode::Dhcp));
+    assert_eq!(
+      merged,
+      "source /etc/network/interfaces.d/*
+
+auto lo
+iface lo inet loopback
+
+# 有线
+iface enp3s0 inet6 auto
+
+auto enp3s0
+iface enp3s0 inet dhcp
+"
+    );
+    // 再次合并结果不变
+    assert_eq!(ifupdown_merge(&merged, &config(AddressMode::Dhcp, AddressMode::Dhcp)), merged);
+  }
+
+  #[tokio::test]
+  async fn test_backend_apply() {
+    let fixed = config(AddressMode::Static, AddressMode::Static);
+    let runner = RecordRunner::new()
+      .respond("nmcli -t -f RUNNING general", "running\n")
+      .respond("nmcli -g GENERAL.CONNECTION device show enp3s0", "Wired connection 1\n");
+    assert_eq!(NetBackend::detect(&runner).await, NetBackend::NetworkManager);
+    assert_eq!(
+      NetBackend::NetworkManager.apply(&runner, &fixed, ConfigPart::All).await.unwrap(),
+      "enp3s0=NetworkManager"
+    );
+    let calls = runner.calls.take();
+    assert!(calls[2].starts_with("nmcli connection modify Wired connection 1 ipv4.method manual"));
+    assert_eq!(calls[3], "nmcli connection up Wired connection 1");
+
+    // 没有连接配置时新建
+    let runner = RecordRunner::new();
+    NetBackend::NetworkManager.apply(&runner, &fixed, ConfigPart::All).await.unwrap();
+    assert_eq!(
+      runner.calls.borrow()[1],
+      "nmcli connection add type ethernet ifname enp3s0 con-name hw-enp3s0 autoconnect yes"
+    );
+
+    let runner = RecordRunner::new().file("/etc/netplan/50-cloud-init.yaml", "network: {}");
+    assert_eq!(NetBackend::detect(&runner).await, NetBackend::Netplan);
+    NetBackend::Netplan.apply(&runner, &fixed, ConfigPart::All).await.unwrap();
+    assert_eq!(runner.read_file(&netplan_path("enp3s0")), Some(netplan_yaml(&fixed)));
+    assert_eq!(runner.calls.borrow().last().unwrap(), "netplan apply");
+
+    let runner = RecordRunner::new().respond("systemctl is-active systemd-networkd", "active\n");
+    assert_eq!(NetBackend::detect(&runner).await, NetBackend::Networkd);
+    NetBackend::Networkd.apply(&runner, &fixed, ConfigPart::All).await.unwrap();
+    assert_eq!(runner.read_file("/etc/systemd/network/10-hw-enp3s0.network"), Some(networkd_unit(&fixed)));
+    assert_eq!(runner.calls.borrow()[2..], ["networkctl reload", "networkctl reconfigure enp3s0"]);
+
+    let runner = RecordRunner::new().file(IFUPDOWN_FILE, "auto lo\niface lo inet loopback\n");
+    assert_eq!(NetBackend::detect(&runner).await, NetBackend::Ifupdown);
+    NetBackend::Ifupdown.apply(&runner, &fixed, ConfigPart::All).await.unwrap();
+    assert!(runner.read_file(IFUPDOWN_FILE).unwrap().ends_with(&ifupdown_stanza(&fixed)));
+    assert_eq!(runner.calls.borrow()[2..], ["ifdown enp3s0", "ifup enp3s0"]);
+
+    assert_eq!(NetBackend::detect(&RecordRunner::new()).await, NetBackend::Iproute);
+    assert!(NetBackend::Iproute.apply(&RecordRunner::new(), &fixed, ConfigPart::All).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_backend_apply_dns() {
+    // 只改 DNS 时配置中的地址来自接口当前状态, 不应写入
+    let dns = InterfaceConfig {
+      dns: vec!["1.1.1.1".into()],
+      ..config(AddressMode::Static, AddressMode::Static)
+    };
+
+    let runner = RecordRunner::new().respond("nmcli -g GENERAL.CONNECTION device show enp3s0", "Wired connection 1\n");
+    NetBackend::NetworkManager.apply(&runner, &dns, ConfigPart::Dns).await.unwrap();
+    assert_eq!(
+      runner.calls.borrow()[1],
+      "nmcli connection modify Wired connection 1 ipv4.dns 1.1.1.1 ipv4.ignore-auto-dns yes"
+    );
+
+    let path = netplan_path("enp3s0");
+    let runner = RecordRunner::new().file(&path, &netplan_yaml(&config(AddressMode::Static, AddressMode::Static)));
+    NetBackend::Netplan.apply(&runner, &dns, ConfigPart::Dns).await.unwrap();
+    let yaml = runner.read_file(&path).unwrap();
+    assert!(yaml.contains("      addresses: [192.168.1.20/24]\n      routes:\n        - to: default\n          via: 192.168.1.1\n"));
+    assert!(yaml.ends_with("      nameservers:\n        addresses: [1.1.1.1]\n") && !yaml.contains("223.5.5.5"));
+    // 没有本工具的文件时只写 DNS, 由 netplan 合并
+    let runner = RecordRunner::new();
+    NetBackend::Netplan.apply(&runner, &dns, ConfigPart::Dns).await.unwrap();
+    assert!(!runner.read_file(&path).unwrap().contains("dhcp4"));
+
+    let unit = "[Match]\nName=en*\n\n[Network]\nDHCP=ipv4\nDNS=223.5.5.5\n\n[Link]\nMTUBytes=9000\n";
+    let runner = RecordRunner::new()
+      .respond(
+        "networkctl status enp3s0",
+        "● 2: enp3s0\n     Network File: /etc/systemd/network/20-wired.network\n",
+      )
+      .file("/etc/systemd/network/20-wired.network", unit);
+    NetBackend::Networkd.apply(&runner, &dns, ConfigPart::Dns).await.unwrap();
+    assert_eq!(
+      runner.read_file("/etc/systemd/network/20-wired.network").unwrap(),
+      "[Match]\nName=en*\n\n[Network]\nDHCP=ipv4\nDNS=1.1.1.1\n\n[Link]\nMTUBytes=9000\n\n[DHCPv4]\nUseDNS=false\n"
+    );
+    assert!(runner.read_file(&networkd_path("enp3s0")).is_none());
+
+    let interfaces = "auto enp3s0\niface enp3s0 inet static\n    address 10.0.0.2/8\n    dns-nameservers 223.5.5.5\n\niface enp4s0 inet dhcp\n";
+    let runner = RecordRunner::new().file(IFUPDOWN_FILE, interfaces);
+    NetBackend::Ifupdown.apply(&runner, &dns, ConfigPart::Dns).await.unwrap();
+    assert_eq!(
+      runner.read_file(IFUPDOWN_FILE).unwrap(),
+      "auto enp3s0\niface enp3s0 inet static\n    address 10.0.0.2/8\n    dns-nameservers 1.1.1.1\n\niface enp4s0 inet dhcp\n"
+    );
+    let runner = RecordRunner::new().file(IFUPDOWN_FILE, "iface enp4s0 inet dhcp\n");
+    assert!(NetBackend::Ifupdown.apply(&runner, &dns, ConfigPart::Dns).await.is_err());
+  }
+}
@@ -0,0 +1,71 @@
+//! netplan YAML
+use super::{cidr, InterfaceConfig};
+use crate::os_more::net_manage::AddressMode;
+
+/// 配置目录, 文件按名称排序加载, 后加载的覆盖同名网卡配置
+pub const NETPLAN_DIR: &str = "/etc/netplan";
+
+pub fn netplan_path(name: &str) -> String {
+  format!("{}/90-hw-{}.yaml", NETPLAN_DIR, name)
+}
+
+/// 生成 netplan 配置
+pub fn netplan_yaml(config: &InterfaceConfig) -> String {
+  let mut yaml = format!("network:\n  version: 2\n  ethernets:\n    {}:\n", config.name);
+  match config.ip_mode {
+    AddressMode::Dhcp => yaml.push_str("      dhcp4: true\n"),
+    AddressMode::Static => {
+      yaml.push_str("      dhcp4: false\n");
+      if let Some(cidr) = cidr(config) {
+        yaml.push_str(&format!("      addresses: [{}]\n", cidr));
+      }
+      if let Some(gateway) = &config.gateway {
+        yaml.push_str(&format!("      routes:\n        - to: default\n          via: {}\n", gateway));
+      }
+    }
+    AddressMode::None => {}
+  }
+  if config.dns_mode == AddressMode::Static {
+    yaml.push_str(&netplan_dns(config));
+  }
+  yaml
+}
+
+/// 静态 DNS 的配置项
+fn netplan_dns(config: &InterfaceConfig) -> String {
+  let mut yaml = format!("      nameservers:\n        addresses: [{}]\n", config.dns.join(", "));
+  if config.ip_mode == AddressMode::Dhcp {
+    yaml.push_str("      dhcp4-overrides:\n        use-dns: false\n");
+  }
+  yaml
+}
+
+/// 只修改 DNS
+///
+/// 已有本工具的配置文件时替换其中的 DNS 配置项, 否则生成只含 DNS 的文件, 由 netplan 与其他文件中的同名网卡配置合并
+pub fn netplan_patch_dns(existing: Option<&str>, config: &InterfaceConfig) -> String {
+  let mut yaml = match existing {
+    Some(existing) => {
+      let mut skip = false;
+      let mut lines = String::new();
+      for line in existing.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if indent <= 6 {
+          skip = indent == 6 && (line.trim() == "nameservers:" || line.trim() == "dhcp4-overrides:");
+        }
+        if !skip {
+          lines.push_str(line);
+          lines.push('\n');
+        }
+      }
+      lines
+    }
+    None => format!("network:\n  version: 2\n  ethernets:\n    {}:\n", config.name),
+  };
+  match config.dns_mode {
+    AddressMode::Static => yaml.push_str(&netplan_dns(config)),
+    AddressMode::Dhcp => yaml.push_str("      dhcp4-overrides:\n        use-dns: true\n"),
+    AddressMode::None => {}
+  }
+  yaml
+}
@@ -0,0 +1,101 @@
+//! systemd-networkd `.network` 文件
+use super::{cidr, InterfaceConfig};
+use crate::os_more::net_manage::AddressMode;
+
+/// 配置目录, 同名网卡匹配文件名排序最前的文件
+pub const NETWORKD_DIR: &str = "/etc/systemd/network";
+
+pub fn networkd_path(name: &str) -> String {
+  format!("{}/10-hw-{}.network", NETWORKD_DIR, name)
+}
+
+/// 生成 `.network` 文件内容
+pub fn networkd_unit(config: &InterfaceConfig) -> String {
+  let mut unit = format!("[Match]\nName={}\n\n[Network]\n", config.name);
+  match config.ip_mode {
+    AddressMode::Dhcp => unit.push_str("DHCP=ipv4\n"),
+    AddressMode::Static => {
+      if let Some(cidr) = cidr(config) {
+        unit.push_str(&format!("Address={}\n", cidr));
+      }
+      if let Some(gateway) = &config.gateway {
+        unit.push_str(&format!("Gateway={}\n", gateway));
+      }
+    }
+    AddressMode::None => {}
+  }
+  if config.dns_mode == AddressMode::Static {
+    for dns in &config.dns {
+      unit.push_str(&format!("DNS={}\n", dns));
+    }
+    if config.ip_mode == AddressMode::Dhcp {
+      unit.push_str("\n[DHCPv4]\nUseDNS=false\n");
+    }
+  }
+  unit
+}
+
+/// `networkctl status <网卡>` 输出中的配置文件路径
+pub fn networkd_file(status: &str) -> Option<String> {
+  status
+    .lines()
+    .find_map(|line| line.trim().strip_prefix("Network File:"))
+    .map(|v| v.trim().to_string())
+    .filter(|v| v.starts_with('/'))
+}
+
+/// 只修改已有 `.network` 文件中的 DNS 配置项, 其他配置保持不变
+pub fn networkd_patch_dns(existing: &str, config: &InterfaceConfig) -> String {
+  // (段落名, 行)
+  let mut sections: Vec<(String, Vec<String>)> = vec![(String::new(), vec![])];
+  for line in existing.lines() {
+    let trimmed = line.trim();
+    if trimmed.starts_with('[') {
+      sections.push((trimmed.to_string(), vec![line.to_string()]));
+      continue;
+    }
+    let (name, lines) = sections.last_mut().unwrap();
+    let key = trimmed.split('=').next().unwrap_or_default().trim();
+    match (name.as_str(), key) {
+      ("[Network]", "DNS") | ("[DHCPv4]", "UseDNS") => {}
+      _ => lines.push(line.to_string()),
+    }
+  }
+  let is_dhcp = sections
+    .iter()
+    .filter(|(name, _)| name == "[Network]")
+    .flat_map(|(_, lines)| lines)
+    .any(|line| {
+      let line = line.trim().replace(' ', "");
+      ["DHCP=yes", "DHCP=ipv4", "DHCP=true"].contains(&line.as_str())
+    });
+  let insert = |lines: &mut Vec<String>, new: Vec<String>| {
+    let at = lines.iter().rposition(|v| !v.trim().is_empty()).map_or(lines.len(), |i| i + 1);
+    lines.splice(at..at, new);
+  };
+  // 追加缺少的段落, 与前文之间空一行
+  let push = |sections: &mut Vec<(String, Vec<String>)>, name: &str, new: Vec<String>| {
+    let blank = sections.last().and_then(|(_, lines)| lines.last()).is_some_and(|v| !v.trim().is_empty());
+    let head = if blank {
+      vec![String::new(), name.to_string()]
+    } else {
+      vec![name.to_string()]
+    };
+    sections.push((name.to_string(), [head, new].concat()));
+  };
+  if config.dns_mode == AddressMode::Static {
+    let dns: Vec<String> = config.dns.iter().map(|v| format!("DNS={}", v)).collect();
+    match sections.iter_mut().find(|(name, _)| name == "[Network]") {
+      Some((_, lines)) => insert(lines, dns),
+      None => push(&mut sections, "[Network]", dns),
+    }
+  }
+  if is_dhcp {
+    let use_dns = format!("UseDNS={}", config.dns_mode != AddressMode::Static);
+    match sections.iter_mut().find(|(name, _)| name == "[DHCPv4]") {
+      Some((_, lines)) => insert(lines, vec![use_dns]),
+      None => push(&mut sections, "[DHCPv4]", vec![use_dns]),
+    }
+  }
+  sections.iter().flat_map(|(_, lines)| lines).map(|line| format!("{}\n", line)).collect()
+}
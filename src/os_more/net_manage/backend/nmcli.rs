@@ -0,0 +1,67 @@
+//! NetworkManager 连接参数
+use super::{cidr, InterfaceConfig};
+use crate::os_more::net_manage::AddressMode;
+
+/// 新建以太网连接的参数
+pub fn nmcli_add_args(conn: &str, ifname: &str) -> Vec<String> {
+  [
+    "connection",
+    "add",
+    "type",
+    "ethernet",
+    "ifname",
+    ifname,
+    "con-name",
+    conn,
+    "autoconnect",
+    "yes",
+  ]
+  .iter()
+  .map(|v| v.to_string())
+  .collect()
+}
+
+/// `nmcli connection modify <连接>` 的属性参数
+pub fn nmcli_args(config: &InterfaceConfig) -> Vec<String> {
+  let mut args: Vec<String> = vec![];
+  let mut set = |key: &str, value: String| args.extend([key.to_string(), value]);
+  match config.ip_mode {
+    AddressMode::Dhcp => {
+      set("ipv4.method", "auto".into());
+      set("ipv4.addresses", String::new());
+      set("ipv4.gateway", String::new());
+    }
+    AddressMode::Static => {
+      set("ipv4.method", "manual".into());
+      set("ipv4.addresses", cidr(config).unwrap_or_default());
+      set("ipv4.gateway", config.gateway.clone().unwrap_or_default());
+    }
+    AddressMode::None => {}
+  }
+  args.extend(nmcli_dns_args(config));
+  args
+}
+
+/// 只修改 DNS 的属性参数, 地址相关属性保持不变
+pub fn nmcli_dns_args(config: &InterfaceConfig) -> Vec<String> {
+  let mut args: Vec<String> = vec![];
+  let mut set = |key: &str, value: String| args.extend([key.to_string(), value]);
+  match config.dns_mode {
+    AddressMode::Dhcp => {
+      set("ipv4.dns", String::new());
+      set("ipv4.ignore-auto-dns", "no".into());
+    }
+    AddressMode::Static => {
+      let (v6, v4): (Vec<&String>, Vec<&String>) = config.dns.iter().partition(|v| v.contains(':'));
+      let join = |v: Vec<&String>| v.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(",");
+      set("ipv4.dns", join(v4));
+      set("ipv4.ignore-auto-dns", "yes".into());
+      if !v6.is_empty() {
+        set("ipv6.dns", join(v6));
+        set("ipv6.ignore-auto-dns", "yes".into());
+      }
+    }
+    AddressMode::None => {}
+  }
+  args
+}
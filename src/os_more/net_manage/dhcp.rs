@@ -1,3 +1,4 @@
+use super::{configure_managed, sudo, AddressMode, ConfigPart};
 use e_utils::cmd::{Cmd, CmdOutput};
 
 /// 设置IP DHCP并验证状态
@@ -7,11 +8,21 @@ pub async fn set_ip_dhcp(iname: &str) -> e_utils::AnyResult<String> {
   let res = if cfg!(target_os = "windows") {
     Cmd::new("netsh").args(["interface", "ip", "set", "address", name, "dhcp"]).a_output().await?
   } else if cfg!(target_os = "macos") {
-    Cmd::new("sudo").args(["ipconfig", "set", name, "DHCP"]).a_output().await?
+    sudo().args(["ipconfig", "set", iname, "DHCP"]).a_output().await?
+  } else if let Some(res) = configure_managed(iname, ConfigPart::All, |config| {
+    config.ip_mode = AddressMode::Dhcp;
+    config.address = None;
+    config.netmask = None;
+    config.gateway = None;
+  })
+  .await?
+  {
+    crate::dp(res);
+    return Ok(format!("{iname}=IP-DHCP"));
   } else {
-    // Linux: 先停止之前的 dhclient，然后重新启动
-    let _ = Cmd::new("sudo").args(["pkill", "dhclient"]).a_output().await;
-    Cmd::new("sudo").args(["dhclient", name]).a_output().await?
+    // Linux: 未检测到网络管理器时先停止之前的 dhclient，然后重新启动
+    let _ = sudo().args(["pkill", "dhclient"]).a_output().await;
+    sudo().args(["dhclient", iname]).a_output().await?
   };
   if is_ip_dhcp(&res, name).await? {
     Ok(format!("{iname}=IP-DHCP"))
@@ -40,15 +51,20 @@ pub async fn set_dns_dhcp(adapter_name: &str) -> e_utils::AnyResult<String> {
       .a_output()
       .await?
   } else if cfg!(target_os = "macos") {
-    Cmd::new("sudo")
-      .args(["networksetup", "-setdnsservers", adapter_name, "empty"])
-      .a_output()
-      .await?
+    sudo().args(["networksetup", "-setdnsservers", adapter_name, "empty"]).a_output().await?
+  } else if let Some(res) = configure_managed(adapter_name, ConfigPart::Dns, |config| {
+    config.dns_mode = AddressMode::Dhcp;
+    config.dns.clear();
+  })
+  .await?
+  {
+    crate::dp(res);
+    return Ok(format!("{adapter_name}=DNS-DHCP"));
   } else {
-    let res = Cmd::new("sudo").args(["resolvconf", "-d", adapter_name]).a_output().await;
+    let res = sudo().args(["resolvconf", "-d", adapter_name]).a_output().await;
     if res.is_err() {
-      let _ = Cmd::new("sudo").args(["rm", "-f", "/etc/resolv.conf"]).a_output().await?;
-      Cmd::new("sudo")
+      let _ = sudo().args(["rm", "-f", "/etc/resolv.conf"]).a_output().await?;
+      sudo()
         .args(["ln", "-s", "/run/systemd/resolve/resolv.conf", "/etc/resolv.conf"])
         .a_output()
         .await?
@@ -2,8 +2,18 @@ mod r#static;
 pub use r#static::*;
 mod dhcp;
 pub use dhcp::*;
+mod address;
+pub use address::*;
+mod backend;
+pub use backend::*;
 mod ping;
 pub use ping::*;
+mod reach;
+pub use reach::*;
+mod snapshot;
+pub use snapshot::*;
+mod sntp;
+pub use sntp::*;
 mod sync_datetime;
 pub use sync_datetime::*;
 
@@ -1,21 +1,365 @@
-use e_utils::cmd::Cmd;
+//! ICMP Echo
+//!
+//! Linux 优先使用无需特权的 `SOCK_DGRAM` ICMP (受 net.ipv4.ping_group_range 限制), 不可用时回退到原始套接字;
+//! Windows 与 macOS 只能使用原始套接字, 需要以管理员/root 权限运行
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::{
+  fmt,
+  mem::MaybeUninit,
+  net::{IpAddr, SocketAddr, ToSocketAddrs as _},
+  time::{Duration, Instant},
+};
 
-/// 检查目标是否可以 ping 通
-pub async fn ping(source: &str, target: &str, count: &str, fail_count: usize) -> e_utils::AnyResult<String> {
-  let mut err = String::new();
-  for i in 0..fail_count {
-    let args = if cfg!(target_os = "windows") {
-      ["ping", "-i", source, "-n", count, "-w", "1000", target] // 修改 Windows 的源 IP 参数
+const ICMPV4_ECHO_REQUEST: u8 = 8;
+const ICMPV4_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+
+/// Ping 参数与判定阈值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PingOptions {
+  /// 发送次数
+  pub count: usize,
+  /// 单次等待回复超时
+  pub timeout: Duration,
+  /// 发送间隔
+  pub interval: Duration,
+  /// 负载字节数
+  pub payload_size: usize,
+  /// 允许的最大丢包率 (%)
+  pub max_loss: f64,
+  /// 允许的最大平均延迟 (ms)
+  pub max_avg_rtt: Option<f64>,
+}
+impl Default for PingOptions {
+  fn default() -> Self {
+    Self {
+      count: 4,
+      timeout: Duration::from_secs(1),
+      interval: Duration::from_secs(1),
+      payload_size: 32,
+      max_loss: 0.0,
+      max_avg_rtt: None,
+    }
+  }
+}
+
+/// 单次探测
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PingProbe {
+  /// 序号
+  pub seq: u16,
+  /// 相对开始的发送时间 (ms)
+  pub at: f64,
+  /// 往返延迟 (ms), 超时为 None
+  pub rtt: Option<f64>,
+}
+
+/// Ping 统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PingStats {
+  pub source: Option<IpAddr>,
+  pub target: IpAddr,
+  pub sent: usize,
+  pub received: usize,
+  /// 丢包率 (%)
+  pub loss: f64,
+  /// 延迟 (ms)
+  pub min: f64,
+  pub avg: f64,
+  pub max: f64,
+  pub mdev: f64,
+  pub probes: Vec<PingProbe>,
+}
+impl PingStats {
+  /// 由探测结果计算统计
+  pub fn from_probes(source: Option<IpAddr>, target: IpAddr, probes: Vec<PingProbe>) -> Self {
+    let rtts: Vec<f64> = probes.iter().filter_map(|p| p.rtt).collect();
+    let (sent, received) = (probes.len(), rtts.len());
+    let loss = if sent == 0 { 100.0 } else { (sent - received) as f64 * 100.0 / sent as f64 };
+    let (min, avg, max, mdev) = if rtts.is_empty() {
+      (0.0, 0.0, 0.0, 0.0)
     } else {
-      ["ping", "-c", count, "-W", "1", "-S", source, target]
+      let n = received as f64;
+      let avg = rtts.iter().sum::<f64>() / n;
+      let mdev = (rtts.iter().map(|v| v * v).sum::<f64>() / n - avg * avg).max(0.0).sqrt();
+      (
+        rtts.iter().cloned().fold(f64::MAX, f64::min),
+        avg,
+        rtts.iter().cloned().fold(0.0, f64::max),
+        mdev,
+      )
     };
-    let res = Cmd::new(&args[0]).args(&args[1..]).a_output().await?;
-    if res.status.success() && res.stdout.contains("0%") {
-      return Ok(res.stdout);
+    Self {
+      source,
+      target,
+      sent,
+      received,
+      loss,
+      min,
+      avg,
+      max,
+      mdev,
+      probes,
+    }
+  }
+  /// 按阈值判定
+  pub fn check(&self, opts: &PingOptions) -> e_utils::AnyResult<()> {
+    if self.received == 0 || self.loss > opts.max_loss {
+      return Err(format!("FAIL 丢包率 {:.1}% 超过 {:.1}%, {}", self.loss, opts.max_loss, self).into());
+    }
+    if let Some(max_avg_rtt) = opts.max_avg_rtt.filter(|v| self.avg > *v) {
+      return Err(format!("FAIL 平均延迟 {:.3}ms 超过 {:.3}ms, {}", self.avg, max_avg_rtt, self).into());
+    }
+    Ok(())
+  }
+}
+impl fmt::Display for PingStats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let source = self.source.map(|v| v.to_string()).unwrap_or_default();
+    write!(
+      f,
+      "{} -> {}: sent={} received={} loss={:.1}% rtt min/avg/max/mdev={:.3}/{:.3}/{:.3}/{:.3} ms",
+      source, self.target, self.sent, self.received, self.loss, self.min, self.avg, self.max, self.mdev
+    )
+  }
+}
+
+/// ICMP 校验和
+pub fn checksum(data: &[u8]) -> u16 {
+  let mut sum: u32 = data.chunks(2).map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]) as u32).sum();
+  while sum >> 16 != 0 {
+    sum = (sum & 0xffff) + (sum >> 16);
+  }
+  !(sum as u16)
+}
+
+/// 构造 Echo Request, ICMPv6 校验和由内核计算
+pub fn echo_request(v6: bool, id: u16, seq: u16, payload_size: usize) -> Vec<u8> {
+  let mut packet = vec![if v6 { ICMPV6_ECHO_REQUEST } else { ICMPV4_ECHO_REQUEST }, 0, 0, 0];
+  packet.extend(id.to_be_bytes());
+  packet.extend(seq.to_be_bytes());
+  packet.extend((0..payload_size).map(|i| i as u8));
+  if !v6 {
+    let sum = checksum(&packet);
+    packet[2..4].copy_from_slice(&sum.to_be_bytes());
+  }
+  packet
+}
+
+/// 解析 Echo Reply 返回 (id, seq), 原始 IPv4 套接字收到的数据包含 IP 头
+pub fn parse_echo_reply(data: &[u8], v6: bool) -> Option<(u16, u16)> {
+  let icmp = match data.first() {
+    Some(v) if !v6 && v >> 4 == 4 => data.get(((v & 0x0f) as usize * 4)..)?,
+    _ => data,
+  };
+  let reply = if v6 { ICMPV6_ECHO_REPLY } else { ICMPV4_ECHO_REPLY };
+  if icmp.len() < 8 || icmp[0] != reply {
+    return None;
+  }
+  Some((u16::from_be_bytes([icmp[4], icmp[5]]), u16::from_be_bytes([icmp[6], icmp[7]])))
+}
+
+/// 打开 ICMP 套接字, 返回是否为原始套接字
+fn open_socket(v6: bool) -> std::io::Result<(Socket, bool)> {
+  let (domain, protocol) = if v6 {
+    (Domain::IPV6, Protocol::ICMPV6)
+  } else {
+    (Domain::IPV4, Protocol::ICMPV4)
+  };
+  if cfg!(target_os = "linux") {
+    if let Ok(socket) = Socket::new(domain, Type::DGRAM, Some(protocol)) {
+      return Ok((socket, false));
+    }
+  }
+  Socket::new(domain, Type::RAW, Some(protocol)).map(|socket| (socket, true))
+}
+
+/// 解析目标地址, 优先与源地址同族
+pub fn resolve(target: &str, source: Option<IpAddr>) -> e_utils::AnyResult<IpAddr> {
+  if let Ok(ip) = target.parse::<IpAddr>() {
+    return Ok(ip);
+  }
+  let addrs: Vec<IpAddr> = (target, 0).to_socket_addrs()?.map(|v| v.ip()).collect();
+  addrs
+    .iter()
+    .find(|ip| source.map_or(ip.is_ipv4(), |s| s.is_ipv4() == ip.is_ipv4()))
+    .or(addrs.first())
+    .copied()
+    .ok_or(format!("无法解析目标地址 {}", target).into())
+}
+
+/// 同步执行 ICMP Echo
+pub fn icmp_ping(source: Option<IpAddr>, target: IpAddr, opts: &PingOptions) -> e_utils::AnyResult<PingStats> {
+  let v6 = target.is_ipv6();
+  let (socket, raw) = open_socket(v6).map_err(|e| match e.kind() {
+    std::io::ErrorKind::PermissionDenied => format!("ICMP 套接字创建失败, 需要管理员权限: {}", e),
+    _ => format!("ICMP 套接字创建失败: {}", e),
+  })?;
+  if let Some(source) = source {
+    socket
+      .bind(&SockAddr::from(SocketAddr::new(source, 0)))
+      .map_err(|e| format!("绑定源地址 {} 失败: {}", source, e))?;
+  }
+  let dest = SockAddr::from(SocketAddr::new(target, 0));
+  // DGRAM 套接字的 id 由内核改写并按套接字过滤
+  let id = std::process::id() as u16;
+  let start = Instant::now();
+  let mut buf = [MaybeUninit::<u8>::uninit(); 1500];
+  let mut probes = vec![];
+  for i in 0..opts.count {
+    let seq = i as u16;
+    let sent_at = Instant::now();
+    socket
+      .send_to(&echo_request(v6, id, seq, opts.payload_size), &dest)
+      .map_err(|e| format!("发送 ICMP 到 {} 失败: {}", target, e))?;
+    let mut rtt = None;
+    while rtt.is_none() {
+      let Some(remaining) = opts.timeout.checked_sub(sent_at.elapsed()).filter(|v| !v.is_zero()) else {
+        break;
+      };
+      socket.set_read_timeout(Some(remaining))?;
+      let len = match socket.recv_from(&mut buf) {
+        Ok((len, _)) => len,
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => break,
+        Err(e) => return Err(e.into()),
+      };
+      let data: Vec<u8> = buf[..len].iter().map(|v| unsafe { v.assume_init() }).collect();
+      match parse_echo_reply(&data, v6) {
+        Some((reply_id, reply_seq)) if reply_seq == seq && (!raw || reply_id == id) => {
+          rtt = Some(sent_at.elapsed().as_secs_f64() * 1000.0);
+        }
+        _ => {}
+      }
+    }
+    probes.push(PingProbe {
+      seq,
+      at: sent_at.duration_since(start).as_secs_f64() * 1000.0,
+      rtt,
+    });
+    if i + 1 < opts.count {
+      std::thread::sleep(opts.interval.saturating_sub(sent_at.elapsed()));
+    }
+  }
+  Ok(PingStats::from_probes(source, target, probes))
+}
+
+/// 解析源地址, 多个地址时取第一个
+pub fn parse_source(source: &str) -> e_utils::AnyResult<Option<IpAddr>> {
+  let source = source.split(',').next().unwrap_or_default().trim();
+  if source.is_empty() || source == "*" {
+    return Ok(None);
+  }
+  Ok(Some(source.parse().map_err(|_| format!("源地址无效: {}", source))?))
+}
+
+/// 网口的源地址, 没有 IPv4 时报错
+///
+/// 未绑定的套接字会走默认路由, 网口本身不通也可能 PASS
+pub fn interface_source(name: &str, ipv4: &str) -> e_utils::AnyResult<IpAddr> {
+  parse_source(ipv4)?.ok_or_else(|| format!("INTERFACE={} 没有 IPv4 地址, 无法绑定源地址", name).into())
+}
+
+/// 按阈值执行 ping, 失败时重试 fail_count 次
+pub async fn ping_with(source: &str, target: &str, opts: &PingOptions, fail_count: usize) -> e_utils::AnyResult<PingStats> {
+  let source = parse_source(source)?;
+  let target = resolve(target, source)?;
+  let mut err = String::new();
+  for i in 0..fail_count.max(1) {
+    let opts_ = opts.clone();
+    let res = tokio::task::spawn_blocking(move || icmp_ping(source, target, &opts_).map_err(|e| e.to_string())).await?;
+    match res.map_err(Into::into).and_then(|stats| stats.check(opts).map(|_| stats)) {
+      Ok(stats) => return Ok(stats),
+      Err(e) => err = e.to_string(),
     }
-    err = res.stdout;
     crate::wp(format!("重试尝试网络连接 {} 次", i + 1));
   }
-  crate::ep(format!("{}", err));
-  return Err(err.into());
+  crate::ep(&err);
+  Err(err.into())
+}
+
+/// 检查目标是否可以 ping 通
+pub async fn ping(source: &str, target: &str, count: &str, fail_count: usize) -> e_utils::AnyResult<String> {
+  let opts = PingOptions {
+    count: count.parse().map_err(|_| format!("Ping 次数无效: {}", count))?,
+    ..Default::default()
+  };
+  Ok(ping_with(source, target, &opts, fail_count).await?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::Ipv4Addr;
+
+  #[test]
+  fn test_icmp_packet() {
+    let packet = echo_request(false, 0x1234, 7, 4);
+    assert_eq!(packet.len(), 12);
+    assert_eq!(checksum(&packet), 0, "校验和覆盖后整体为 0");
+    // 原始套接字收到的回复带 20 字节 IP 头
+    let mut reply = vec![0x45u8];
+    reply.extend([0u8; 19]);
+    reply.extend([ICMPV4_ECHO_REPLY, 0, 0, 0, 0x12, 0x34, 0, 7]);
+    assert_eq!(parse_echo_reply(&reply, false), Some((0x1234, 7)));
+    assert_eq!(parse_echo_reply(&packet, false), None);
+    assert_eq!(parse_echo_reply(&[ICMPV6_ECHO_REPLY, 0, 0, 0, 0, 1, 0, 2], true), Some((1, 2)));
+  }
+
+  #[test]
+  fn test_ping_stats() {
+    let target = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let probe = |seq, rtt| PingProbe {
+      seq,
+      at: seq as f64 * 1000.0,
+      rtt,
+    };
+    let stats = PingStats::from_probes(
+      None,
+      target,
+      vec![probe(0, Some(1.0)), probe(1, None), probe(2, Some(3.0)), probe(3, Some(2.0))],
+    );
+    assert_eq!((stats.sent, stats.received, stats.loss), (4, 3, 25.0));
+    assert_eq!((stats.min, stats.avg, stats.max), (1.0, 2.0, 3.0));
+    assert!((stats.mdev - (2.0f64 / 3.0).sqrt()).abs() < 1e-9);
+
+    let mut opts = PingOptions::default();
+    assert!(stats.check(&opts).is_err());
+    opts.max_loss = 25.0;
+    assert!(stats.check(&opts).is_ok());
+    opts.max_avg_rtt = Some(1.5);
+    assert!(stats.check(&opts).unwrap_err().to_string().contains("平均延迟"));
+    // 全部丢失时不满足任何阈值
+    let lost = PingStats::from_probes(None, target, vec![probe(0, None)]);
+    assert!(lost
+      .check(&PingOptions {
+        max_loss: 100.0,
+        ..Default::default()
+      })
+      .is_err());
+    assert_eq!(parse_source("192.168.1.2,192.168.1.3").unwrap(), Some("192.168.1.2".parse().unwrap()));
+    assert!(parse_source("eth0").is_err());
+    assert_eq!(interface_source("eth0", "10.0.0.2").unwrap(), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+    assert!(interface_source("eth0", "").unwrap_err().to_string().contains("eth0"));
+  }
+
+  #[test]
+  fn test_ping_loopback() {
+    let opts = PingOptions {
+      count: 3,
+      interval: Duration::from_millis(10),
+      ..Default::default()
+    };
+    let source = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    let stats = match icmp_ping(source, IpAddr::V4(Ipv4Addr::LOCALHOST), &opts) {
+      Ok(stats) => stats,
+      // 无 ICMP 权限的环境
+      Err(e) if e.to_string().contains("ICMP") => return crate::wp(e.to_string()),
+      Err(e) => panic!("{}", e),
+    };
+    assert_eq!((stats.sent, stats.received, stats.loss), (3, 3, 0.0));
+    assert!(stats.min <= stats.avg && stats.avg <= stats.max && stats.probes[2].at > stats.probes[0].at);
+    assert!(stats.check(&opts).is_ok());
+  }
 }
@@ -0,0 +1,315 @@
+//! TCP/UDP/HTTP 连通性探测
+//!
+//! 用于屏蔽 ICMP 的目标, 参数与统计沿用 [`PingOptions`]/[`PingStats`], 可绑定网卡 IPv4 作为源地址
+use super::{parse_source, PingOptions, PingProbe, PingStats};
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::{
+  fmt,
+  net::{IpAddr, SocketAddr, ToSocketAddrs as _},
+  time::Instant,
+};
+use strum::Display;
+use tokio::{
+  io::{AsyncReadExt as _, AsyncWriteExt as _},
+  net::{TcpSocket, TcpStream, UdpSocket},
+};
+
+/// 探测类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ReachKind {
+  Tcp,
+  Udp,
+  Http,
+}
+
+/// 探测目标
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReachTarget {
+  /// TCP 连接 host:port
+  Tcp(String),
+  /// UDP 回显 host:port, 需目标原样返回数据
+  Udp(String),
+  /// HTTP GET, 要求状态码一致
+  Http { url: String, expected: u16 },
+}
+impl ReachTarget {
+  pub fn kind(&self) -> ReachKind {
+    match self {
+      ReachTarget::Tcp(_) => ReachKind::Tcp,
+      ReachTarget::Udp(_) => ReachKind::Udp,
+      ReachTarget::Http { .. } => ReachKind::Http,
+    }
+  }
+  pub fn address(&self) -> &str {
+    match self {
+      ReachTarget::Tcp(v) | ReachTarget::Udp(v) => v,
+      ReachTarget::Http { url, .. } => url,
+    }
+  }
+}
+
+/// 探测统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReachStats {
+  pub kind: ReachKind,
+  /// host:port 或 URL
+  pub address: String,
+  #[serde(flatten)]
+  pub stats: PingStats,
+  /// 最后一次的 HTTP 状态码
+  pub status: Option<u16>,
+  /// 最后一次失败原因
+  pub error: Option<String>,
+}
+impl ReachStats {
+  /// 按阈值判定
+  pub fn check(&self, opts: &PingOptions) -> AnyResult<()> {
+    self.stats.check(opts).map_err(|e| match &self.error {
+      Some(error) => format!("{}, {} {}: {}", e, self.kind, self.address, error).into(),
+      None => format!("{}, {} {}", e, self.kind, self.address).into(),
+    })
+  }
+}
+impl fmt::Display for ReachStats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} {} {}", self.kind, self.address, self.stats)?;
+    if let Some(status) = self.status {
+      write!(f, " status={}", status)?;
+    }
+    Ok(())
+  }
+}
+
+/// 解析 `http://host[:port][/path]`, 不支持 https
+pub fn parse_http_url(url: &str) -> AnyResult<(String, u16, String)> {
+  let rest = url.strip_prefix("http://").ok_or(format!("仅支持 http:// 地址: {}", url))?;
+  let (authority, path) = match rest.find('/') {
+    Some(i) => (&rest[..i], &rest[i..]),
+    None => (rest, "/"),
+  };
+  let (host, port) = match authority.rsplit_once(':') {
+    Some((host, port)) if !port.contains(']') => (host, port.parse().map_err(|_| format!("端口无效: {}", url))?),
+    _ => (authority, 80),
+  };
+  if host.is_empty() {
+    return Err(format!("地址无效: {}", url).into());
+  }
+  Ok((host.to_string(), port, path.to_string()))
+}
+
+/// 解析 host:port, 优先与源地址同族
+fn resolve_addr(host: &str, port: u16, source: Option<IpAddr>) -> AnyResult<SocketAddr> {
+  let host = host.trim_start_matches('[').trim_end_matches(']');
+  let addrs: Vec<SocketAddr> = (host, port).to_socket_addrs()?.collect();
+  addrs
+    .iter()
+    .find(|v| source.map_or(v.is_ipv4(), |s| s.is_ipv4() == v.is_ipv4()))
+    .or(addrs.first())
+    .copied()
+    .ok_or(format!("无法解析目标地址 {}", host).into())
+}
+
+fn split_host_port(address: &str) -> AnyResult<(&str, u16)> {
+  let (host, port) = address.rsplit_once(':').ok_or(format!("地址格式错误: {} (host:port)", address))?;
+  Ok((host, port.parse().map_err(|_| format!("端口无效: {}", address))?))
+}
+
+async fn tcp_connect(source: Option<IpAddr>, addr: SocketAddr) -> std::io::Result<TcpStream> {
+  let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+  if let Some(source) = source {
+    socket.bind(SocketAddr::new(source, 0))?;
+  }
+  socket.connect(addr).await
+}
+
+/// 执行一次探测, 返回 HTTP 状态码
+async fn reach_once(source: Option<IpAddr>, addr: SocketAddr, target: &ReachTarget, seq: u16, opts: &PingOptions) -> AnyResult<Option<u16>> {
+  match target {
+    ReachTarget::Tcp(_) => {
+      tcp_connect(source, addr).await?;
+      Ok(None)
+    }
+    ReachTarget::Udp(_) => {
+      let bind = source.unwrap_or(if addr.is_ipv4() { IpAddr::from([0u8; 4]) } else { IpAddr::from([0u16; 8]) });
+      let socket = UdpSocket::bind(SocketAddr::new(bind, 0)).await?;
+      socket.connect(addr).await?;
+      let mut payload = seq.to_be_bytes().to_vec();
+      payload.extend((0..opts.payload_size).map(|i| i as u8));
+      socket.send(&payload).await?;
+      let mut buf = vec![0u8; payload.len() + 1];
+      // 忽略之前超时探测的迟到回复
+      loop {
+        let len = socket.recv(&mut buf).await?;
+        if buf[..len] == payload[..] {
+          return Ok(None);
+        }
+      }
+    }
+    ReachTarget::Http { url, expected } => {
+      let (host, _, path) = parse_http_url(url)?;
+      let mut stream = tcp_connect(source, addr).await?;
+      let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: hw\r\nConnection: close\r\n\r\n", path, host);
+      stream.write_all(request.as_bytes()).await?;
+      let mut buf = vec![];
+      let mut chunk = [0u8; 512];
+      while !buf.contains(&b'\n') {
+        let len = stream.read(&mut chunk).await?;
+        if len == 0 {
+          break;
+        }
+        buf.extend_from_slice(&chunk[..len]);
+      }
+      let line = String::from_utf8_lossy(&buf);
+      let status = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|v| v.parse::<u16>().ok())
+        .ok_or(format!("HTTP 响应无效: {}", line.lines().next().unwrap_or_default()))?;
+      if status != *expected {
+        return Err(format!("HTTP 状态码 {}, 期望 {}", status, expected).into());
+      }
+      Ok(Some(status))
+    }
+  }
+}
+
+/// 按次数探测并统计延迟, 单次超时与间隔见 opts
+pub async fn reach(source: Option<IpAddr>, target: &ReachTarget, opts: &PingOptions) -> AnyResult<ReachStats> {
+  let addr = match target {
+    ReachTarget::Tcp(v) | ReachTarget::Udp(v) => {
+      let (host, port) = split_host_port(v)?;
+      resolve_addr(host, port, source)?
+    }
+    ReachTarget::Http { url, .. } => {
+      let (host, port, _) = parse_http_url(url)?;
+      resolve_addr(&host, port, source)?
+    }
+  };
+  let start = Instant::now();
+  let (mut probes, mut status, mut error) = (vec![], None, None);
+  for i in 0..opts.count {
+    let seq = i as u16;
+    let sent_at = Instant::now();
+    let rtt = match tokio::time::timeout(opts.timeout, reach_once(source, addr, target, seq, opts)).await {
+      Ok(Ok(v)) => {
+        status = v.or(status);
+        Some(sent_at.elapsed().as_secs_f64() * 1000.0)
+      }
+      Ok(Err(e)) => {
+        error = Some(e.to_string());
+        None
+      }
+      Err(_) => {
+        error = Some("超时".into());
+        None
+      }
+    };
+    probes.push(PingProbe {
+      seq,
+      at: sent_at.duration_since(start).as_secs_f64() * 1000.0,
+      rtt,
+    });
+    if i + 1 < opts.count {
+      tokio::time::sleep(opts.interval.saturating_sub(sent_at.elapsed())).await;
+    }
+  }
+  Ok(ReachStats {
+    kind: target.kind(),
+    address: target.address().to_string(),
+    stats: PingStats::from_probes(source, addr.ip(), probes),
+    status,
+    error,
+  })
+}
+
+/// 按阈值探测, 失败时重试 fail_count 次
+pub async fn reach_with(source: &str, target: &ReachTarget, opts: &PingOptions, fail_count: usize) -> AnyResult<ReachStats> {
+  let source = parse_source(source)?;
+  let mut err = String::new();
+  for i in 0..fail_count.max(1) {
+    match reach(source, target, opts).await.and_then(|stats| stats.check(opts).map(|_| stats)) {
+      Ok(stats) => return Ok(stats),
+      Err(e) => err = e.to_string(),
+    }
+    crate::wp(format!("重试尝试网络连接 {} 次", i + 1));
+  }
+  crate::ep(&err);
+  Err(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn opts(count: usize) -> PingOptions {
+    PingOptions {
+      count,
+      interval: Duration::from_millis(10),
+      timeout: Duration::from_millis(500),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_parse_http_url() {
+    assert_eq!(
+      parse_http_url("http://127.0.0.1:8080/health?x=1").unwrap(),
+      ("127.0.0.1".into(), 8080, "/health?x=1".into())
+    );
+    assert_eq!(parse_http_url("http://example.com").unwrap(), ("example.com".into(), 80, "/".into()));
+    assert_eq!(parse_http_url("http://[::1]:81/").unwrap(), ("[::1]".into(), 81, "/".into()));
+    assert!(parse_http_url("https://example.com").is_err());
+  }
+
+  #[tokio::test]
+  async fn test_reach_local() {
+    let source = "127.0.0.1";
+    let tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let tcp_addr = tcp.local_addr().unwrap();
+    tokio::spawn(async move {
+      while let Ok((mut stream, _)) = tcp.accept().await {
+        let mut buf = [0u8; 1024];
+        if let Ok(len) = stream.read(&mut buf).await {
+          let status = if buf[..len].starts_with(b"GET /health ") {
+            "204 No Content"
+          } else {
+            "404 Not Found"
+          };
+          let _ = stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).as_bytes()).await;
+        }
+      }
+    });
+    let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let udp_addr = udp.local_addr().unwrap();
+    tokio::spawn(async move {
+      let mut buf = [0u8; 1024];
+      while let Ok((len, peer)) = udp.recv_from(&mut buf).await {
+        let _ = udp.send_to(&buf[..len], peer).await;
+      }
+    });
+
+    let stats = reach_with(source, &ReachTarget::Tcp(tcp_addr.to_string()), &opts(3), 1).await.unwrap();
+    assert_eq!((stats.stats.sent, stats.stats.received), (3, 3));
+    assert_eq!(stats.stats.source, Some("127.0.0.1".parse().unwrap()));
+    let stats = reach_with(source, &ReachTarget::Udp(udp_addr.to_string()), &opts(2), 1).await.unwrap();
+    assert_eq!(stats.stats.loss, 0.0);
+    let http = |path: &str, expected| ReachTarget::Http {
+      url: format!("http://{}{}", tcp_addr, path),
+      expected,
+    };
+    let stats = reach_with(source, &http("/health", 204), &opts(2), 1).await.unwrap();
+    assert_eq!(stats.status, Some(204));
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!((json["kind"].as_str(), json["received"].as_u64()), (Some("http"), Some(2)));
+
+    let err = reach_with(source, &http("/missing", 200), &opts(1), 1).await.unwrap_err();
+    assert!(err.to_string().contains("HTTP 状态码 404"), "{}", err);
+    // 无监听的端口
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+    assert!(reach_with(source, &ReachTarget::Tcp(closed.to_string()), &opts(1), 1).await.is_err());
+  }
+}
@@ -0,0 +1,381 @@
+//! 网络配置快照, 应用与回滚
+use super::{ping_with, set_dns_dhcp, set_dns_servers, set_ip_dhcp, set_static_ip, PingOptions};
+use crate::os_more::net_interface::{Interface, InterfaceType};
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use std::{
+  net::IpAddr,
+  path::Path,
+  str::FromStr,
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// 探测重试间隔
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 地址/DNS 获取方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressMode {
+  Dhcp,
+  Static,
+  /// 无地址, 应用时跳过
+  None,
+}
+
+/// 单个网卡的配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceConfig {
+  /// 网卡名称 (friendly_name)
+  pub name: String,
+  pub ip_mode: AddressMode,
+  /// 静态 IPv4 地址
+  #[serde(default)]
+  pub address: Option<String>,
+  /// 子网掩码
+  #[serde(default)]
+  pub netmask: Option<String>,
+  #[serde(default)]
+  pub gateway: Option<String>,
+  pub dns_mode: AddressMode,
+  #[serde(default)]
+  pub dns: Vec<String>,
+}
+impl InterfaceConfig {
+  /// 由接口生成配置, 按 [`dhcp_enabled`] 判断是否为 DHCP
+  pub fn from_interface(x: &Interface) -> Self {
+    Self::with_dhcp(x, dhcp_enabled(x))
+  }
+  /// 由接口生成配置, dhcp 为地址是否由 DHCP 获取
+  pub fn with_dhcp(x: &Interface, dhcp: bool) -> Self {
+    let ipv4 = x.ipv4.first();
+    let ip_mode = match ipv4 {
+      Some(_) if dhcp => AddressMode::Dhcp,
+      Some(_) => AddressMode::Static,
+      None => AddressMode::None,
+    };
+    let dns: Vec<String> = x.dns_servers.iter().map(|v| v.to_string()).collect();
+    Self {
+      name: x.friendly_name.clone(),
+      ip_mode,
+      address: ipv4.map(|v| v.addr.to_string()),
+      netmask: ipv4.map(|v| v.netmask.to_string()),
+      gateway: x.gateway.as_ref().map(|v| v.ip_addr.to_string()),
+      dns_mode: match ip_mode {
+        AddressMode::Static if !dns.is_empty() => AddressMode::Static,
+        AddressMode::None => AddressMode::None,
+        _ => AddressMode::Dhcp,
+      },
+      dns,
+    }
+  }
+  /// 应用配置
+  pub async fn apply(&self) -> AnyResult<Vec<String>> {
+    let mut res = vec![];
+    match self.ip_mode {
+      AddressMode::Dhcp => res.push(set_ip_dhcp(&self.name).await?),
+      AddressMode::Static => {
+        let address = self.address.as_deref().ok_or(format!("{} 静态配置缺少 address", self.name))?;
+        let netmask = self.netmask.as_deref().ok_or(format!("{} 静态配置缺少 netmask", self.name))?;
+        res.push(set_static_ip(&self.name, address, netmask, self.gateway.as_deref()).await?);
+      }
+      AddressMode::None => {}
+    }
+    match self.dns_mode {
+      AddressMode::Static => {
+        let servers = self
+          .dns
+          .iter()
+          .map(|v| v.parse::<IpAddr>().map_err(|_| format!("{} 无效DNS地址: {}", self.name, v)))
+          .collect::<Result<Vec<_>, _>>()?;
+        res.push(set_dns_servers(&self.name, &servers).await?)
+      }
+      AddressMode::Dhcp => res.push(set_dns_dhcp(&self.name).await?),
+      AddressMode::None => {}
+    }
+    Ok(res)
+  }
+}
+
+/// IPv4 地址是否由 DHCP 获取
+///
+/// Windows 读取适配器的 DhcpEnabled 标志, Linux 查找记录了当前地址的 DHCP 租约, 其他系统视为静态
+pub fn dhcp_enabled(x: &Interface) -> bool {
+  if cfg!(target_os = "windows") {
+    // IP_ADAPTER_DHCP_ENABLED
+    x.flags & 0x4 != 0
+  } else if cfg!(target_os = "linux") {
+    dhcp_lease(Path::new("/"), x)
+  } else {
+    false
+  }
+}
+
+/// DHCP 租约目录: systemd-networkd (按 ifindex 命名), NetworkManager, dhclient
+const LEASE_DIRS: [&str; 4] = ["run/systemd/netif/leases", "var/lib/NetworkManager", "var/lib/dhcp", "var/lib/dhclient"];
+
+/// root 下是否有该网卡当前 IPv4 地址的租约
+///
+/// 只认包含当前地址的租约, 改为静态地址后残留的旧租约不会误判
+fn dhcp_lease(root: &Path, x: &Interface) -> bool {
+  let Some(addr) = x.ipv4.first().map(|v| v.addr.to_string()) else {
+    return false;
+  };
+  let iface = format!("interface \"{}\"", x.name);
+  LEASE_DIRS
+    .iter()
+    .flat_map(|dir| std::fs::read_dir(root.join(dir)).into_iter().flatten().flatten())
+    .any(|entry| {
+      let file = entry.file_name().to_string_lossy().to_string();
+      let Ok(content) = std::fs::read_to_string(entry.path()) else {
+        return false;
+      };
+      let own = file == x.index.to_string() || file.contains(&x.name) || content.contains(&iface);
+      own
+        && content.lines().any(|line| {
+          let line = line.trim();
+          line == format!("ADDRESS={}", addr) || line == format!("fixed-address {};", addr)
+        })
+    })
+}
+
+/// 网络配置快照 / 期望状态文档
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetSnapshot {
+  /// 创建时间 (Unix 秒)
+  #[serde(default)]
+  pub created: u64,
+  pub interfaces: Vec<InterfaceConfig>,
+}
+impl NetSnapshot {
+  /// 由接口生成快照, 忽略环回接口
+  pub fn from_interfaces(interfaces: &[Interface]) -> Self {
+    Self {
+      created: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+      interfaces: interfaces
+        .iter()
+        .filter(|x| x.if_type != InterfaceType::Loopback)
+        .map(InterfaceConfig::from_interface)
+        .collect(),
+    }
+  }
+  pub fn load(path: &str) -> AnyResult<Self> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+  }
+  pub fn save(&self, path: &str) -> AnyResult<()> {
+    Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+  }
+  /// 快照中与期望状态同名的网卡
+  pub fn subset(&self, desired: &NetSnapshot) -> NetSnapshot {
+    NetSnapshot {
+      created: self.created,
+      interfaces: self
+        .interfaces
+        .iter()
+        .filter(|x| desired.interfaces.iter().any(|d| d.name == x.name))
+        .cloned()
+        .collect(),
+    }
+  }
+  /// 依次应用
+  pub async fn apply(&self) -> AnyResult<Vec<String>> {
+    let mut res = vec![];
+    for config in &self.interfaces {
+      res.extend(config.apply().await?);
+    }
+    Ok(res)
+  }
+}
+
+/// 应用后的连通性探测
+#[derive(Debug, Clone, PartialEq)]
+pub enum Probe {
+  /// ping 配置中的网关
+  Gateway,
+  /// ping 指定目标
+  Ping(String),
+  /// TCP 连接 host:port
+  Tcp(String),
+}
+impl FromStr for Probe {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.split_once(':') {
+      _ if s == "gateway" => Ok(Probe::Gateway),
+      Some(("ping", target)) if !target.is_empty() => Ok(Probe::Ping(target.into())),
+      Some(("tcp", addr)) if addr.contains(':') => Ok(Probe::Tcp(addr.into())),
+      _ => Err(format!("探测格式错误: {} (gateway | ping:<host> | tcp:<host>:<port>)", s)),
+    }
+  }
+}
+impl Probe {
+  /// 执行一次探测
+  pub async fn run(&self, desired: &NetSnapshot) -> AnyResult<()> {
+    let opts = PingOptions {
+      count: 1,
+      ..Default::default()
+    };
+    match self {
+      Probe::Gateway => {
+        let gateways: Vec<&str> = desired.interfaces.iter().filter_map(|x| x.gateway.as_deref()).collect();
+        if gateways.is_empty() {
+          return Err("期望状态中没有网关可探测".into());
+        }
+        for gateway in gateways {
+          ping_with("*", gateway, &opts, 1).await?;
+        }
+        Ok(())
+      }
+      Probe::Ping(target) => ping_with("*", target, &opts, 1).await.map(|_| ()),
+      Probe::Tcp(addr) => match tokio::time::timeout(Duration::from_secs(2), tokio::net::TcpStream::connect(addr.as_str())).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(format!("TCP {} 连接失败: {}", addr, e).into()),
+        Err(_) => Err(format!("TCP {} 连接超时", addr).into()),
+      },
+    }
+  }
+  /// 在超时内重复探测直到成功
+  pub async fn wait(&self, desired: &NetSnapshot, timeout: Duration) -> AnyResult<()> {
+    let start = Instant::now();
+    loop {
+      match self.run(desired).await {
+        Ok(()) => return Ok(()),
+        Err(e) if start.elapsed() >= timeout => return Err(e),
+        Err(e) => crate::wp(format!("探测失败, 重试: {}", e)),
+      }
+      tokio::time::sleep(PROBE_INTERVAL).await;
+    }
+  }
+}
+
+/// 应用期望状态, 探测失败或应用出错时回滚到快照
+pub async fn apply_with_rollback(desired: &NetSnapshot, snapshot: &NetSnapshot, probe: &Probe, timeout: Duration) -> AnyResult<Vec<String>> {
+  let err = match desired.apply().await {
+    Ok(res) => match probe.wait(desired, timeout).await {
+      Ok(()) => return Ok(res),
+      Err(e) => format!("应用后探测失败: {}", e),
+    },
+    Err(e) => format!("应用失败: {}", e),
+  };
+  crate::wp(format!("{}, 回滚网络配置", err));
+  match snapshot.subset(desired).apply().await {
+    Ok(_) => Err(format!("{}, 已回滚", err).into()),
+    Err(e) => Err(format!("{}, 回滚失败: {}", err, e).into()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::sysfs_tree;
+  use crate::os_more::net_interface::{Gateway, InterfaceStatus, Ipv4Net, MacAddr};
+  use std::net::Ipv4Addr;
+
+  fn iface(name: &str, if_type: InterfaceType, gateway: bool) -> Interface {
+    Interface {
+      index: 0,
+      name: name.into(),
+      friendly_name: name.into(),
+      description: String::new(),
+      if_type,
+      mac_addr: MacAddr::zero(),
+      ipv4: vec![Ipv4Net::new(Ipv4Addr::new(192, 168, 1, 20), 24)],
+      ipv6: vec![],
+      flags: 0,
+      transmit_speed: 0,
+      receive_speed: 0,
+      gateway: gateway.then(|| Gateway {
+        mac_addr: MacAddr::zero(),
+        ip_addr: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+      }),
+      oper_status: InterfaceStatus::Up,
+      dns_servers: vec![IpAddr::V4(Ipv4Addr::new(223, 5, 5, 5))],
+      stats: None,
+      topology: Default::default(),
+    }
+  }
+
+  #[test]
+  fn test_snapshot() {
+    assert_eq!(NetSnapshot::from_interfaces(&[iface("lo", InterfaceType::Loopback, false)]).interfaces, []);
+    // 有网关但不是 DHCP 获取的地址仍为静态
+    let snapshot = NetSnapshot {
+      created: 0,
+      interfaces: vec![
+        InterfaceConfig::with_dhcp(&iface("enp3s0", InterfaceType::Ethernet, false), true),
+        InterfaceConfig::with_dhcp(&iface("enp4s0", InterfaceType::Ethernet, true), false),
+        InterfaceConfig::with_dhcp(
+          &Interface {
+            ipv4: vec![],
+            ..iface("enp5s0", InterfaceType::Ethernet, false)
+          },
+          true,
+        ),
+      ],
+    };
+    assert_eq!(snapshot.interfaces[2].ip_mode, AddressMode::None);
+    assert_eq!(snapshot.interfaces[2].dns_mode, AddressMode::None);
+    let (dhcp, fixed) = (&snapshot.interfaces[0], &snapshot.interfaces[1]);
+    assert_eq!((dhcp.ip_mode, dhcp.dns_mode), (AddressMode::Dhcp, AddressMode::Dhcp));
+    assert_eq!((fixed.ip_mode, fixed.dns_mode), (AddressMode::Static, AddressMode::Static));
+    assert_eq!(fixed.netmask.as_deref(), Some("255.255.255.0"));
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert!(json.contains(r#""ip_mode":"dhcp""#));
+    assert_eq!(serde_json::from_str::<NetSnapshot>(&json).unwrap(), snapshot);
+
+    // 期望状态文档可省略可选字段
+    let desired: NetSnapshot = serde_json::from_str(r#"{"interfaces":[{"name":"enp4s0","ip_mode":"dhcp","dns_mode":"dhcp"}]}"#).unwrap();
+    assert_eq!(snapshot.subset(&desired).interfaces, std::slice::from_ref(fixed));
+  }
+
+  #[test]
+  fn test_dhcp_lease() {
+    let root = sysfs_tree(
+      "lease",
+      &[
+        (
+          "run/systemd/netif/leases/2",
+          "# This is private data. Do not parse.\nADDRESS=192.168.1.20\nNETMASK=255.255.255.0\n",
+        ),
+        (
+          "var/lib/dhcp/dhclient.leases",
+          "lease {\n  interface \"enp4s0\";\n  fixed-address 10.0.0.9;\n}\n",
+        ),
+      ],
+    );
+    let x = Interface {
+      index: 2,
+      ..iface("enp3s0", InterfaceType::Ethernet, true)
+    };
+    assert!(dhcp_lease(&root, &x));
+    // 其他网卡的租约与旧地址的租约不算
+    assert!(!dhcp_lease(&root, &Interface { index: 3, ..x.clone() }));
+    let y = Interface {
+      ipv4: vec![Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 9), 8)],
+      ..iface("enp4s0", InterfaceType::Ethernet, true)
+    };
+    assert!(dhcp_lease(&root, &y));
+    assert!(!dhcp_lease(&root, &Interface { ipv4: vec![], ..y.clone() }));
+    std::fs::remove_dir_all(root).unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_probe() {
+    assert_eq!("gateway".parse::<Probe>(), Ok(Probe::Gateway));
+    assert_eq!("ping:8.8.8.8".parse::<Probe>(), Ok(Probe::Ping("8.8.8.8".into())));
+    assert!("tcp:8.8.8.8".parse::<Probe>().is_err());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let desired = NetSnapshot {
+      created: 0,
+      interfaces: vec![],
+    };
+    let probe: Probe = format!("tcp:{}", addr).parse().unwrap();
+    assert!(probe.wait(&desired, Duration::ZERO).await.is_ok());
+    drop(listener);
+    assert!(probe.wait(&desired, Duration::ZERO).await.is_err());
+    assert!(Probe::Gateway.run(&desired).await.is_err());
+  }
+}
@@ -0,0 +1,281 @@
+//! SNTPv4 客户端 (RFC 4330)
+use serde::{Deserialize, Serialize};
+use std::{
+  fmt,
+  net::{SocketAddr, ToSocketAddrs as _, UdpSocket},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// NTP 端口
+pub const NTP_PORT: u16 = 123;
+/// 1900-01-01 到 1970-01-01 的秒数
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+/// 包长度
+const PACKET_LEN: usize = 48;
+const MODE_CLIENT: u8 = 3;
+const MODE_SERVER: u8 = 4;
+
+/// 系统时间转 NTP 64 位时间戳
+pub fn to_ntp(time: SystemTime) -> u64 {
+  let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64() + NTP_UNIX_OFFSET;
+  ((secs.trunc() as u64) << 32) | ((secs.fract() * 4294967296.0) as u64)
+}
+/// NTP 64 位时间戳转 Unix 秒
+pub fn from_ntp(ts: u64) -> f64 {
+  (ts >> 32) as f64 + (ts & 0xffff_ffff) as f64 / 4294967296.0 - NTP_UNIX_OFFSET
+}
+fn unix_secs(time: SystemTime) -> f64 {
+  time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+}
+
+/// NTP 报文
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SntpPacket {
+  pub leap: u8,
+  pub version: u8,
+  pub mode: u8,
+  pub stratum: u8,
+  pub poll: i8,
+  pub precision: i8,
+  pub root_delay: u32,
+  pub root_dispersion: u32,
+  pub ref_id: [u8; 4],
+  pub ref_ts: u64,
+  pub orig_ts: u64,
+  pub recv_ts: u64,
+  pub tx_ts: u64,
+}
+impl SntpPacket {
+  /// 客户端请求
+  pub fn request(tx_ts: u64) -> Self {
+    Self {
+      version: 4,
+      mode: MODE_CLIENT,
+      tx_ts,
+      ..Default::default()
+    }
+  }
+  pub fn to_bytes(&self) -> [u8; PACKET_LEN] {
+    let mut buf = [0u8; PACKET_LEN];
+    buf[0] = (self.leap << 6) | ((self.version & 0x7) << 3) | (self.mode & 0x7);
+    buf[1] = self.stratum;
+    buf[2] = self.poll as u8;
+    buf[3] = self.precision as u8;
+    buf[4..8].copy_from_slice(&self.root_delay.to_be_bytes());
+    buf[8..12].copy_from_slice(&self.root_dispersion.to_be_bytes());
+    buf[12..16].copy_from_slice(&self.ref_id);
+    for (i, ts) in [self.ref_ts, self.orig_ts, self.recv_ts, self.tx_ts].iter().enumerate() {
+      buf[16 + i * 8..24 + i * 8].copy_from_slice(&ts.to_be_bytes());
+    }
+    buf
+  }
+  pub fn parse(buf: &[u8]) -> Option<Self> {
+    if buf.len() < PACKET_LEN {
+      return None;
+    }
+    let u32_at = |i: usize| u32::from_be_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+    let u64_at = |i: usize| ((u32_at(i) as u64) << 32) | u32_at(i + 4) as u64;
+    Some(Self {
+      leap: buf[0] >> 6,
+      version: (buf[0] >> 3) & 0x7,
+      mode: buf[0] & 0x7,
+      stratum: buf[1],
+      poll: buf[2] as i8,
+      precision: buf[3] as i8,
+      root_delay: u32_at(4),
+      root_dispersion: u32_at(8),
+      ref_id: [buf[12], buf[13], buf[14], buf[15]],
+      ref_ts: u64_at(16),
+      orig_ts: u64_at(24),
+      recv_ts: u64_at(32),
+      tx_ts: u64_at(40),
+    })
+  }
+  /// 参考源标识, stratum 1 为 ASCII 代码, 其他为上游 IPv4
+  pub fn ref_id_string(&self) -> String {
+    if self.stratum <= 1 {
+      String::from_utf8_lossy(&self.ref_id).trim_end_matches('\0').to_string()
+    } else {
+      std::net::Ipv4Addr::from(self.ref_id).to_string()
+    }
+  }
+}
+
+/// SNTP 查询结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SntpResult {
+  pub server: SocketAddr,
+  pub stratum: u8,
+  pub leap: u8,
+  pub ref_id: String,
+  /// 本地时钟相对服务器的偏差 (ms), 正数表示本地偏慢
+  pub offset_ms: f64,
+  /// 往返延迟 (ms)
+  pub delay_ms: f64,
+  /// 服务器时间 (Unix 秒)
+  pub server_time: f64,
+}
+impl SntpResult {
+  /// 由四个时间戳计算 (Unix 秒): t1 发送, t2 服务器接收, t3 服务器发送, t4 接收
+  pub fn from_timestamps(server: SocketAddr, reply: &SntpPacket, t1: f64, t4: f64) -> Self {
+    let (t2, t3) = (from_ntp(reply.recv_ts), from_ntp(reply.tx_ts));
+    Self {
+      server,
+      stratum: reply.stratum,
+      leap: reply.leap,
+      ref_id: reply.ref_id_string(),
+      offset_ms: ((t2 - t1) + (t3 - t4)) / 2.0 * 1000.0,
+      delay_ms: ((t4 - t1) - (t3 - t2)).max(0.0) * 1000.0,
+      server_time: t3,
+    }
+  }
+}
+impl fmt::Display for SntpResult {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "SERVER={}, STRATUM={}, REF={}, OFFSET={:.3}ms, DELAY={:.3}ms",
+      self.server, self.stratum, self.ref_id, self.offset_ms, self.delay_ms
+    )
+  }
+}
+
+/// 解析服务器地址, 未指定端口时使用 123
+fn resolve(server: &str) -> e_utils::AnyResult<SocketAddr> {
+  let addrs = match server.to_socket_addrs() {
+    Ok(addrs) => addrs.collect::<Vec<_>>(),
+    Err(_) => (server, NTP_PORT).to_socket_addrs()?.collect(),
+  };
+  addrs
+    .iter()
+    .find(|v| v.is_ipv4())
+    .or(addrs.first())
+    .copied()
+    .ok_or(format!("无法解析时间服务器 {}", server).into())
+}
+
+/// 同步查询时间服务器
+pub fn sntp_query(server: &str, timeout: Duration) -> e_utils::AnyResult<SntpResult> {
+  let addr = resolve(server)?;
+  let socket = UdpSocket::bind(if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" })?;
+  socket.set_read_timeout(Some(timeout))?;
+  socket.connect(addr)?;
+  let t1 = SystemTime::now();
+  let tx_ts = to_ntp(t1);
+  socket.send(&SntpPacket::request(tx_ts).to_bytes())?;
+  let mut buf = [0u8; 512];
+  let len = socket.recv(&mut buf).map_err(|e| format!("时间服务器 {} 无响应: {}", addr, e))?;
+  let t4 = SystemTime::now();
+  let reply = SntpPacket::parse(&buf[..len]).ok_or(format!("时间服务器 {} 响应长度错误: {}", addr, len))?;
+  if reply.mode != MODE_SERVER || reply.orig_ts != tx_ts {
+    return Err(format!("时间服务器 {} 响应不匹配", addr).into());
+  }
+  if reply.stratum == 0 {
+    return Err(format!("时间服务器 {} 拒绝服务 KoD={}", addr, reply.ref_id_string()).into());
+  }
+  if reply.leap == 3 || reply.tx_ts == 0 {
+    return Err(format!("时间服务器 {} 未同步", addr).into());
+  }
+  Ok(SntpResult::from_timestamps(addr, &reply, unix_secs(t1), unix_secs(t4)))
+}
+
+/// 异步查询时间服务器
+pub async fn sntp(server: &str) -> e_utils::AnyResult<SntpResult> {
+  let server = server.to_string();
+  tokio::task::spawn_blocking(move || sntp_query(&server, Duration::from_secs(3)).map_err(|e| e.to_string()))
+    .await?
+    .map_err(Into::into)
+}
+
+/// 按偏差调整系统时钟
+#[cfg(target_os = "linux")]
+pub fn step_clock(offset_ms: f64) -> e_utils::AnyResult<()> {
+  let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+  if unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, &mut ts) } != 0 {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  let nanos = ts.tv_sec as i128 * 1_000_000_000 + ts.tv_nsec as i128 + (offset_ms * 1_000_000.0) as i128;
+  let ts = libc::timespec {
+    tv_sec: nanos.div_euclid(1_000_000_000) as libc::time_t,
+    tv_nsec: nanos.rem_euclid(1_000_000_000) as libc::c_long,
+  };
+  if unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &ts) } != 0 {
+    return Err(format!("设置系统时间失败: {}", std::io::Error::last_os_error()).into());
+  }
+  Ok(())
+}
+#[cfg(not(target_os = "linux"))]
+pub fn step_clock(_offset_ms: f64) -> e_utils::AnyResult<()> {
+  Err("不支持的系统".into())
+}
+
+/// 检查本地时钟偏差
+pub async fn check_time(server: &str, max_offset_ms: f64) -> e_utils::AnyResult<SntpResult> {
+  let res = sntp(server).await?;
+  if res.offset_ms.abs() > max_offset_ms {
+    return Err(format!("FAIL 时钟偏差超过 {}ms, {}", max_offset_ms, res).into());
+  }
+  Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// 本地 NTP 服务, 时间比本机快 offset 秒
+  fn serve(offset: f64, stratum: u8) -> SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    std::thread::spawn(move || {
+      let mut buf = [0u8; PACKET_LEN];
+      let (_, peer) = socket.recv_from(&mut buf).unwrap();
+      let req = SntpPacket::parse(&buf).unwrap();
+      let shift = Duration::from_secs_f64(offset.abs());
+      let now = || {
+        to_ntp(if offset >= 0.0 {
+          SystemTime::now() + shift
+        } else {
+          SystemTime::now() - shift
+        })
+      };
+      let reply = SntpPacket {
+        version: 4,
+        mode: MODE_SERVER,
+        stratum,
+        ref_id: *b"GPS\0",
+        orig_ts: req.tx_ts,
+        recv_ts: now(),
+        tx_ts: now(),
+        ..Default::default()
+      };
+      socket.send_to(&reply.to_bytes(), peer).unwrap();
+    });
+    addr
+  }
+
+  #[test]
+  fn test_sntp_packet() {
+    let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_250);
+    assert!((from_ntp(to_ntp(time)) - 1_700_000_000.25).abs() < 1e-6);
+    let packet = SntpPacket::request(to_ntp(time));
+    let bytes = packet.to_bytes();
+    assert_eq!(bytes[0], 0x23);
+    assert_eq!(SntpPacket::parse(&bytes), Some(packet));
+    assert_eq!(SntpPacket::parse(&bytes[..40]), None);
+  }
+
+  #[test]
+  fn test_sntp_local_server() {
+    let res = sntp_query(&serve(5.0, 1).to_string(), Duration::from_secs(2)).unwrap();
+    assert!((res.offset_ms - 5000.0).abs() < 100.0, "{}", res);
+    assert!(res.delay_ms >= 0.0 && res.delay_ms < 100.0);
+    assert_eq!((res.stratum, res.ref_id.as_str()), (1, "GPS"));
+
+    let res = sntp_query(&serve(-0.5, 2).to_string(), Duration::from_secs(2)).unwrap();
+    assert!((res.offset_ms + 500.0).abs() < 100.0, "{}", res);
+    assert_eq!(res.ref_id, "71.80.83.0");
+
+    let err = sntp_query(&serve(0.0, 0).to_string(), Duration::from_secs(2)).unwrap_err();
+    assert!(err.to_string().contains("KoD"));
+  }
+}
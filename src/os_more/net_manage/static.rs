@@ -1,7 +1,14 @@
+use super::{configure_managed, AddressMode, ConfigPart};
+use crate::os_more::net_interface::{get_interfaces, Interface, Ipv4Net};
 use e_utils::{cmd::Cmd, AnyResult};
+use serde::{Deserialize, Serialize};
+use std::{
+  net::{IpAddr, Ipv4Addr},
+  time::{Duration, Instant},
+};
 
 /// 执行命令并检查输出
-async fn exec_cmd(cmd: Cmd) -> AnyResult<String> {
+pub(crate) async fn exec_cmd(cmd: Cmd) -> AnyResult<String> {
   let output = cmd.a_output().await?;
   if output.status.success() {
     Ok(output.stdout)
@@ -10,84 +17,337 @@ async fn exec_cmd(cmd: Cmd) -> AnyResult<String> {
   }
 }
 
-/// 验证IP配置
-async fn verify_ip_config(_adapter_name: &str, _ip: &str, _gateway: Option<&str>) -> AnyResult<bool> {
-  Ok(true)
+/// 以管理员权限执行的命令, 后续以 `.args([程序, 参数..])` 传入
+///
+/// 已是 root 时通过 env 执行, 不依赖 sudo
+pub(crate) fn sudo() -> Cmd {
+  #[cfg(unix)]
+  let launcher = if unsafe { libc::geteuid() } == 0 { "env" } else { "sudo" };
+  #[cfg(not(unix))]
+  let launcher = "sudo";
+  crate::common::cmd::exe(launcher)
 }
 
-/// 验证DNS配置
-async fn verify_dns_config(_adapter_name: &str, _primary_dns: &str, _secondary_dns: Option<&str>) -> AnyResult<bool> {
-  Ok(true)
+/// 验证轮询超时
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+/// 验证轮询间隔
+pub const VERIFY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 期望与实际配置的差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+  pub field: String,
+  pub expected: String,
+  pub actual: String,
+}
+impl ConfigDiff {
+  pub(crate) fn new(field: &str, expected: impl ToString, actual: impl ToString) -> Self {
+    Self {
+      field: field.into(),
+      expected: expected.to_string(),
+      actual: actual.to_string(),
+    }
+  }
+}
+
+/// 子网掩码或前缀长度转前缀长度
+pub(crate) fn parse_prefix(netmask: &str) -> Option<u8> {
+  let netmask = netmask.trim_start_matches('/');
+  match netmask.parse::<u8>() {
+    Ok(prefix) => Some(prefix),
+    Err(_) => format!("{}/{}", Ipv4Addr::UNSPECIFIED, netmask).parse::<Ipv4Net>().ok().map(|v| v.prefix_len),
+  }
+}
+
+/// 比较接口的 IP/前缀/网关
+pub fn diff_ip_config(iface: &Interface, ip: &str, netmask: Option<&str>, gateway: Option<&str>) -> Vec<ConfigDiff> {
+  let mut diffs = vec![];
+  let actual: Vec<(IpAddr, u8)> = iface
+    .ipv4
+    .iter()
+    .map(|v| (IpAddr::V4(v.addr), v.prefix_len))
+    .chain(iface.ipv6.iter().map(|v| (IpAddr::V6(v.addr), v.prefix_len)))
+    .collect();
+  let addrs = || actual.iter().map(|(ip, prefix)| format!("{}/{}", ip, prefix)).collect::<Vec<_>>().join(",");
+  match (ip.parse::<IpAddr>().ok(), actual.iter().find(|(v, _)| Some(*v) == ip.parse().ok())) {
+    (None, _) => diffs.push(ConfigDiff::new("ip", ip, "无效地址")),
+    (_, None) => diffs.push(ConfigDiff::new("ip", ip, addrs())),
+    (_, Some((_, prefix))) => {
+      if let Some(expected) = netmask.and_then(parse_prefix).filter(|v| v != prefix) {
+        diffs.push(ConfigDiff::new("prefix", format!("{}/{}", ip, expected), addrs()));
+      }
+    }
+  }
+  if let Some(gateway) = gateway.filter(|v| !v.is_empty()) {
+    let actual = iface.gateway.as_ref().map(|v| v.ip_addr.to_string()).unwrap_or_default();
+    if gateway.parse::<IpAddr>().ok().map(|v| v.to_string()) != Some(actual.clone()) {
+      diffs.push(ConfigDiff::new("gateway", gateway, actual));
+    }
+  }
+  diffs
+}
+
+/// 比较接口的 DNS, 期望的服务器需按顺序位于列表开头
+pub fn diff_dns_config(iface: &Interface, primary_dns: &str, secondary_dns: Option<&str>) -> Vec<ConfigDiff> {
+  let expected: Vec<String> = [Some(primary_dns), secondary_dns]
+    .into_iter()
+    .flatten()
+    .filter(|v| !v.is_empty())
+    .map(|v| v.parse::<IpAddr>().map(|v| v.to_string()).unwrap_or_else(|_| v.to_string()))
+    .collect();
+  diff_dns_list(iface, &expected)
+}
+
+/// 比较接口的 DNS 列表, 支持 IPv4/IPv6
+pub fn diff_dns_servers(iface: &Interface, servers: &[IpAddr]) -> Vec<ConfigDiff> {
+  diff_dns_list(iface, &servers.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+}
+
+fn diff_dns_list(iface: &Interface, expected: &[String]) -> Vec<ConfigDiff> {
+  let actual: Vec<String> = iface.dns_servers.iter().map(|v| v.to_string()).collect();
+  if actual.starts_with(expected) {
+    vec![]
+  } else {
+    vec![ConfigDiff::new("dns", expected.join(","), actual.join(","))]
+  }
+}
+
+/// 轮询接口直到配置一致或超时, 返回最后一次的差异
+pub async fn poll_config<L, C>(adapter_name: &str, timeout: Duration, load: L, check: C) -> Vec<ConfigDiff>
+where
+  L: Fn() -> Vec<Interface>,
+  C: Fn(&Interface) -> Vec<ConfigDiff>,
+{
+  let start = Instant::now();
+  loop {
+    let diffs = match load().iter().find(|v| v.friendly_name == adapter_name || v.name == adapter_name) {
+      Some(iface) => check(iface),
+      None => vec![ConfigDiff::new("interface", adapter_name, "未找到")],
+    };
+    if diffs.is_empty() || start.elapsed() >= timeout {
+      return diffs;
+    }
+    tokio::time::sleep(VERIFY_INTERVAL).await;
+  }
+}
+
+/// 验证IP配置, 一致时返回空列表
+pub async fn verify_ip_config(adapter_name: &str, ip: &str, netmask: Option<&str>, gateway: Option<&str>) -> AnyResult<Vec<ConfigDiff>> {
+  Ok(
+    poll_config(adapter_name, VERIFY_TIMEOUT, get_interfaces, |iface| {
+      diff_ip_config(iface, ip, netmask, gateway)
+    })
+    .await,
+  )
+}
+
+/// 验证DNS配置, 一致时返回空列表
+pub async fn verify_dns_config(adapter_name: &str, primary_dns: &str, secondary_dns: Option<&str>) -> AnyResult<Vec<ConfigDiff>> {
+  Ok(
+    poll_config(adapter_name, VERIFY_TIMEOUT, get_interfaces, |iface| {
+      diff_dns_config(iface, primary_dns, secondary_dns)
+    })
+    .await,
+  )
 }
 
 /// 设置静态IP
 pub async fn set_static_ip(adapter_name: &str, ip: &str, netmask: &str, gateway: Option<&str>) -> AnyResult<String> {
-  let name = &format!("\"{}\"", adapter_name);
+  // 只有 netsh 需要引号, 其他命令直接传参
+  let name = &if cfg!(target_os = "windows") {
+    format!("\"{}\"", adapter_name)
+  } else {
+    adapter_name.to_string()
+  };
+  // 校验输入, 掩码与前缀长度均可
+  let net: Ipv4Net = format!("{}/{}", ip, netmask).parse()?;
+  if let Some(gw) = gateway {
+    gw.parse::<Ipv4Addr>().map_err(|_| format!("无效网关地址: {}", gw))?;
+  }
+  let mask = &net.netmask.to_string();
 
   let res = match () {
     _ if cfg!(target_os = "windows") => {
-      exec_cmd(Cmd::new("netsh").args(["interface", "ip", "set", "address", name, "static", ip, netmask, gateway.unwrap_or_default()])).await?
+      exec_cmd(Cmd::new("netsh").args(["interface", "ip", "set", "address", name, "static", ip, mask, gateway.unwrap_or_default()])).await?
     }
     _ if cfg!(target_os = "macos") => {
-      exec_cmd(Cmd::new("sudo").args(["ifconfig", name, ip, "netmask", netmask])).await?;
+      exec_cmd(sudo().args(["ifconfig", name, ip, "netmask", mask])).await?;
       if let Some(gw) = gateway {
-        exec_cmd(Cmd::new("sudo").args(["route", "add", "default", gw])).await?
+        exec_cmd(sudo().args(["route", "add", "default", gw])).await?
       } else {
         String::new()
       }
     }
     _ => {
-      exec_cmd(Cmd::new("sudo").args(["ip", "addr", "add", &format!("{}/{}", ip, netmask), "dev", name])).await?;
-      if let Some(gw) = gateway {
-        exec_cmd(Cmd::new("sudo").args(["ip", "route", "add", "default", "via", gw])).await?
-      } else {
-        String::new()
+      let managed = configure_managed(adapter_name, ConfigPart::All, |config| {
+        config.ip_mode = AddressMode::Static;
+        config.address = Some(ip.into());
+        config.netmask = Some(netmask.into());
+        config.gateway = gateway.map(String::from);
+      })
+      .await?;
+      match managed {
+        Some(res) => res,
+        None => {
+          exec_cmd(sudo().args(["ip", "addr", "add", &net.to_string(), "dev", name])).await?;
+          if let Some(gw) = gateway {
+            exec_cmd(sudo().args(["ip", "route", "replace", "default", "via", gw, "dev", name])).await?
+          } else {
+            String::new()
+          }
+        }
       }
     }
   };
   crate::dp(&res);
   // 验证配置是否生效
-  if res.is_empty() && verify_ip_config(adapter_name, ip, gateway).await? {
+  let diffs = verify_ip_config(adapter_name, ip, Some(netmask), gateway).await?;
+  if diffs.is_empty() {
     Ok(format!("成功设置静态IP: {} {} {} {}", adapter_name, ip, netmask, gateway.unwrap_or("")))
   } else {
-    Err(format!("{} {} -> {}", adapter_name, ip, res).into())
+    Err(format!("{} {} 配置未生效 -> {}", adapter_name, ip, serde_json::to_string(&diffs)?).into())
   }
 }
 
 /// 设置静态DNS
 pub async fn set_static_dns(adapter_name: &str, primary_dns: &str, secondary_dns: Option<&str>) -> AnyResult<String> {
-  let name = &format!("\"{}\"", adapter_name);
+  let servers = [Some(primary_dns), secondary_dns]
+    .into_iter()
+    .flatten()
+    .filter(|v| !v.is_empty())
+    .map(|v| v.parse::<IpAddr>().map_err(|_| format!("无效DNS地址: {}", v)))
+    .collect::<Result<Vec<_>, _>>()?;
+  set_dns_servers(adapter_name, &servers).await
+}
+
+/// 设置静态DNS列表, 按顺序生效, 支持 IPv4/IPv6
+pub async fn set_dns_servers(adapter_name: &str, servers: &[IpAddr]) -> AnyResult<String> {
+  if servers.is_empty() {
+    return Err(format!("{} DNS列表为空", adapter_name).into());
+  }
+  // 只有 netsh 需要引号, 其他命令直接传参
+  let name = &if cfg!(target_os = "windows") {
+    format!("\"{}\"", adapter_name)
+  } else {
+    adapter_name.to_string()
+  };
+  let list: Vec<String> = servers.iter().map(|v| v.to_string()).collect();
 
   let res = match () {
     _ if cfg!(target_os = "windows") => {
-      exec_cmd(Cmd::new("netsh").args(["interface", "ip", "set", "dns", name, "static", primary_dns])).await?;
-      if let Some(secondary) = secondary_dns {
-        exec_cmd(Cmd::new("netsh").args(["interface", "ip", "add", "dns", name, secondary, "index=2"])).await?
-      } else {
-        String::new()
+      let mut res = String::new();
+      // IPv4/IPv6 分别设置, 未指定的协议保持不变
+      for (family, is_v4) in [("ipv4", true), ("ipv6", false)] {
+        for (i, dns) in servers.iter().filter(|v| v.is_ipv4() == is_v4).enumerate() {
+          let dns = &dns.to_string();
+          res = match i {
+            0 => exec_cmd(Cmd::new("netsh").args(["interface", family, "set", "dns", name, "static", dns])).await?,
+            _ => exec_cmd(Cmd::new("netsh").args(["interface", family, "add", "dns", name, dns, &format!("index={}", i + 1)])).await?,
+          };
+        }
       }
+      res
     }
-    _ if cfg!(target_os = "macos") => {
-      let dns_servers = match secondary_dns {
-        Some(secondary) => format!("{} {}", primary_dns, secondary),
-        None => primary_dns.to_string(),
-      };
-      exec_cmd(Cmd::new("sudo").args(["networksetup", "-setdnsservers", name, &dns_servers])).await?
-    }
+    _ if cfg!(target_os = "macos") => exec_cmd(sudo().args(["networksetup", "-setdnsservers", name]).args(&list)).await?,
     _ => {
-      let mut content = format!("nameserver {}\n", primary_dns);
-      if let Some(secondary) = secondary_dns {
-        content.push_str(&format!("nameserver {}\n", secondary));
+      let managed = configure_managed(adapter_name, ConfigPart::Dns, |config| {
+        config.dns_mode = AddressMode::Static;
+        config.dns = list.clone();
+      })
+      .await?;
+      match managed {
+        Some(res) => res,
+        None => {
+          let content: String = list.iter().map(|v| format!("nameserver {}\n", v)).collect();
+          std::fs::write("/etc/resolv.conf", content)?;
+          String::new()
+        }
       }
-      std::fs::write("/etc/resolv.conf", content)?;
-      String::new()
     }
   };
   crate::dp(&res);
   // 验证配置是否生效
-  if verify_dns_config(adapter_name, primary_dns, secondary_dns).await? {
-    Ok(format!("成功设置静态DNS: {} {} {}", adapter_name, primary_dns, secondary_dns.unwrap_or("")))
+  let diffs = poll_config(adapter_name, VERIFY_TIMEOUT, get_interfaces, |iface| diff_dns_servers(iface, servers)).await;
+  if diffs.is_empty() {
+    Ok(format!("成功设置静态DNS: {} {}", adapter_name, list.join(" ")))
   } else {
-    Err(format!("{} {} -> {}", adapter_name, primary_dns, res).into())
+    Err(format!("{} {} 配置未生效 -> {}", adapter_name, list.join(","), serde_json::to_string(&diffs)?).into())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::os_more::net_interface::{Gateway, InterfaceStatus, InterfaceType, MacAddr};
+  use std::cell::Cell;
+
+  fn iface(ip: [u8; 4], prefix: u8, gateway: Option<[u8; 4]>, dns: &[&str]) -> Interface {
+    Interface {
+      index: 2,
+      name: "enp3s0".into(),
+      friendly_name: "enp3s0".into(),
+      description: "igc".into(),
+      if_type: InterfaceType::Ethernet,
+      mac_addr: MacAddr::zero(),
+      ipv4: vec![Ipv4Net::new(Ipv4Addr::from(ip), prefix)],
+      ipv6: vec![],
+      flags: 0,
+      transmit_speed: 0,
+      receive_speed: 0,
+      gateway: gateway.map(|v| Gateway {
+        mac_addr: MacAddr::zero(),
+        ip_addr: IpAddr::V4(Ipv4Addr::from(v)),
+      }),
+      oper_status: InterfaceStatus::Up,
+      dns_servers: dns.iter().map(|v| v.parse().unwrap()).collect(),
+      stats: None,
+      topology: Default::default(),
+    }
+  }
+
+  #[test]
+  fn test_diff_config() {
+    let x = iface([192, 168, 1, 20], 24, Some([192, 168, 1, 1]), &["223.5.5.5", "114.114.114.114"]);
+    assert!(diff_ip_config(&x, "192.168.1.20", Some("255.255.255.0"), Some("192.168.1.1")).is_empty());
+    assert!(diff_ip_config(&x, "192.168.1.20", Some("24"), None).is_empty());
+
+    let diffs = diff_ip_config(&x, "192.168.1.30", Some("255.255.0.0"), Some("192.168.1.254"));
+    assert_eq!(
+      diffs,
+      [
+        ConfigDiff::new("ip", "192.168.1.30", "192.168.1.20/24"),
+        ConfigDiff::new("gateway", "192.168.1.254", "192.168.1.1")
+      ]
+    );
+    assert_eq!(diff_ip_config(&x, "192.168.1.20", Some("255.255.0.0"), None)[0].field, "prefix");
+
+    assert!(diff_dns_config(&x, "223.5.5.5", Some("114.114.114.114")).is_empty());
+    assert!(diff_dns_config(&x, "223.5.5.5", None).is_empty());
+    assert_eq!(
+      diff_dns_config(&x, "114.114.114.114", Some("223.5.5.5")),
+      [ConfigDiff::new("dns", "114.114.114.114,223.5.5.5", "223.5.5.5,114.114.114.114")]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_poll_config() {
+    // 第二次读取时配置生效
+    let polls = Cell::new(0);
+    let load = || {
+      polls.set(polls.get() + 1);
+      let ip = if polls.get() < 2 { [0, 0, 0, 0] } else { [10, 0, 0, 2] };
+      vec![iface(ip, 8, None, &[])]
+    };
+    let diffs = poll_config("enp3s0", Duration::from_secs(5), load, |x| diff_ip_config(x, "10.0.0.2", Some("8"), None)).await;
+    assert!(diffs.is_empty() && polls.get() == 2);
+
+    let diffs = poll_config("eth9", Duration::ZERO, load, |x| diff_ip_config(x, "10.0.0.2", None, None)).await;
+    assert_eq!(diffs, [ConfigDiff::new("interface", "eth9", "未找到")]);
+  }
+
+  #[cfg(target_os = "linux")]
+  #[tokio::test]
+  async fn test_verify_loopback() {
+    assert!(verify_ip_config("lo", "127.0.0.1", Some("255.0.0.0"), None).await.unwrap().is_empty());
   }
 }
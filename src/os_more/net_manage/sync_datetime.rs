@@ -39,7 +39,12 @@ async fn ensure_windows_time_service() -> e_utils::AnyResult<()> {
 
 pub async fn sync_datetime(server: &str, is_register: bool) -> e_utils::AnyResult<String> {
   #[cfg(not(windows))]
-  return Err("不支持的系统".into());
+  {
+    let _ = is_register;
+    let res = super::sntp(server).await?;
+    super::step_clock(res.offset_ms)?;
+    Ok(format!("时间同步{server}成功, {res}"))
+  }
   #[cfg(windows)]
   {
     if is_register {
@@ -0,0 +1,439 @@
+//! SMBIOS/DMI 表解析
+//!
+//! Linux 读取 /sys/firmware/dmi/tables/{smbios_entry_point,DMI}
+use serde::{Deserialize, Serialize};
+
+/// 入口点路径
+pub const ENTRY_POINT_PATH: &str = "/sys/firmware/dmi/tables/smbios_entry_point";
+/// 结构表路径
+pub const DMI_TABLE_PATH: &str = "/sys/firmware/dmi/tables/DMI";
+
+/// 结构类型
+pub mod kind {
+  pub const BIOS: u8 = 0;
+  pub const SYSTEM: u8 = 1;
+  pub const BASEBOARD: u8 = 2;
+  pub const CHASSIS: u8 = 3;
+  pub const PROCESSOR: u8 = 4;
+  pub const MEMORY_DEVICE: u8 = 17;
+  pub const END_OF_TABLE: u8 = 127;
+}
+
+/// 入口点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryPoint {
+  pub major: u8,
+  pub minor: u8,
+  /// 结构表长度 (3.x 为最大长度)
+  pub table_len: u32,
+  /// 结构表物理地址
+  pub table_addr: u64,
+}
+impl EntryPoint {
+  /// 解析 "_SM_" (2.x) 或 "_SM3_" (3.x) 入口点
+  pub fn parse(data: &[u8]) -> e_utils::Result<Self> {
+    if data.starts_with(b"_SM3_") && data.len() >= 0x18 {
+      Ok(Self {
+        major: data[0x07],
+        minor: data[0x08],
+        table_len: u32::from_le_bytes(data[0x0C..0x10].try_into()?),
+        table_addr: u64::from_le_bytes(data[0x10..0x18].try_into()?),
+      })
+    } else if data.starts_with(b"_SM_") && data.len() >= 0x1F {
+      Ok(Self {
+        major: data[0x06],
+        minor: data[0x07],
+        table_len: u16::from_le_bytes(data[0x16..0x18].try_into()?) as u32,
+        table_addr: u32::from_le_bytes(data[0x18..0x1C].try_into()?) as u64,
+      })
+    } else {
+      Err("SMBIOS 入口点格式错误".into())
+    }
+  }
+}
+
+/// 原始结构
+#[derive(Debug, Clone)]
+pub struct Structure<'a> {
+  pub kind: u8,
+  pub handle: u16,
+  /// 格式化区 (含 4 字节头)
+  pub formatted: &'a [u8],
+  /// 字符串区
+  pub strings: Vec<&'a [u8]>,
+}
+impl Structure<'_> {
+  pub fn byte(&self, offset: usize) -> Option<u8> {
+    self.formatted.get(offset).copied()
+  }
+  pub fn word(&self, offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(self.formatted.get(offset..offset + 2)?.try_into().ok()?))
+  }
+  pub fn dword(&self, offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(self.formatted.get(offset..offset + 4)?.try_into().ok()?))
+  }
+  /// 读取字符串 偏移处为 1 开始的序号, 0 表示无
+  pub fn string(&self, offset: usize) -> String {
+    self
+      .byte(offset)
+      .filter(|&i| i > 0)
+      .and_then(|i| self.strings.get(i as usize - 1))
+      .map(|s| String::from_utf8_lossy(s).trim().to_string())
+      .unwrap_or_default()
+  }
+}
+
+/// 遍历结构表
+pub fn structures(table: &[u8]) -> Vec<Structure<'_>> {
+  let mut res = vec![];
+  let mut pos = 0;
+  while pos + 4 <= table.len() {
+    let kind = table[pos];
+    let len = table[pos + 1] as usize;
+    let handle = u16::from_le_bytes([table[pos + 2], table[pos + 3]]);
+    if len < 4 || pos + len > table.len() {
+      break;
+    }
+    let formatted = &table[pos..pos + len];
+    // 字符串区以两个 0 结束
+    let rest = &table[pos + len..];
+    let end = match rest.windows(2).position(|w| w == [0, 0]) {
+      Some(end) => end,
+      None => break,
+    };
+    let strings = rest[..end].split(|&b| b == 0).filter(|s| !s.is_empty()).collect();
+    res.push(Structure {
+      kind,
+      handle,
+      formatted,
+      strings,
+    });
+    pos += len + end + 2;
+    if kind == kind::END_OF_TABLE {
+      break;
+    }
+  }
+  res
+}
+
+/// BIOS 信息 (类型 0)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BiosInfo {
+  pub vendor: String,
+  pub version: String,
+  pub release_date: String,
+  /// ROM 大小 (KB)
+  pub rom_size_kb: u32,
+}
+/// 系统信息 (类型 1)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemInfo {
+  pub manufacturer: String,
+  pub product_name: String,
+  pub version: String,
+  pub serial_number: String,
+  pub uuid: String,
+  pub sku_number: String,
+  pub family: String,
+}
+/// 主板信息 (类型 2)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoardInfo {
+  pub manufacturer: String,
+  pub product: String,
+  pub version: String,
+  pub serial_number: String,
+  pub asset_tag: String,
+}
+/// 机箱信息 (类型 3)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChassisInfo {
+  pub manufacturer: String,
+  pub chassis_type: String,
+  pub version: String,
+  pub serial_number: String,
+  pub asset_tag: String,
+}
+/// 处理器信息 (类型 4)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProcessorInfo {
+  pub socket: String,
+  pub manufacturer: String,
+  pub version: String,
+  /// 最大频率 (MHz)
+  pub max_speed: u16,
+  /// 当前频率 (MHz)
+  pub current_speed: u16,
+  pub core_count: u16,
+  pub thread_count: u16,
+}
+/// 内存设备 (类型 17)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryDevice {
+  /// 插槽 如 DIMM_A1
+  pub locator: String,
+  pub bank_locator: String,
+  /// 大小 (MB), 0 表示空插槽
+  pub size_mb: u64,
+  pub memory_type: String,
+  /// 标称速率 (MT/s)
+  pub speed: u16,
+  /// 配置速率 (MT/s)
+  pub configured_speed: u16,
+  pub manufacturer: String,
+  pub serial_number: String,
+  pub part_number: String,
+}
+
+/// 机箱类型名称
+pub fn chassis_type_name(v: u8) -> &'static str {
+  match v & 0x7F {
+    0x03 => "Desktop",
+    0x04 => "Low Profile Desktop",
+    0x06 => "Mini Tower",
+    0x07 => "Tower",
+    0x08 => "Portable",
+    0x09 => "Laptop",
+    0x0A => "Notebook",
+    0x0D => "All in One",
+    0x0E => "Sub Notebook",
+    0x11 => "Main Server Chassis",
+    0x17 => "Rack Mount Chassis",
+    0x1E => "Tablet",
+    0x1F => "Convertible",
+    0x23 => "Mini PC",
+    0x24 => "Stick PC",
+    0x01 => "Other",
+    _ => "Unknown",
+  }
+}
+
+/// 内存类型名称
+pub fn memory_type_name(v: u8) -> &'static str {
+  match v {
+    0x12 => "DDR",
+    0x13 => "DDR2",
+    0x18 => "DDR3",
+    0x1A => "DDR4",
+    0x1B => "LPDDR",
+    0x1C => "LPDDR2",
+    0x1D => "LPDDR3",
+    0x1E => "LPDDR4",
+    0x22 => "DDR5",
+    0x23 => "LPDDR5",
+    0x01 => "Other",
+    _ => "Unknown",
+  }
+}
+
+/// 解析后的 SMBIOS 信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Smbios {
+  /// 版本 如 3.3
+  pub version: String,
+  pub bios: Option<BiosInfo>,
+  pub system: Option<SystemInfo>,
+  pub baseboard: Option<BoardInfo>,
+  pub chassis: Option<ChassisInfo>,
+  pub processors: Vec<ProcessorInfo>,
+  pub memory_devices: Vec<MemoryDevice>,
+}
+impl Smbios {
+  /// 从入口点与结构表解析, 入口点可选
+  pub fn parse(entry_point: Option<&[u8]>, table: &[u8]) -> e_utils::Result<Self> {
+    let entry = entry_point.map(EntryPoint::parse).transpose()?;
+    let table = match &entry {
+      Some(entry) if (entry.table_len as usize) < table.len() => &table[..entry.table_len as usize],
+      _ => table,
+    };
+    let structures = structures(table);
+    if structures.is_empty() {
+      return Err("SMBIOS 结构表为空".into());
+    }
+    let mut res = Self {
+      version: entry.map(|e| format!("{}.{}", e.major, e.minor)).unwrap_or_default(),
+      ..Default::default()
+    };
+    for s in &structures {
+      match s.kind {
+        kind::BIOS if res.bios.is_none() => res.bios = Some(parse_bios(s)),
+        kind::SYSTEM if res.system.is_none() => res.system = Some(parse_system(s)),
+        kind::BASEBOARD if res.baseboard.is_none() => res.baseboard = Some(parse_board(s)),
+        kind::CHASSIS if res.chassis.is_none() => res.chassis = Some(parse_chassis(s)),
+        kind::PROCESSOR => res.processors.push(parse_processor(s)),
+        kind::MEMORY_DEVICE => res.memory_devices.push(parse_memory_device(s)),
+        _ => {}
+      }
+    }
+    Ok(res)
+  }
+  /// 从目录读取 smbios_entry_point 与 DMI, 用于测试目录
+  pub fn from_dir(dir: impl AsRef<std::path::Path>) -> e_utils::Result<Self> {
+    let dir = dir.as_ref();
+    let table = std::fs::read(dir.join("DMI")).map_err(|e| format!("SMBIOS {} 读取失败: {}", dir.join("DMI").display(), e))?;
+    let entry = std::fs::read(dir.join("smbios_entry_point")).ok();
+    Self::parse(entry.as_deref(), &table)
+  }
+  /// 读取本机 SMBIOS
+  #[cfg(target_os = "linux")]
+  pub fn read() -> e_utils::Result<Self> {
+    let table = std::fs::read(DMI_TABLE_PATH).map_err(|e| format!("SMBIOS {} 读取失败: {}", DMI_TABLE_PATH, e))?;
+    let entry = std::fs::read(ENTRY_POINT_PATH).ok();
+    Self::parse(entry.as_deref(), &table)
+  }
+  /// 已安装的内存条
+  pub fn memory_modules(&self) -> Vec<&MemoryDevice> {
+    self.memory_devices.iter().filter(|m| m.size_mb > 0).collect()
+  }
+}
+
+fn parse_bios(s: &Structure) -> BiosInfo {
+  BiosInfo {
+    vendor: s.string(0x04),
+    version: s.string(0x05),
+    release_date: s.string(0x08),
+    rom_size_kb: match s.byte(0x09) {
+      // 16MB 及以上使用扩展字段, 高两位为单位 MB/GB
+      Some(0xFF) => s.word(0x18).map_or(0, |v| {
+        let size = (v & 0x3FFF) as u32 * 1024;
+        if v >> 14 == 1 {
+          size * 1024
+        } else {
+          size
+        }
+      }),
+      v => v.map_or(0, |v| (v as u32 + 1) * 64),
+    },
+  }
+}
+
+fn parse_system(s: &Structure) -> SystemInfo {
+  let uuid = s
+    .formatted
+    .get(0x08..0x18)
+    .filter(|b| !b.iter().all(|&v| v == 0) && !b.iter().all(|&v| v == 0xFF))
+    .map(|b| {
+      // 前三段为小端序
+      format!(
+        "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6], b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]
+      )
+    })
+    .unwrap_or_default();
+  SystemInfo {
+    manufacturer: s.string(0x04),
+    product_name: s.string(0x05),
+    version: s.string(0x06),
+    serial_number: s.string(0x07),
+    uuid,
+    sku_number: s.string(0x19),
+    family: s.string(0x1A),
+  }
+}
+
+fn parse_board(s: &Structure) -> BoardInfo {
+  BoardInfo {
+    manufacturer: s.string(0x04),
+    product: s.string(0x05),
+    version: s.string(0x06),
+    serial_number: s.string(0x07),
+    asset_tag: s.string(0x08),
+  }
+}
+
+fn parse_chassis(s: &Structure) -> ChassisInfo {
+  ChassisInfo {
+    manufacturer: s.string(0x04),
+    chassis_type: chassis_type_name(s.byte(0x05).unwrap_or(0)).to_string(),
+    version: s.string(0x06),
+    serial_number: s.string(0x07),
+    asset_tag: s.string(0x08),
+  }
+}
+
+fn parse_processor(s: &Structure) -> ProcessorInfo {
+  // 3.0 起 0xFF 表示使用扩展字段
+  let count = |offset: usize, ext: usize| match s.byte(offset) {
+    Some(0xFF) => s.word(ext).unwrap_or(0xFF),
+    v => v.unwrap_or(0) as u16,
+  };
+  ProcessorInfo {
+    socket: s.string(0x04),
+    manufacturer: s.string(0x07),
+    version: s.string(0x10),
+    max_speed: s.word(0x14).unwrap_or(0),
+    current_speed: s.word(0x16).unwrap_or(0),
+    core_count: count(0x23, 0x2A),
+    thread_count: count(0x25, 0x2E),
+  }
+}
+
+fn parse_memory_device(s: &Structure) -> MemoryDevice {
+  let size_mb = match s.word(0x0C).unwrap_or(0) {
+    0 | 0xFFFF => 0,
+    // 扩展大小字段
+    0x7FFF => s.dword(0x1C).map_or(0, |v| (v & 0x7FFF_FFFF) as u64),
+    // 最高位为 1 时单位为 KB
+    v if v & 0x8000 != 0 => (v & 0x7FFF) as u64 / 1024,
+    v => v as u64,
+  };
+  MemoryDevice {
+    locator: s.string(0x10),
+    bank_locator: s.string(0x11),
+    size_mb,
+    memory_type: memory_type_name(s.byte(0x12).unwrap_or(0)).to_string(),
+    speed: s.word(0x15).unwrap_or(0),
+    configured_speed: s.word(0x20).unwrap_or(0),
+    manufacturer: s.string(0x17),
+    serial_number: s.string(0x18),
+    part_number: s.string(0x1A),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/os_more/smbios/fixtures");
+
+  #[test]
+  fn test_smbios_desktop_fixture() {
+    let smbios = Smbios::from_dir(format!("{FIXTURES}/desktop")).unwrap();
+    assert_eq!(smbios.version, "3.3");
+    let bios = smbios.bios.as_ref().unwrap();
+    assert_eq!(
+      (bios.vendor.as_str(), bios.version.as_str(), bios.rom_size_kb),
+      ("American Megatrends Inc.", "1402", 16384)
+    );
+    let system = smbios.system.as_ref().unwrap();
+    assert_eq!(system.uuid, "03000200-0400-0500-0006-000700080009");
+    assert_eq!(smbios.baseboard.as_ref().unwrap().product, "PRIME Z790-P");
+    assert_eq!(smbios.chassis.as_ref().unwrap().chassis_type, "Desktop");
+    assert_eq!(smbios.processors[0].core_count, 24);
+    assert_eq!(smbios.processors[0].thread_count, 32);
+
+    assert_eq!(smbios.memory_devices.len(), 4);
+    let modules = smbios.memory_modules();
+    assert_eq!(modules.len(), 2);
+    assert_eq!(modules[0].locator, "DIMM_A2");
+    assert_eq!((modules[0].size_mb, modules[0].speed, modules[0].memory_type.as_str()), (16384, 5600, "DDR5"));
+    assert_eq!((modules[1].manufacturer.as_str(), modules[1].part_number.as_str()), ("Kingston", "KF556C40-16"));
+  }
+
+  #[test]
+  fn test_smbios_legacy_fixture() {
+    let smbios = Smbios::from_dir(format!("{FIXTURES}/legacy")).unwrap();
+    assert_eq!(smbios.version, "2.8");
+    assert_eq!(smbios.system.as_ref().unwrap().uuid, "");
+    assert_eq!(smbios.chassis.as_ref().unwrap().chassis_type, "Rack Mount Chassis");
+    assert_eq!(smbios.processors.len(), 2);
+    let modules = smbios.memory_modules();
+    assert_eq!(modules.len(), 1);
+    assert_eq!((modules[0].size_mb, modules[0].memory_type.as_str()), (65536, "DDR4"));
+    assert_eq!(modules[0].part_number, "M393A8G40AB2-CWE");
+    // 无入口点时也能解析
+    let table = std::fs::read(format!("{FIXTURES}/legacy/DMI")).unwrap();
+    assert_eq!(Smbios::parse(None, &table).unwrap().memory_devices.len(), 2);
+    assert!(Smbios::parse(Some(b"bad"), &table).is_err());
+  }
+}
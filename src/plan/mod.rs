@@ -0,0 +1,499 @@
+//! 测试计划: 按顺序执行文件中描述的多个 API 调用, 汇总为一份报告
+//!
+//! `hw plan run <file> [key=value ...]`, 文件按扩展名解析为 TOML, YAML (`.yaml`/`.yml`) 或 JSON:
+//! ```toml
+//! name = "Station A"
+//! timeout = 120
+//! report = "report.json"
+//! [vars]
+//! clock = 3000
+//!
+//! [[steps]]
+//! name = "CPU 主频"
+//! api = "OS"
+//! task = "check"
+//! args = ["CPU", "Clock"]
+//! command = [10, "${clock}", 2000, 80]
+//! criteria = ["warmup=2", "p95=4000"]
+//! retries = 1
+//!
+//! [[steps]]
+//! api = "OSMore"
+//! task = "CpuName"
+//! expect = { contains = ["Intel"] }
+//! save = "cpu_name"
+//! continue_on_fail = true
+//! ```
+use crate::cli::{Opts, OptsApi};
+use e_utils::AnyResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+  collections::BTreeMap,
+  future::Future,
+  path::Path,
+  pin::Pin,
+  time::{Duration, Instant},
+};
+
+/// 步骤超时后留给清理的时间
+const TEARDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// 结果期望, 所有条件均需满足
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Expect {
+  /// 结果完全一致
+  pub equals: Option<String>,
+  /// 结果包含所有片段
+  pub contains: Vec<String>,
+  /// 结果不包含任一片段
+  pub not_contains: Vec<String>,
+  /// 结果按数字解析后不低于
+  pub min: Option<f64>,
+  /// 结果按数字解析后不高于
+  pub max: Option<f64>,
+}
+impl Expect {
+  pub fn check(&self, output: &str) -> Result<(), String> {
+    if let Some(v) = self.equals.as_ref().filter(|v| v.as_str() != output) {
+      return Err(format!("结果 {} 不等于 {}", output, v));
+    }
+    if let Some(v) = self.contains.iter().find(|v| !output.contains(v.as_str())) {
+      return Err(format!("结果不包含 {}", v));
+    }
+    if let Some(v) = self.not_contains.iter().find(|v| output.contains(v.as_str())) {
+      return Err(format!("结果包含 {}", v));
+    }
+    if self.min.is_some() || self.max.is_some() {
+      let value = output.trim().parse::<f64>().map_err(|_| format!("结果 {} 不是数字", output))?;
+      if let Some(min) = self.min.filter(|min| value < *min) {
+        return Err(format!("结果 {} 低于 {}", value, min));
+      }
+      if let Some(max) = self.max.filter(|max| value > *max) {
+        return Err(format!("结果 {} 高于 {}", value, max));
+      }
+    }
+    Ok(())
+  }
+}
+
+/// 计划步骤, 对应一次 `hw --api <api> --task <task> --args <args> -- <command>` 调用
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlanStep {
+  /// 默认 `<api> <task>`
+  pub name: String,
+  pub api: String,
+  pub task: String,
+  pub args: Vec<Value>,
+  pub command: Vec<Value>,
+  pub filter: Vec<Value>,
+  pub full: bool,
+  /// 传感器步骤的采样记录文件
+  pub record: Option<String>,
+  /// 传感器步骤的判定条件, 见 [`crate::api_test::Criteria`]
+  pub criteria: Vec<Value>,
+  pub expect: Expect,
+  /// 超时秒数, 默认使用计划的 timeout
+  pub timeout: Option<u64>,
+  /// 失败后重试次数
+  pub retries: u32,
+  /// 失败后继续执行后续步骤, 默认使用计划的 continue_on_fail
+  pub continue_on_fail: Option<bool>,
+  /// 成功时将结果保存为变量
+  pub save: Option<String>,
+}
+
+/// 测试计划
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Plan {
+  pub name: String,
+  /// 变量, 步骤中以 `${name}` 引用
+  pub vars: BTreeMap<String, Value>,
+  /// 默认步骤超时秒数, 0 不限制
+  pub timeout: u64,
+  pub continue_on_fail: bool,
+  /// 报告输出文件 (JSON)
+  pub report: Option<String>,
+  pub steps: Vec<PlanStep>,
+}
+impl Plan {
+  /// 按扩展名解析: `.yaml`/`.yml` 为 YAML, `.json` 为 JSON, 其余为 TOML
+  pub fn parse(content: &str, ext: &str) -> AnyResult<Self> {
+    let plan: Self = match ext.to_ascii_lowercase().as_str() {
+      "yaml" | "yml" => serde_yaml_ng::from_str(content)?,
+      "json" => serde_json::from_str(content)?,
+      _ => toml::from_str(content)?,
+    };
+    if let Some((i, _)) = plan.steps.iter().enumerate().find(|(_, v)| v.api.is_empty()) {
+      return Err(format!("第 {} 步缺少 api", i + 1).into());
+    }
+    Ok(plan)
+  }
+  pub fn load(path: impl AsRef<Path>) -> AnyResult<Self> {
+    let path = path.as_ref();
+    let ext = path.extension().and_then(|v| v.to_str()).unwrap_or_default();
+    Self::parse(&std::fs::read_to_string(path)?, ext)
+  }
+}
+
+/// 变量值转为字符串, 字符串不带引号
+fn value_str(v: &Value) -> String {
+  match v {
+    Value::String(s) => s.clone(),
+    Value::Null => String::new(),
+    v => v.to_string(),
+  }
+}
+
+/// 替换 `${name}` 变量
+pub fn expand_vars(s: &str, vars: &BTreeMap<String, Value>) -> Result<String, String> {
+  let mut res = String::new();
+  let mut rest = s;
+  while let Some(start) = rest.find("${") {
+    let end = rest[start..].find('}').ok_or_else(|| format!("变量未闭合: {}", s))? + start;
+    let name = &rest[start + 2..end];
+    let value = vars.get(name).ok_or_else(|| format!("未定义变量: {}", name))?;
+    res.push_str(&rest[..start]);
+    res.push_str(&value_str(value));
+    rest = &rest[end + 1..];
+  }
+  res.push_str(rest);
+  Ok(res)
+}
+
+/// 步骤状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, strum::Display)]
+pub enum StepStatus {
+  PASS,
+  FAIL,
+  SKIP,
+}
+
+/// 步骤报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepReport {
+  pub name: String,
+  pub api: String,
+  pub task: String,
+  pub status: StepStatus,
+  pub attempts: u32,
+  pub elapsed_ms: u64,
+  pub output: String,
+  pub error: Option<String>,
+}
+
+/// 计划报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanReport {
+  pub name: String,
+  pub res: String,
+  pub passed: usize,
+  pub failed: usize,
+  pub skipped: usize,
+  pub elapsed_ms: u64,
+  pub steps: Vec<StepReport>,
+}
+impl PlanReport {
+  pub fn is_pass(&self) -> bool {
+    self.failed == 0
+  }
+}
+
+/// 单次调用的执行器, 默认为 [`crate::cli::api`]
+pub type StepRunner<'a> = dyn FnMut(Opts) -> Pin<Box<dyn Future<Output = AnyResult<String>>>> + 'a;
+
+/// 展开变量并生成调用参数
+pub fn step_opts(step: &PlanStep, vars: &BTreeMap<String, Value>) -> AnyResult<Opts> {
+  let expand = |list: &[Value]| list.iter().map(|v| expand_vars(&value_str(v), vars)).collect::<Result<Vec<_>, _>>();
+  let api = expand_vars(&step.api, vars)?;
+  if api.eq_ignore_ascii_case("Plan") {
+    return Err("计划步骤不能嵌套 Plan".into());
+  }
+  Ok(Opts {
+    api: api.parse::<OptsApi>()?,
+    task: expand_vars(&step.task, vars)?,
+    full: step.full,
+    filter: expand(&step.filter)?,
+    args: expand(&step.args)?,
+    record: step.record.as_deref().map(|v| expand_vars(v, vars)).transpose()?,
+    criteria: expand(&step.criteria)?,
+    command: expand(&step.command)?,
+    ..Default::default()
+  })
+}
+
+/// 按顺序执行计划
+pub async fn run_plan(plan: &Plan, mut vars: BTreeMap<String, Value>, runner: &mut StepRunner<'_>) -> PlanReport {
+  let start = Instant::now();
+  let mut steps = vec![];
+  let mut stopped = false;
+  for (i, step) in plan.steps.iter().enumerate() {
+    let name = Some(step.name.clone())
+      .filter(|v| !v.is_empty())
+      .unwrap_or_else(|| format!("{} {}", step.api, step.task).trim().to_string());
+    let mut report = StepReport {
+      name,
+      api: step.api.clone(),
+      task: step.task.clone(),
+      status: StepStatus::SKIP,
+      attempts: 0,
+      elapsed_ms: 0,
+      output: String::new(),
+      error: None,
+    };
+    if stopped {
+      steps.push(report);
+      continue;
+    }
+    crate::p(format!("\n=== 步骤 {}/{}: {} ===", i + 1, plan.steps.len(), report.name));
+    let step_start = Instant::now();
+    let timeout = step.timeout.unwrap_or(plan.timeout);
+    let res: Result<String, String> = async {
+      let mut last = String::new();
+      for attempt in 0..=step.retries {
+        report.attempts = attempt + 1;
+        if attempt > 0 {
+          crate::wp(format!("{} 第 {} 次重试: {}", report.name, attempt, last));
+        }
+        let mut opts = step_opts(step, &vars).map_err(|e| e.to_string())?;
+        let res = if timeout > 0 {
+          // 传感器测试在截止时间自行停止并清理, 外层超时多留清理时间; 其他调用不读取截止时间, 按原超时
+          opts.deadline = Some(Instant::now() + Duration::from_secs(timeout));
+          let grace = if opts.api.is_backend() { TEARDOWN_GRACE } else { Duration::ZERO };
+          tokio::time::timeout(Duration::from_secs(timeout) + grace, runner(opts))
+            .await
+            .unwrap_or_else(|_| {
+              crate::api_test::LOAD_CONTROLLER.stop_running();
+              Err(format!("超时 {} 秒", timeout).into())
+            })
+        } else {
+          runner(opts).await
+        };
+        match res.map_err(|e| e.to_string()).and_then(|v| step.expect.check(&v).map(|_| v)) {
+          Ok(v) => return Ok(v),
+          Err(e) => last = e,
+        }
+      }
+      Err(last)
+    }
+    .await;
+    report.elapsed_ms = step_start.elapsed().as_millis() as u64;
+    match res {
+      Ok(output) => {
+        if let Some(save) = &step.save {
+          vars.insert(save.clone(), Value::String(output.clone()));
+        }
+        report.status = StepStatus::PASS;
+        report.output = output;
+      }
+      Err(e) => {
+        crate::ep(format!("{} 失败: {}", report.name, e));
+        report.status = StepStatus::FAIL;
+        report.error = Some(e);
+        stopped = !step.continue_on_fail.unwrap_or(plan.continue_on_fail);
+      }
+    }
+    steps.push(report);
+  }
+  let count = |status: StepStatus| steps.iter().filter(|v| v.status == status).count();
+  let (passed, failed, skipped) = (count(StepStatus::PASS), count(StepStatus::FAIL), count(StepStatus::SKIP));
+  PlanReport {
+    name: plan.name.clone(),
+    res: if failed == 0 { "PASS" } else { "FAIL" }.to_string(),
+    passed,
+    failed,
+    skipped,
+    elapsed_ms: start.elapsed().as_millis() as u64,
+    steps,
+  }
+}
+
+/// 报告摘要
+pub fn report_summary(report: &PlanReport) -> String {
+  let mut s = format!("\n=== 计划 {} -> {} ===\n", report.name, report.res);
+  for (i, step) in report.steps.iter().enumerate() {
+    s.push_str(&format!(
+      "{:>3}. [{}] {} ({} 次, {} ms){}\n",
+      i + 1,
+      step.status,
+      step.name,
+      step.attempts,
+      step.elapsed_ms,
+      step.error.as_ref().map(|e| format!(" - {}", e)).unwrap_or_default()
+    ));
+  }
+  s.push_str(&format!(
+    "通过: {} 失败: {} 跳过: {} 耗时: {} ms\n====================",
+    report.passed, report.failed, report.skipped, report.elapsed_ms
+  ));
+  s
+}
+
+/// 读取计划文件, 命令行 `key=value` 覆盖文件中的同名变量
+fn load_plan(args: &[&str]) -> AnyResult<(Plan, BTreeMap<String, Value>)> {
+  let plan = Plan::load(args.first().ok_or("缺少计划文件")?)?;
+  let mut vars = plan.vars.clone();
+  for kv in args.iter().skip(1) {
+    let (k, v) = kv.split_once('=').ok_or_else(|| format!("变量格式应为 key=value: {}", kv))?;
+    vars.insert(k.to_string(), Value::String(v.to_string()));
+  }
+  Ok((plan, vars))
+}
+
+/// # Plan 接口
+///
+/// - `run <file> [key=value ...]` 执行计划
+/// - `check <file> [key=value ...]` 只检查文件与变量, 不执行
+pub async fn plan_query<T: AsRef<str>>(task: &str, args: &[T], is_full: bool) -> AnyResult<String> {
+  let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+  match task {
+    "run" => {
+      let (plan, vars) = load_plan(&args)?;
+      let mut runner =
+        |opts: Opts| -> Pin<Box<dyn Future<Output = AnyResult<String>>>> { Box::pin(async move { crate::cli::api(opts, &mut Value::Null).await }) };
+      let report = run_plan(&plan, vars, &mut runner).await;
+      crate::p(report_summary(&report));
+      let json = if is_full {
+        serde_json::to_string_pretty(&report)?
+      } else {
+        serde_json::to_string(&report)?
+      };
+      if let Some(file) = &plan.report {
+        std::fs::write(file, &json)?;
+      }
+      if report.is_pass() {
+        Ok(json)
+      } else {
+        Err(json.into())
+      }
+    }
+    "check" => {
+      let (plan, mut vars) = load_plan(&args)?;
+      for (i, step) in plan.steps.iter().enumerate() {
+        step_opts(step, &vars).map_err(|e| format!("第 {} 步: {}", i + 1, e))?;
+        if let Some(save) = &step.save {
+          vars.entry(save.clone()).or_insert(Value::Null);
+        }
+      }
+      Ok(format!("PASS {} 共 {} 步", plan.name, plan.steps.len()))
+    }
+    _ => Err("Not supported".into()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PLAN: &str = r#"
+name = "Station A"
+continue_on_fail = false
+[vars]
+clock = 3000
+nic = "eth0"
+
+[[steps]]
+name = "clock"
+api = "OS"
+task = "check"
+args = ["CPU", "Clock"]
+command = [10, "${clock}", 2000, 80]
+
+[[steps]]
+api = "OSMore"
+task = "CpuName"
+expect = { contains = ["Intel"] }
+save = "cpu"
+continue_on_fail = true
+retries = 2
+
+[[steps]]
+api = "OSMore"
+task = "NetInterface"
+args = ["check-mac", "${nic}"]
+filter = ["${cpu}"]
+
+[[steps]]
+api = "Disk"
+task = "check-load"
+args = [10, 90]
+"#;
+
+  #[test]
+  fn test_parse_plan() {
+    let plan = Plan::parse(PLAN, "toml").unwrap();
+    assert_eq!((plan.name.as_str(), plan.steps.len()), ("Station A", 4));
+    let opts = step_opts(&plan.steps[0], &plan.vars).unwrap();
+    assert_eq!(
+      (opts.api, opts.command),
+      (OptsApi::OS, vec!["10".into(), "3000".into(), "2000".into(), "80".into()])
+    );
+    assert_eq!(step_opts(&plan.steps[2], &plan.vars).unwrap_err().to_string(), "未定义变量: cpu");
+    let mut step = plan.steps[0].clone();
+    step.api = "Nope".into();
+    assert!(step_opts(&step, &plan.vars).is_err());
+    let yaml = "name: y\nsteps:\n  - api: Disk\n    task: check-load\n    args: [10, 90]\n";
+    assert_eq!(Plan::parse(yaml, "yml").unwrap().steps[0].args, vec![Value::from(10), Value::from(90)]);
+    assert!(Plan::parse("[[steps]]\ntask = \"x\"\n", "toml").is_err());
+    assert!(expand_vars("${x", &plan.vars).is_err());
+  }
+
+  #[test]
+  fn test_expect() {
+    let expect = Expect {
+      contains: vec!["Intel".into()],
+      ..Default::default()
+    };
+    assert!(expect.check("Intel(R) Core").is_ok());
+    assert!(expect.check("AMD Ryzen").is_err());
+    let range = Expect {
+      min: Some(10.0),
+      max: Some(20.0),
+      ..Default::default()
+    };
+    assert!(range.check("15").is_ok());
+    assert_eq!(range.check("25"), Err("结果 25 高于 20".into()));
+    assert!(range.check("abc").is_err());
+  }
+
+  #[tokio::test]
+  async fn test_run_plan() {
+    let plan = Plan::parse(PLAN, "toml").unwrap();
+    let mut calls = vec![];
+    let mut runner = |opts: Opts| -> Pin<Box<dyn Future<Output = AnyResult<String>>>> {
+      calls.push(format!("{} {} {:?}", opts.api, opts.task, opts.filter));
+      let res = match opts.task.as_str() {
+        "CpuName" => Ok("AMD Ryzen".to_string()),
+        "NetInterface" => Err("FAIL eth0".into()),
+        _ => Ok("PASS".to_string()),
+      };
+      Box::pin(async move { res })
+    };
+    let report = run_plan(&plan, plan.vars.clone(), &mut runner).await;
+    let status: Vec<StepStatus> = report.steps.iter().map(|v| v.status).collect();
+    assert_eq!(status, vec![StepStatus::PASS, StepStatus::FAIL, StepStatus::FAIL, StepStatus::SKIP]);
+    assert_eq!((report.steps[1].attempts, report.res.as_str()), (3, "FAIL"));
+    // 第 2 步未保存 cpu 变量, 第 3 步展开失败且未调用
+    assert_eq!(report.steps[2].error.as_deref(), Some("未定义变量: cpu"));
+    assert_eq!(calls.len(), 4);
+    assert!(report_summary(&report).contains("通过: 1 失败: 2 跳过: 1"));
+  }
+
+  #[tokio::test]
+  async fn test_run_plan_timeout() {
+    let plan = Plan::parse("timeout = 1\n[[steps]]\napi = \"Disk\"\ntask = \"check-load\"\n", "toml").unwrap();
+    let mut runner = |_: Opts| -> Pin<Box<dyn Future<Output = AnyResult<String>>>> {
+      Box::pin(async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok("PASS".to_string())
+      })
+    };
+    // 非传感器步骤不读取截止时间, 按配置的超时结束
+    let report = run_plan(&plan, BTreeMap::new(), &mut runner).await;
+    assert_eq!(report.steps[0].error.as_deref(), Some("超时 1 秒"));
+    assert!(report.elapsed_ms < 3000);
+  }
+}
@@ -0,0 +1,312 @@
+#![allow(non_snake_case)]
+use crate::{
+  api_test::{BackendFuture, HardwareType, Sensor, SensorBackend, SensorType},
+  common::sysfs,
+  wmic::HardwareMonitor,
+};
+use e_utils::AnyResult;
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  time::{Duration, Instant},
+};
+
+/// 首次查询时两次采样的间隔
+pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+/// powercap 控制类型前缀 (AMD 在较新内核中同样以 intel-rapl 注册)
+const DOMAIN_PREFIXES: [&str; 3] = ["intel-rapl:", "intel-rapl-mmio:", "amd-rapl:"];
+
+/// RAPL 能量域
+#[derive(Debug, Clone)]
+pub struct RaplDomain {
+  /// 目录名 如 intel-rapl:0:1
+  pub id: String,
+  /// 域名称 如 package-0, core, uncore, dram
+  pub name: String,
+  /// 所属 package 名称
+  pub package: String,
+  /// 域目录
+  pub path: PathBuf,
+  /// 计数器最大值 (微焦), 用于处理回绕
+  pub max_energy_range_uj: u64,
+  /// 硬件类型
+  pub hw_type: HardwareType,
+}
+impl RaplDomain {
+  /// 根据域名称推断硬件类型
+  pub fn parse_hw_type(name: &str) -> HardwareType {
+    match name {
+      "dram" => HardwareType::RAM,
+      "psys" => HardwareType::Mainboard,
+      _ => HardwareType::CPU,
+    }
+  }
+  /// 读取能量计数器 (微焦)
+  pub fn energy_uj(&self) -> Option<u64> {
+    sysfs::read_num(self.path.join("energy_uj"))
+  }
+  /// 显示名称 如 package-0 core
+  pub fn label(&self) -> String {
+    if self.name == self.package {
+      self.name.clone()
+    } else {
+      format!("{} {}", self.package, self.name)
+    }
+  }
+}
+
+/// 两次采样间的能量差, 计数器回绕时以 max_energy_range_uj 补齐
+pub fn energy_delta(prev: u64, cur: u64, max_energy_range_uj: u64) -> u64 {
+  if cur >= prev {
+    cur - prev
+  } else {
+    max_energy_range_uj.saturating_sub(prev) + cur
+  }
+}
+
+/// 单次采样
+#[derive(Debug, Clone, Copy)]
+struct RaplSample {
+  energy_uj: u64,
+  at: Instant,
+  min: f64,
+  max: f64,
+}
+
+/// Linux RAPL 功率 (/sys/class/powercap)
+#[derive(Debug, Clone)]
+pub struct RAPL {
+  root: PathBuf,
+  samples: HashMap<String, RaplSample>,
+}
+impl RAPL {
+  /// 指定 sysfs 根目录, 用于测试目录
+  pub fn with_root(root: impl Into<PathBuf>) -> Self {
+    Self {
+      root: root.into(),
+      samples: HashMap::new(),
+    }
+  }
+  /// 获取根目录
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+  /// 枚举能量域
+  pub fn domains(&self) -> AnyResult<Vec<RaplDomain>> {
+    let mut domains = vec![];
+    for entry in fs::read_dir(&self.root)?.flatten() {
+      let id = match entry.file_name().into_string() {
+        Ok(id) if DOMAIN_PREFIXES.iter().any(|p| id.starts_with(p)) => id,
+        _ => continue,
+      };
+      let path = entry.path();
+      let name = match sysfs::read_str(path.join("name")) {
+        Some(name) => name,
+        None => continue,
+      };
+      // 子域 intel-rapl:0:0 的 package 为 intel-rapl:0
+      let package = match id.rsplit_once(':') {
+        Some((parent, _)) if parent.contains(':') => sysfs::read_str(self.root.join(parent).join("name")).unwrap_or_else(|| parent.to_string()),
+        _ => name.clone(),
+      };
+      domains.push(RaplDomain {
+        hw_type: RaplDomain::parse_hw_type(&name),
+        max_energy_range_uj: sysfs::read_num(path.join("max_energy_range_uj")).unwrap_or(u64::MAX),
+        id,
+        name,
+        package,
+        path,
+      });
+    }
+    domains.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(domains)
+  }
+  /// 采样一次并与上次采样计算功率 (瓦), 没有上次采样的域返回 None
+  fn sample(&mut self, domain: &RaplDomain) -> Option<f64> {
+    let energy_uj = match domain.energy_uj() {
+      Some(v) => v,
+      None => {
+        crate::dp(format!("RAPL {} energy_uj 读取失败", domain.id));
+        return None;
+      }
+    };
+    let at = Instant::now();
+    let prev = self.samples.get(&domain.id).copied();
+    let watts = prev.and_then(|prev| {
+      let secs = at.duration_since(prev.at).as_secs_f64();
+      (secs > 0.0).then(|| energy_delta(prev.energy_uj, energy_uj, domain.max_energy_range_uj) as f64 / 1_000_000.0 / secs)
+    });
+    let (min, max) = match (prev, watts) {
+      (Some(prev), Some(w)) if prev.max >= prev.min => (prev.min.min(w), prev.max.max(w)),
+      (_, Some(w)) => (w, w),
+      (Some(prev), None) => (prev.min, prev.max),
+      (None, None) => (f64::MAX, f64::MIN),
+    };
+    self.samples.insert(domain.id.clone(), RaplSample { energy_uj, at, min, max });
+    watts
+  }
+  /// 查询
+  pub fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> AnyResult<Vec<Sensor>> {
+    let domains = self.query_domains(&hw_type, &sensor_type)?;
+    if self.warm_up(&domains) {
+      std::thread::sleep(SAMPLE_INTERVAL);
+    }
+    self.read_sensors(&hw_type, &sensor_type, &domains)
+  }
+  /// 异步查询, 首次采样的等待不阻塞运行时
+  pub async fn a_query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> AnyResult<Vec<Sensor>> {
+    let domains = self.query_domains(&hw_type, &sensor_type)?;
+    if self.warm_up(&domains) {
+      tokio::time::sleep(SAMPLE_INTERVAL).await;
+    }
+    self.read_sensors(&hw_type, &sensor_type, &domains)
+  }
+  /// 查询涉及的能量域
+  fn query_domains(&self, hw_type: &HardwareType, sensor_type: &SensorType) -> AnyResult<Vec<RaplDomain>> {
+    if *sensor_type != SensorType::ALL && *sensor_type != SensorType::Power {
+      return Err(format!("RAPL Sensor type {} is not supported", sensor_type).into());
+    }
+    let hw_types = hw_type.clone().all();
+    Ok(self.domains()?.into_iter().filter(|d| hw_types.contains(&d.hw_type)).collect())
+  }
+  /// 首次采样未采样过的域, 返回是否需要等待一个间隔再采样
+  fn warm_up(&mut self, domains: &[RaplDomain]) -> bool {
+    let fresh: Vec<&RaplDomain> = domains.iter().filter(|d| !self.samples.contains_key(&d.id)).collect();
+    fresh.iter().for_each(|d| {
+      self.sample(d);
+    });
+    !fresh.is_empty()
+  }
+  /// 再次采样并生成传感器
+  fn read_sensors(&mut self, hw_type: &HardwareType, sensor_type: &SensorType, domains: &[RaplDomain]) -> AnyResult<Vec<Sensor>> {
+    let mut sensors = vec![];
+    for (index, domain) in domains.iter().enumerate() {
+      let Some(value) = self.sample(domain) else {
+        continue;
+      };
+      let sample = self.samples[&domain.id];
+      sensors.push(Sensor {
+        Name: domain.label(),
+        Identifier: format!("/powercap/{}", domain.id),
+        _SensorType: SensorType::Power.to_string(),
+        SensorType: SensorType::Power,
+        Parent: domain.hw_type.to_string(),
+        Value: value,
+        Min: sample.min,
+        Max: sample.max,
+        Index: index as i32,
+        data: format!("{:.1}", value),
+      });
+    }
+    if sensors.is_empty() {
+      return Err(format!("RAPL No sensors found for {} {}", hw_type, sensor_type).into());
+    }
+    Ok(sensors)
+  }
+}
+
+impl SensorBackend for RAPL {
+  fn name(&self) -> &str {
+    "RAPL"
+  }
+  fn ready(&mut self) -> BackendFuture<'_, ()> {
+    crate::api_test::wait_ready::<Self>(10)
+  }
+  fn query(&mut self, hw_type: HardwareType, sensor_type: SensorType) -> BackendFuture<'_, Vec<Sensor>> {
+    Box::pin(async move { self.a_query(hw_type, sensor_type).await })
+  }
+}
+
+impl HardwareMonitor for RAPL {
+  type HWType = HardwareType;
+  type SensorType = Sensor;
+  const CON_QUERY: &'static str = "/sys/class/powercap";
+  const HW_QUERY: &'static str = "";
+  const SENSOR_QUERY: &'static str = "";
+  fn new() -> AnyResult<Self> {
+    Ok(Self::with_root(Self::CON_QUERY))
+  }
+  fn test(count: u64) -> AnyResult<()> {
+    for i in 1..=count {
+      match Self::new().and_then(|mut api| api.query(HardwareType::ALL, SensorType::Power)) {
+        Ok(_) => {
+          crate::dp("RAPL ready");
+          return Ok(());
+        }
+        Err(e) => crate::wp(e.to_string()),
+      }
+      crate::dp(format!("Loading... ({}%/{}%)", i, count));
+      std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+    Err("RAPL load timeout".into())
+  }
+  fn stop() -> AnyResult<()> {
+    Ok(())
+  }
+  fn clean() -> AnyResult<()> {
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::sysfs_tree;
+
+  #[test]
+  fn test_rapl_fixture() {
+    let root = sysfs_tree(
+      "rapl",
+      &[
+        ("intel-rapl/enabled", "1\n"),
+        ("intel-rapl:0/name", "package-0\n"),
+        ("intel-rapl:0/energy_uj", "1000000\n"),
+        ("intel-rapl:0/max_energy_range_uj", "262143328850\n"),
+        ("intel-rapl:0:0/name", "core\n"),
+        ("intel-rapl:0:0/energy_uj", "500000\n"),
+        ("intel-rapl:0:0/max_energy_range_uj", "262143328850\n"),
+        ("intel-rapl:0:1/name", "dram\n"),
+        ("intel-rapl:0:1/energy_uj", "262143000000\n"),
+        ("intel-rapl:0:1/max_energy_range_uj", "262143328850\n"),
+      ],
+    );
+    let mut api = RAPL::with_root(&root);
+
+    let domains = api.domains().unwrap();
+    let labels: Vec<String> = domains.iter().map(|d| d.label()).collect();
+    assert_eq!(labels, ["package-0", "package-0 core", "package-0 dram"]);
+    assert_eq!(domains[2].hw_type, HardwareType::RAM);
+
+    assert_eq!(energy_delta(100, 350, 1000), 250);
+    assert_eq!(energy_delta(900, 50, 1000), 150);
+
+    // 计数器未变化时功率为 0
+    let all = api.query(HardwareType::ALL, SensorType::Power).unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!((all[0].Value, all[0].Parent.as_str()), (0.0, "CPU"));
+
+    fs::write(root.join("intel-rapl:0/energy_uj"), "31000000\n").unwrap();
+    fs::write(root.join("intel-rapl:0:1/energy_uj"), "1000000\n").unwrap();
+    let all = api.query(HardwareType::ALL, SensorType::ALL).unwrap();
+    assert_eq!(all.len(), 3);
+    assert!(all[0].Value > 0.0 && all[0].Max == all[0].Value && all[0].Min == 0.0);
+    assert!(all[2].Value > 0.0, "dram 计数器回绕");
+    assert_eq!(api.query(HardwareType::CPU, SensorType::Power).unwrap().len(), 2);
+    assert!(api.query(HardwareType::CPU, SensorType::Temperature).is_err());
+
+    fs::remove_dir_all(root).unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_rapl_async_query() {
+    let root = sysfs_tree("rapl-async", &[("intel-rapl:0/name", "package-0\n"), ("intel-rapl:0/energy_uj", "1000000\n")]);
+    let mut api = RAPL::with_root(&root);
+    // 首次查询的采样间隔在运行时中等待, 不阻塞其他任务
+    let ticker = tokio::spawn(tokio::time::sleep(SAMPLE_INTERVAL / 2));
+    let all = api.a_query(HardwareType::CPU, SensorType::Power).await.unwrap();
+    assert!(ticker.is_finished());
+    assert_eq!(all[0].Value, 0.0);
+    fs::remove_dir_all(root).unwrap();
+  }
+}